@@ -0,0 +1,270 @@
+//! Achievements unlocked by what happens in the tanks.
+//!
+//! Unlocks and the best progress towards each achievement live in a
+//! per-user [`Profile`], `<config dir>/profile.json`, kept apart from tank
+//! saves so they carry over between games.
+
+use crate::aquarium::Aquarium;
+use crate::config;
+use crate::mode::GameMode;
+use crate::species;
+use crate::tank::{DeathCause, Tank, TickSummary};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Ticks without a death for [`Achievement::NoDeaths`].
+const DEATH_FREE_TICKS: u64 = 1000;
+/// Generation needed for [`Achievement::Generations`].
+const GENERATIONS: u64 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Achievement {
+    FirstBirth,
+    Generations,
+    FullLifespan,
+    NoDeaths,
+    EverySpecies,
+    ScenarioWon,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstBirth,
+        Achievement::Generations,
+        Achievement::FullLifespan,
+        Achievement::NoDeaths,
+        Achievement::EverySpecies,
+        Achievement::ScenarioWon,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::FirstBirth => "First Fry",
+            Achievement::Generations => "Dynasty",
+            Achievement::FullLifespan => "Golden Years",
+            Achievement::NoDeaths => "Clean Record",
+            Achievement::EverySpecies => "Collector",
+            Achievement::ScenarioWon => "Challenger",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Achievement::FirstBirth => "A fish is born".to_string(),
+            Achievement::Generations => format!("Breed a generation {} fish", GENERATIONS),
+            Achievement::FullLifespan => "A fish lives out its species' full lifespan".to_string(),
+            Achievement::NoDeaths => format!("A stocked tank goes {} ticks without a death", DEATH_FREE_TICKS),
+            Achievement::EverySpecies => "Own a fish of every species at once".to_string(),
+            Achievement::ScenarioWon => "Win a scenario".to_string(),
+        }
+    }
+
+    /// Progress needed to unlock.
+    pub fn target(&self) -> u64 {
+        match self {
+            Achievement::Generations => GENERATIONS,
+            Achievement::FullLifespan => 100,
+            Achievement::NoDeaths => DEATH_FREE_TICKS,
+            Achievement::EverySpecies => species::get_all_species().len() as u64,
+            Achievement::FirstBirth | Achievement::ScenarioWon => 1,
+        }
+    }
+
+    /// How far `tank` has come after the tick in `summary`. Lifespan
+    /// progress is the oldest fish's share of its lifespan, in percent.
+    fn progress(&self, aquarium: &Aquarium, tank: &Tank, summary: &TickSummary) -> u64 {
+        match self {
+            Achievement::FirstBirth => summary.births().count() as u64,
+            Achievement::Generations => tank.fish.iter().map(|f| f.generation as u64).max().unwrap_or(0),
+            Achievement::FullLifespan => {
+                if summary.deaths().any(|death| death.cause == DeathCause::OldAge) {
+                    return 100;
+                }
+                tank.fish
+                    .iter()
+                    .filter_map(|f| species::get_species(&f.species).map(|s| f.age_fraction(&s)))
+                    .map(|share| ((share * 100.0) as u64).min(99))
+                    .max()
+                    .unwrap_or(0)
+            }
+            Achievement::NoDeaths if tank.fish.is_empty() => 0,
+            Achievement::NoDeaths => {
+                // Counted from the later of the last death and the tank
+                // being stocked, so an empty tank doesn't bank time
+                let stocked = tank.stocked_since.unwrap_or(tank.tick_count);
+                let since = tank.lineage.last_death().unwrap_or(0).max(stocked);
+                tank.tick_count.saturating_sub(since)
+            }
+            Achievement::EverySpecies => {
+                let owned: BTreeSet<&str> = aquarium
+                    .tanks
+                    .iter()
+                    .flat_map(|tank| &tank.fish)
+                    .map(|f| f.species.as_str())
+                    .collect();
+                owned.len() as u64
+            }
+            Achievement::ScenarioWon => match &aquarium.mode {
+                GameMode::Scenario(run) => run.outcome.as_ref().is_some_and(|o| o.won) as u64,
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// When and where an achievement was unlocked.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Unlock {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub tank: String,
+    pub tick: u64,
+}
+
+impl Unlock {
+    /// "2026-10-19, Display tick 5230".
+    pub fn describe(&self) -> String {
+        format!("{}, {} tick {}", date(self.time), self.tank, self.tick)
+    }
+}
+
+/// A player's achievements across every game.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Profile {
+    #[serde(default)]
+    pub unlocked: BTreeMap<Achievement, Unlock>,
+    /// Best progress seen towards each locked achievement.
+    #[serde(default)]
+    pub best: BTreeMap<Achievement, u64>,
+}
+
+impl Profile {
+    /// Read a profile. A missing file is a fresh profile.
+    pub fn load(path: &Path) -> io::Result<Profile> {
+        if !path.exists() {
+            return Ok(Profile::default());
+        }
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+        })
+    }
+
+    /// Write the profile, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains_key(&achievement)
+    }
+
+    /// Best progress so far, capped at the target.
+    pub fn progress(&self, achievement: Achievement) -> u64 {
+        if self.is_unlocked(achievement) {
+            return achievement.target();
+        }
+        self.best.get(&achievement).copied().unwrap_or(0).min(achievement.target())
+    }
+
+    /// Update progress from one aquarium tick, with one summary per tank,
+    /// and return what was newly unlocked.
+    pub fn check(&mut self, aquarium: &Aquarium, summaries: &[TickSummary]) -> Vec<Achievement> {
+        let mut unlocked = Vec::new();
+        for (tank, summary) in aquarium.tanks.iter().zip(summaries) {
+            for achievement in Achievement::ALL {
+                if self.is_unlocked(achievement) {
+                    continue;
+                }
+                let progress = achievement.progress(aquarium, tank, summary);
+                let best = self.best.entry(achievement).or_default();
+                *best = (*best).max(progress);
+                if progress >= achievement.target() {
+                    self.best.remove(&achievement);
+                    self.unlocked.insert(achievement, Unlock {
+                        time: now(),
+                        tank: tank.name.clone(),
+                        tick: tank.tick_count,
+                    });
+                    unlocked.push(achievement);
+                }
+            }
+        }
+        unlocked
+    }
+}
+
+/// `<config dir>/profile.json`.
+pub fn profile_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("profile.json"))
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// `YYYY-MM-DD` for a Unix time, in UTC.
+fn date(time: u64) -> String {
+    // Days to a civil date, after Howard Hinnant's `civil_from_days`
+    let days = (time / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fish::Fish;
+
+    fn no_deaths(tank: &Tank) -> u64 {
+        let aquarium = Aquarium::single(tank.clone());
+        Achievement::NoDeaths.progress(&aquarium, tank, &TickSummary::default())
+    }
+
+    #[test]
+    fn an_empty_tank_banks_no_death_free_time() {
+        let mut tank = Tank::with_seed(60, 20, 1);
+        for _ in 0..1500 {
+            tank.tick();
+        }
+        tank.add_fish(Fish::new("Goldfish".to_string(), (5, 5)));
+        assert_eq!(no_deaths(&tank), 0);
+        tank.tick();
+        assert_eq!(tank.stocked_since, Some(1501));
+        for _ in 0..200 {
+            tank.tick();
+        }
+        assert_eq!(no_deaths(&tank), 200);
+    }
+
+    #[test]
+    fn death_free_time_restarts_when_a_tank_empties() {
+        let mut tank = Tank::with_seed(60, 20, 1);
+        let fish = Fish::new("Goldfish".to_string(), (5, 5));
+        let id = fish.id;
+        tank.add_fish(fish);
+        for _ in 0..300 {
+            tank.tick();
+        }
+        assert_eq!(no_deaths(&tank), 299);
+        tank.remove_fish(id);
+        tank.tick();
+        assert_eq!(tank.stocked_since, None);
+        assert_eq!(no_deaths(&tank), 0);
+    }
+}
@@ -0,0 +1,1321 @@
+//! Interactive application state.
+//!
+//! [`App`] owns the tank plus everything the TUI needs between frames. Input
+//! goes through [`App::handle_event`], time through [`App::on_tick`], and
+//! `ui::render` only reads from it, so the whole loop can be driven with
+//! synthetic events.
+
+use crate::achievements::Profile;
+use crate::aquarium::Aquarium;
+use crate::economy::{self, ShopItem};
+use crate::events::{Severity, TankEvent, TimedEvent};
+use crate::fish::Fish;
+use crate::keymap::{Action, Context, Keymap};
+use crate::lineage::{Branch, TreeRow};
+use crate::maintenance::{FreshWater, Task};
+use crate::mode::GameMode;
+use crate::roster::RosterState;
+use crate::species::{self, Species};
+use crate::sprite::Sprites;
+use crate::tank::{self, Tank, TickSummary};
+use crate::theme::Theme;
+use crate::ui;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Ticks between health samples shown in the inspector sparkline.
+const HEALTH_SAMPLE_INTERVAL: u64 = 10;
+/// Samples kept per fish.
+const HEALTH_HISTORY_LEN: usize = 60;
+/// How long a toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+/// Cells the camera moves per pan, across and down.
+const PAN_STEP: (u16, u16) = (8, 3);
+/// Toasts shown at once; older ones are dropped early.
+const MAX_TOASTS: usize = 4;
+/// Events kept in the log screen.
+const EVENT_LOG_LEN: usize = 500;
+/// Time windows on the stats screen: number of samples (all when `None`)
+/// and a label.
+pub const STATS_WINDOWS: [(Option<usize>, &str); 3] = [
+    (Some(20), "last 20 samples"),
+    (Some(100), "last 100 samples"),
+    (None, "all history"),
+];
+/// Generations shown each way in the family tree.
+const LINEAGE_DEPTH: usize = 4;
+/// Longest name the rename prompt accepts.
+const MAX_NAME_LEN: usize = 24;
+
+/// A screen on the stack. The bottom is always `Tank`; modals are pushed on
+/// top and only the topmost screen receives input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Screen {
+    Tank,
+    SpeciesPicker,
+    Help,
+    Roster,
+    /// Text prompt for renaming the selected fish.
+    Rename,
+    Stats,
+    EventLog,
+    /// Family tree of one fish.
+    Lineage,
+    /// Choice of tank to net the selected fish into.
+    NetPicker,
+    /// Buying and selling in economy mode.
+    Shop,
+    /// Choosing how to play a new game.
+    ModeSelect,
+    /// How a scenario ended.
+    Results,
+    Achievements,
+    /// Water changes and other care for the tank on screen.
+    Maintenance,
+}
+
+/// A short message shown over the tank until it expires.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub text: String,
+    pub severity: Severity,
+    pub until: Instant,
+}
+
+impl Screen {
+    /// Which keymap context handles input on this screen.
+    pub fn context(&self) -> Context {
+        match self {
+            Screen::Tank => Context::Normal,
+            Screen::SpeciesPicker => Context::Picker,
+            Screen::Help => Context::Help,
+            Screen::Roster => Context::Roster,
+            Screen::Rename => Context::Prompt,
+            Screen::Stats => Context::Stats,
+            Screen::EventLog => Context::EventLog,
+            Screen::Lineage => Context::Lineage,
+            Screen::NetPicker => Context::Picker,
+            Screen::Shop => Context::Shop,
+            Screen::ModeSelect => Context::ModeSelect,
+            Screen::Results => Context::Help,
+            Screen::Achievements => Context::Help,
+            Screen::Maintenance => Context::Maintenance,
+        }
+    }
+}
+
+pub struct App {
+    /// Every tank; the active one is on screen and takes input.
+    pub aquarium: Aquarium,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub sprites: Sprites,
+    /// Sprite animations run off the time since this instant.
+    pub started: Instant,
+    pub species: Vec<Species>,
+    screens: Vec<Screen>,
+    pub paused: bool,
+    /// Selected fish, by id so births and deaths don't shift it.
+    pub selected_fish: Option<Uuid>,
+    pub should_quit: bool,
+    /// When on, clicking empty water drops a food pellet there.
+    pub feed_mode: bool,
+    pub roster: RosterState,
+    /// Index into `STATS_WINDOWS`.
+    pub stats_window: usize,
+    /// Text being typed into a prompt.
+    pub input: String,
+    /// Active toasts, oldest first.
+    pub toasts: VecDeque<Toast>,
+    /// Recent events, oldest first.
+    pub event_log: VecDeque<TimedEvent>,
+    /// Events scrolled past at the top of the log, newest first.
+    pub log_scroll: usize,
+    /// Favorites already reported as unwell, so each spell is reported once.
+    favorite_alerts: HashSet<Uuid>,
+    /// Fish the family tree is centred on.
+    pub lineage_focus: Option<Uuid>,
+    /// Earlier focuses, for going back.
+    lineage_history: Vec<Uuid>,
+    /// Highlighted row of the family tree.
+    pub lineage_cursor: usize,
+    /// Where exported files are written.
+    pub export_dir: PathBuf,
+    /// Status glyphs next to sprites and a full description of the
+    /// selected fish.
+    pub accessible: bool,
+    /// Plain-text tank view in place of the picture; events are listed as
+    /// lines instead of toasts.
+    pub text_view: bool,
+    /// Tank cell shown in the top-left corner of the view.
+    pub camera: (u16, u16),
+    /// Keep the camera centred on the selected fish.
+    pub follow: bool,
+    /// Show the whole-tank overview when the tank doesn't fit the view.
+    pub minimap: bool,
+    /// Side panel with details of the selected fish.
+    pub inspector_open: bool,
+    /// Recent health samples per fish, oldest first, for the inspector.
+    pub health_history: HashMap<Uuid, VecDeque<u64>>,
+    /// First visible line of the help overlay.
+    pub help_scroll: u16,
+    /// Terminal area, used to map mouse positions onto the tank.
+    area: Rect,
+    /// Fish being dragged and the column offset it was grabbed at.
+    dragging: Option<(Uuid, u16)>,
+    /// What the shop sells, in display order.
+    pub shop_items: Vec<ShopItem>,
+    /// Highlighted row of the shop.
+    pub shop_cursor: usize,
+    /// Modes offered by the new-game selector.
+    pub mode_choices: Vec<GameMode>,
+    pub mode_cursor: usize,
+    /// Highlighted row of the maintenance screen: a task, then the tap
+    /// water temperature.
+    pub maintenance_cursor: usize,
+    /// Tap water used for water changes and top-offs.
+    pub fresh_water: FreshWater,
+    /// Achievements unlocked across every game.
+    pub profile: Profile,
+    /// Where the profile is saved on each unlock; not saved when `None`.
+    pub profile_path: Option<PathBuf>,
+}
+
+impl App {
+    pub fn new(aquarium: Aquarium, keymap: Keymap) -> Self {
+        App {
+            aquarium,
+            keymap,
+            theme: Theme::default(),
+            sprites: Sprites::builtin(),
+            started: Instant::now(),
+            species: species::get_all_species(),
+            screens: vec![Screen::Tank],
+            paused: false,
+            selected_fish: None,
+            should_quit: false,
+            feed_mode: false,
+            roster: RosterState::default(),
+            stats_window: 0,
+            input: String::new(),
+            toasts: VecDeque::new(),
+            event_log: VecDeque::new(),
+            log_scroll: 0,
+            favorite_alerts: HashSet::new(),
+            lineage_focus: None,
+            lineage_history: Vec::new(),
+            lineage_cursor: 0,
+            export_dir: PathBuf::from("."),
+            accessible: false,
+            text_view: false,
+            camera: (0, 0),
+            follow: false,
+            minimap: true,
+            inspector_open: false,
+            health_history: HashMap::new(),
+            help_scroll: 0,
+            area: Rect::default(),
+            dragging: None,
+            shop_items: ShopItem::catalogue(),
+            shop_cursor: 0,
+            mode_choices: Vec::new(),
+            mode_cursor: 0,
+            maintenance_cursor: 0,
+            fresh_water: FreshWater::default(),
+            profile: Profile::default(),
+            profile_path: None,
+        }
+    }
+
+    /// The tank on screen.
+    pub fn tank(&self) -> &Tank {
+        self.aquarium.active()
+    }
+
+    pub fn tank_mut(&mut self) -> &mut Tank {
+        self.aquarium.active_mut()
+    }
+
+    /// The screen currently receiving input.
+    pub fn screen(&self) -> Screen {
+        *self.screens.last().unwrap_or(&Screen::Tank)
+    }
+
+    /// The whole stack, bottom first, for drawing modals in order.
+    pub fn screens(&self) -> &[Screen] {
+        &self.screens
+    }
+
+    pub fn push_screen(&mut self, screen: Screen) {
+        self.screens.push(screen);
+    }
+
+    /// Close the topmost modal. The tank screen is never popped.
+    pub fn pop_screen(&mut self) {
+        if self.screens.len() > 1 {
+            self.screens.pop();
+        }
+    }
+
+    /// The selected fish's id, if it is still in the tank.
+    pub fn selected_fish_id(&self) -> Option<Uuid> {
+        self.selected_fish
+            .filter(|id| self.tank().get_fish(*id).is_some())
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Resize(width, height) => self.resize(*width, *height),
+            Event::Key(key) => self.handle_key(key),
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => {}
+        }
+        self.update_camera();
+    }
+
+    pub fn handle_key(&mut self, key: &KeyEvent) {
+        // Ignore release/repeat events on terminals that report them
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        if self.screen() == Screen::Rename {
+            self.handle_prompt_key(key);
+            return;
+        }
+        if self.screen() == Screen::Roster && self.roster.searching {
+            self.handle_search_key(key);
+            return;
+        }
+        if let Some(action) = self.keymap.lookup(self.screen().context(), key) {
+            self.apply(action);
+        }
+    }
+
+    /// Perform an action in the context of the current screen.
+    pub fn apply(&mut self, action: Action) {
+        match (self.screen(), action) {
+            (Screen::Help, Action::Close)
+            | (Screen::SpeciesPicker, Action::Close)
+            | (Screen::NetPicker, Action::Close)
+            | (Screen::Results, Action::Close)
+            | (Screen::Achievements, Action::Close) => self.pop_screen(),
+            (Screen::ModeSelect, action) => self.apply_mode_select(action),
+            (Screen::SpeciesPicker, Action::PickSpecies(n)) => {
+                if let Some(species) = self.species.get(n) {
+                    let fish = Fish::new(species.name.clone(), self.arrival_position());
+                    self.tank_mut().add_fish(fish);
+                    self.pop_screen();
+                }
+            }
+            (Screen::NetPicker, Action::PickSpecies(n)) => {
+                if let Some(&to) = self.net_targets().get(n) {
+                    self.pop_screen();
+                    self.net_selected(to);
+                }
+            }
+            (Screen::Shop, action) => self.apply_shop(action),
+            (Screen::Maintenance, action) => self.apply_maintenance(action),
+            (Screen::Roster, action) => self.apply_roster(action),
+            (Screen::Stats, Action::CycleWindow) => {
+                self.stats_window = (self.stats_window + 1) % STATS_WINDOWS.len()
+            }
+            (Screen::Stats, Action::Close) => self.pop_screen(),
+            (Screen::EventLog, Action::CursorUp) => self.log_scroll = self.log_scroll.saturating_sub(1),
+            (Screen::EventLog, Action::CursorDown) => self.scroll_log_down(),
+            (Screen::EventLog, Action::Close) => self.pop_screen(),
+            (Screen::Lineage, action) => self.apply_lineage(action),
+            (Screen::Tank, action) => self.apply_normal(action),
+            _ => {}
+        }
+    }
+
+    fn apply_normal(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::TogglePause => self.paused = !self.paused,
+            Action::OpenHelp => {
+                self.help_scroll = 0;
+                self.push_screen(Screen::Help);
+            }
+            Action::ToggleFeedMode => self.feed_mode = !self.feed_mode,
+            Action::ToggleInspector => self.inspector_open = !self.inspector_open,
+            Action::ToggleAccessible => self.accessible = !self.accessible,
+            Action::ToggleTextView => self.text_view = !self.text_view,
+            Action::PanLeft => self.pan(-(PAN_STEP.0 as i32), 0),
+            Action::PanRight => self.pan(PAN_STEP.0 as i32, 0),
+            Action::PanUp => self.pan(0, -(PAN_STEP.1 as i32)),
+            Action::PanDown => self.pan(0, PAN_STEP.1 as i32),
+            Action::ToggleFollow => {
+                if self.follow {
+                    self.follow = false;
+                } else if self.selected_fish_id().is_some() {
+                    self.follow = true;
+                    self.update_camera();
+                } else {
+                    self.notify("Select a fish to follow".to_string(), Severity::Info);
+                }
+            }
+            Action::ToggleMinimap => self.minimap = !self.minimap,
+            Action::FeedSelected => {
+                if let Some(fish_id) = self.selected_fish_id()
+                    && self.use_food(1)
+                {
+                    self.tank_mut().feed_fish(fish_id);
+                }
+            }
+            Action::Rename => {
+                if let Some(fish) = self.selected_fish_id().and_then(|id| self.tank().get_fish(id)) {
+                    self.input = fish.name.clone().unwrap_or_default();
+                    self.push_screen(Screen::Rename);
+                }
+            }
+            Action::ToggleFavorite => {
+                if let Some(fish_id) = self.selected_fish_id() {
+                    self.tank_mut().toggle_favorite(fish_id);
+                }
+            }
+            Action::AddFish => {
+                if self.aquarium.wallet.is_some() {
+                    self.open_shop();
+                } else if self.god_actions() {
+                    self.push_screen(Screen::SpeciesPicker);
+                }
+            }
+            Action::OpenShop => {
+                if self.aquarium.wallet.is_some() {
+                    self.open_shop();
+                } else {
+                    self.notify("The shop is only open in modes with money".to_string(), Severity::Info);
+                }
+            }
+            Action::Feed => {
+                let portions = self.tank().fish.len() as u32;
+                if self.use_food(portions) {
+                    self.tank_mut().feed();
+                }
+            }
+            Action::SpeedUp => {
+                self.tank_mut().increase_speed();
+                self.aquarium.set_speed(self.tank().simulation_speed);
+            }
+            Action::SpeedDown => {
+                self.tank_mut().decrease_speed();
+                self.aquarium.set_speed(self.tank().simulation_speed);
+            }
+            Action::ToggleIncidents if self.aquarium.mode.random_events() => self.notify(
+                format!("{} always has random incidents", self.aquarium.mode.label()),
+                Severity::Info,
+            ),
+            Action::ToggleIncidents => {
+                let tank = self.tank_mut();
+                tank.incidents.settings.enabled = !tank.incidents.settings.enabled;
+                let state = if tank.incidents.settings.enabled { "on" } else { "off" };
+                let text = format!("Random incidents {} in {}", state, tank.name);
+                self.notify(text, Severity::Info);
+            }
+            Action::NextTank => self.switch_tank(1),
+            Action::PrevTank => self.switch_tank(-1),
+            Action::NetFish => {
+                if self.selected_fish_id().is_none() {
+                    self.notify("Select a fish to net".to_string(), Severity::Info);
+                } else if self.net_targets().is_empty() {
+                    self.notify("There is no other tank".to_string(), Severity::Info);
+                } else {
+                    self.push_screen(Screen::NetPicker);
+                }
+            }
+            Action::SelectNext => self.select_next(),
+            Action::SelectPrev => self.select_prev(),
+            Action::OpenStats => self.push_screen(Screen::Stats),
+            Action::OpenAchievements => self.push_screen(Screen::Achievements),
+            Action::OpenMaintenance => {
+                self.maintenance_cursor = 0;
+                self.push_screen(Screen::Maintenance);
+            }
+            Action::OpenLineage => {
+                if let Some(fish_id) = self.selected_fish_id() {
+                    self.lineage_history.clear();
+                    self.focus_lineage(fish_id);
+                    self.push_screen(Screen::Lineage);
+                }
+            }
+            Action::OpenEventLog => {
+                self.log_scroll = 0;
+                self.push_screen(Screen::EventLog);
+            }
+            Action::OpenRoster => {
+                self.roster.cursor = self.selected_fish_id();
+                self.push_screen(Screen::Roster);
+            }
+            Action::RemoveFish => {
+                if let Some(fish_id) = self.selected_fish_id()
+                    && self.god_actions()
+                {
+                    self.tank_mut().remove_fish(fish_id);
+                    self.selected_fish = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Offer the modes for a new game before anything runs.
+    pub fn choose_mode(&mut self, choices: Vec<GameMode>) {
+        self.mode_choices = choices;
+        self.mode_cursor = 0;
+        self.push_screen(Screen::ModeSelect);
+    }
+
+    /// Whether the new-game selector is still open, so no game has begun.
+    pub fn choosing_mode(&self) -> bool {
+        self.screens.contains(&Screen::ModeSelect)
+    }
+
+    fn apply_mode_select(&mut self, action: Action) {
+        match action {
+            Action::CursorUp => self.mode_cursor = self.mode_cursor.saturating_sub(1),
+            Action::CursorDown => {
+                self.mode_cursor = (self.mode_cursor + 1).min(self.mode_choices.len().saturating_sub(1))
+            }
+            Action::Confirm => {
+                let Some(mode) = self.mode_choices.get(self.mode_cursor).cloned() else {
+                    return;
+                };
+                let display = self.aquarium.tanks[0].clone();
+                self.aquarium = mode.new_game(display);
+                self.mode_choices.clear();
+                self.pop_screen();
+                self.update_camera();
+            }
+            Action::Quit => self.should_quit = true,
+            _ => {}
+        }
+    }
+
+    /// Rows are the tasks followed by the tap water temperature. Left and
+    /// right change the water change share on its row and the temperature
+    /// on the last.
+    fn apply_maintenance(&mut self, action: Action) {
+        let task = Task::ALL.get(self.maintenance_cursor).copied();
+        match action {
+            Action::CursorUp => self.maintenance_cursor = self.maintenance_cursor.saturating_sub(1),
+            Action::CursorDown => self.maintenance_cursor = (self.maintenance_cursor + 1).min(Task::ALL.len()),
+            Action::Decrease | Action::Increase => {
+                let step = if action == Action::Increase { 1 } else { -1 };
+                match task {
+                    Some(Task::WaterChange) => self.fresh_water.adjust_percent(step),
+                    None => self.fresh_water.adjust_temperature(step as f32),
+                    Some(_) => {}
+                }
+            }
+            Action::Confirm => {
+                if let Some(task) = task {
+                    let fresh = self.fresh_water.clone();
+                    let record = task.perform(self.tank_mut(), &fresh);
+                    let text = format!("{}: {}", task.label(), record.detail);
+                    self.notify(text, Severity::Info);
+                }
+            }
+            Action::Close => self.pop_screen(),
+            _ => {}
+        }
+    }
+
+    /// Whether free fish, removing and dragging are allowed; tells the
+    /// player when they aren't.
+    fn god_actions(&mut self) -> bool {
+        if self.aquarium.mode.god_actions() {
+            return true;
+        }
+        let text = format!("{} doesn't allow that; use the shop instead", self.aquarium.mode.label());
+        self.notify(text, Severity::Info);
+        false
+    }
+
+    /// Where a fish added by the player appears: staggered, and inside the
+    /// part of the tank on screen.
+    fn arrival_position(&self) -> (u16, u16) {
+        let (x, y) = self.tank().spawn_position();
+        let (width, height) = self.view_size();
+        self.tank().clamp_position((
+            self.camera.0 + x % width.max(1),
+            self.camera.1 + y % height.max(1),
+        ))
+    }
+
+    /// Take food from the wallet in economy mode; always succeeds in a
+    /// sandbox.
+    fn use_food(&mut self, portions: u32) -> bool {
+        let Some(wallet) = &mut self.aquarium.wallet else {
+            return true;
+        };
+        if wallet.use_food(portions) {
+            return true;
+        }
+        let food = wallet.food;
+        self.notify(
+            format!("Not enough food ({} portions left); buy more in the shop", food),
+            Severity::Warning,
+        );
+        false
+    }
+
+    fn open_shop(&mut self) {
+        self.shop_cursor = 0;
+        self.push_screen(Screen::Shop);
+    }
+
+    fn apply_shop(&mut self, action: Action) {
+        match action {
+            Action::CursorUp => self.shop_cursor = self.shop_cursor.saturating_sub(1),
+            Action::CursorDown => {
+                self.shop_cursor = (self.shop_cursor + 1).min(self.shop_items.len().saturating_sub(1))
+            }
+            Action::Buy => self.buy(),
+            Action::SellFish => self.sell_selected(),
+            Action::Close => self.pop_screen(),
+            _ => {}
+        }
+    }
+
+    /// Buy the item under the shop cursor for the tank on screen.
+    fn buy(&mut self) {
+        let Some(item) = self.shop_items.get(self.shop_cursor).cloned() else {
+            return;
+        };
+        if let Err(reason) = item.check(self.tank()) {
+            self.notify(reason, Severity::Warning);
+            return;
+        }
+        let position = self.arrival_position();
+        let active = self.aquarium.active;
+        let Some(wallet) = &mut self.aquarium.wallet else {
+            return;
+        };
+        if !wallet.pay(item.price()) {
+            let text = format!("{} costs {}; you have {}", item.label(), item.price(), wallet.coins);
+            self.notify(text, Severity::Warning);
+            return;
+        }
+        let tank = &mut self.aquarium.tanks[active];
+        match &item {
+            ShopItem::Fish(species) => tank.add_fish(Fish::new(species.name.clone(), position)),
+            other => other.install(tank, wallet),
+        }
+        self.notify(format!("Bought {} for {}", item.label(), item.price()), Severity::Info);
+    }
+
+    fn sell_selected(&mut self) {
+        let Some(fish) = self.selected_fish_id().and_then(|id| self.tank().get_fish(id)) else {
+            self.notify("Select a fish to sell".to_string(), Severity::Info);
+            return;
+        };
+        let Some(species) = species::get_species(&fish.species) else {
+            return;
+        };
+        let (id, price) = (fish.id, economy::sale_price(fish, &species));
+        if self.aquarium.wallet.is_none() {
+            return;
+        }
+        if let Some(sold) = self.tank_mut().sell_fish(id, price)
+            && let Some(wallet) = &mut self.aquarium.wallet
+        {
+            wallet.receive(price);
+            self.selected_fish = None;
+            self.follow = false;
+            self.notify(format!("Sold {} for {}", sold.display_name(), price), Severity::Info);
+        }
+    }
+
+    /// Show another tank; the camera and selection start over.
+    fn switch_tank(&mut self, step: isize) {
+        self.aquarium.cycle(step);
+        self.selected_fish = None;
+        self.follow = false;
+        self.dragging = None;
+        self.camera = (0, 0);
+        self.roster.marked.clear();
+    }
+
+    /// Tanks the selected fish can be netted into, in picker order.
+    pub fn net_targets(&self) -> Vec<usize> {
+        (0..self.aquarium.tanks.len())
+            .filter(|&index| index != self.aquarium.active)
+            .collect()
+    }
+
+    fn net_selected(&mut self, to: usize) {
+        let Some(fish_id) = self.selected_fish_id() else {
+            return;
+        };
+        if let Some(transfer) = self.aquarium.net_fish(fish_id, to) {
+            self.selected_fish = None;
+            self.follow = false;
+            let severity = if transfer.stress >= tank::STRESS_HARMFUL {
+                Severity::Warning
+            } else {
+                Severity::Info
+            };
+            self.notify(
+                format!(
+                    "Netted {} from {} to {} (stress {})",
+                    transfer.fish, transfer.from, transfer.to, transfer.stress
+                ),
+                severity,
+            );
+        }
+    }
+
+    fn apply_roster(&mut self, action: Action) {
+        match action {
+            Action::CursorUp => self.roster.move_cursor(self.aquarium.active(), -1),
+            Action::CursorDown => self.roster.move_cursor(self.aquarium.active(), 1),
+            Action::CycleSort => self.roster.cycle_sort(),
+            Action::ReverseSort => self.roster.descending = !self.roster.descending,
+            Action::CycleFilter => self.roster.cycle_filter(),
+            Action::StartSearch => self.roster.searching = true,
+            Action::ToggleMark => self.roster.toggle_mark(self.aquarium.active()),
+            Action::MarkAll => self.roster.mark_all(self.aquarium.active()),
+            Action::RemoveMarked => {
+                if !self.god_actions() {
+                    return;
+                }
+                for id in self.roster.targets(self.aquarium.active()) {
+                    self.tank_mut().remove_fish(id);
+                }
+                self.roster.marked.clear();
+            }
+            Action::Confirm => {
+                let rows = self.roster.rows(self.aquarium.active());
+                if let Some(idx) = self.roster.cursor_index(&rows) {
+                    self.selected_fish = Some(rows[idx].id);
+                }
+                self.pop_screen();
+            }
+            Action::Close => self.pop_screen(),
+            _ => {}
+        }
+    }
+
+    fn apply_lineage(&mut self, action: Action) {
+        let rows = self.lineage_rows();
+        match action {
+            Action::CursorUp => self.lineage_cursor = self.lineage_cursor.saturating_sub(1),
+            Action::CursorDown => {
+                self.lineage_cursor = (self.lineage_cursor + 1).min(rows.len().saturating_sub(1))
+            }
+            Action::Confirm => {
+                if let (Some(row), Some(focus)) = (rows.get(self.lineage_cursor), self.lineage_focus)
+                    && row.id != focus
+                {
+                    self.lineage_history.push(focus);
+                    self.focus_lineage(row.id);
+                }
+            }
+            Action::Back => {
+                if let Some(previous) = self.lineage_history.pop() {
+                    self.focus_lineage(previous);
+                }
+            }
+            Action::ExportDot => self.export_lineage(),
+            Action::Close => self.pop_screen(),
+            _ => {}
+        }
+    }
+
+    /// Centre the family tree on a fish, with the cursor on it.
+    fn focus_lineage(&mut self, id: Uuid) {
+        self.lineage_focus = Some(id);
+        self.lineage_cursor = self
+            .lineage_rows()
+            .iter()
+            .position(|row| row.branch == Branch::Root)
+            .unwrap_or(0);
+    }
+
+    /// Rows of the family tree around the focused fish.
+    pub fn lineage_rows(&self) -> Vec<TreeRow> {
+        self.lineage_focus
+            .map(|id| self.tank().lineage.tree(id, LINEAGE_DEPTH))
+            .unwrap_or_default()
+    }
+
+    /// Write the focused fish's family line to `lineage-<name>.dot`.
+    fn export_lineage(&mut self) {
+        let Some(record) = self.lineage_focus.and_then(|id| self.tank().lineage.get(id)) else {
+            return;
+        };
+        let stem: String = record
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        let path = self.export_dir.join(format!("lineage-{}.dot", stem));
+        match fs::write(&path, self.tank().lineage.to_dot(record.id)) {
+            Ok(()) => self.notify(format!("Exported {}", path.display()), Severity::Good),
+            Err(e) => self.notify(format!("Export failed: {}", e), Severity::Bad),
+        }
+    }
+
+    /// Typing into the rename prompt. Confirm saves, Close cancels.
+    fn handle_prompt_key(&mut self, key: &KeyEvent) {
+        match self.keymap.lookup(Context::Prompt, key) {
+            Some(Action::Confirm) => {
+                if let Some(fish_id) = self.selected_fish_id() {
+                    self.aquarium.active_mut().rename_fish(fish_id, &self.input);
+                }
+                self.input.clear();
+                self.pop_screen();
+            }
+            Some(Action::Close) => {
+                self.input.clear();
+                self.pop_screen();
+            }
+            _ => edit_text(&mut self.input, key, MAX_NAME_LEN),
+        }
+    }
+
+    /// Typing into the roster search box. Confirm keeps the query, Close
+    /// clears it.
+    fn handle_search_key(&mut self, key: &KeyEvent) {
+        match self.keymap.lookup(Context::Prompt, key) {
+            Some(Action::Confirm) => self.roster.searching = false,
+            Some(Action::Close) => {
+                self.roster.query.clear();
+                self.roster.searching = false;
+            }
+            _ => edit_text(&mut self.roster.query, key, MAX_NAME_LEN),
+        }
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseEvent) {
+        match (self.screen(), mouse.kind) {
+            (Screen::Help, MouseEventKind::ScrollDown) => {
+                self.help_scroll = self.help_scroll.saturating_add(1)
+            }
+            (Screen::Help, MouseEventKind::ScrollUp) => {
+                self.help_scroll = self.help_scroll.saturating_sub(1)
+            }
+            (Screen::EventLog, MouseEventKind::ScrollDown) => self.scroll_log_down(),
+            (Screen::EventLog, MouseEventKind::ScrollUp) => {
+                self.log_scroll = self.log_scroll.saturating_sub(1)
+            }
+            (Screen::Roster, MouseEventKind::ScrollDown) => self.roster.move_cursor(self.aquarium.active(), 1),
+            (Screen::Roster, MouseEventKind::ScrollUp) => self.roster.move_cursor(self.aquarium.active(), -1),
+            (Screen::Tank, MouseEventKind::ScrollDown) => self.select_next(),
+            (Screen::Tank, MouseEventKind::ScrollUp) => self.select_prev(),
+            (Screen::Tank, MouseEventKind::Down(MouseButton::Left)) if !self.text_view => {
+                let Some(point) = self.tank_point(mouse.column, mouse.row) else {
+                    return;
+                };
+                if let Some(fish_id) = self.fish_at(point) {
+                    self.select_fish(fish_id);
+                    if !self.aquarium.mode.god_actions() {
+                        return;
+                    }
+                    let grab_x = self
+                        .tank()
+                        .get_fish(fish_id)
+                        .map_or(0, |f| point.0.saturating_sub(f.position.0));
+                    self.dragging = Some((fish_id, grab_x));
+                } else if self.feed_mode && self.use_food(1) {
+                    self.tank_mut().drop_food(point);
+                }
+            }
+            (Screen::Tank, MouseEventKind::Drag(MouseButton::Left)) => {
+                if let Some((fish_id, grab_x)) = self.dragging
+                    && let Some(point) = self.tank_point(mouse.column, mouse.row)
+                {
+                    self.tank_mut()
+                        .move_fish(fish_id, (point.0.saturating_sub(grab_x), point.1));
+                }
+            }
+            (_, MouseEventKind::Up(MouseButton::Left)) => self.dragging = None,
+            _ => {}
+        }
+    }
+
+    /// Convert a terminal cell to tank coordinates, if it is inside the water.
+    fn tank_point(&self, column: u16, row: u16) -> Option<(u16, u16)> {
+        let inner = ui::tank_inner_area(self.area, self.inspector_open, self.accessible, self.tank().dimensions);
+        let inside = column >= inner.x
+            && column < inner.x + inner.width
+            && row >= inner.y
+            && row < inner.y + inner.height;
+        inside.then(|| (column - inner.x + self.camera.0, row - inner.y + self.camera.1))
+    }
+
+    /// The topmost fish whose sprite covers a tank point.
+    fn fish_at(&self, point: (u16, u16)) -> Option<Uuid> {
+        self.tank()
+            .fish
+            .iter()
+            .rev()
+            .find(|fish| {
+                let (width, height) = self.sprites.get(&fish.species).map_or((1, 1), |set| set.size());
+                point.1 >= fish.position.1
+                    && point.1 < fish.position.1 + height
+                    && point.0 >= fish.position.0
+                    && point.0 < fish.position.0 + width
+            })
+            .map(|fish| fish.id)
+    }
+
+    fn select_fish(&mut self, id: Uuid) {
+        self.selected_fish = Some(id);
+    }
+
+    /// Position of the selected fish in the tank's list, if still alive.
+    fn selected_index(&self) -> Option<usize> {
+        let id = self.selected_fish?;
+        self.tank().fish.iter().position(|f| f.id == id)
+    }
+
+    fn select_next(&mut self) {
+        let count = self.tank().fish.len();
+        if count == 0 {
+            return;
+        }
+        let idx = match self.selected_index() {
+            None => 0,
+            Some(idx) => (idx + 1) % count,
+        };
+        self.selected_fish = Some(self.tank().fish[idx].id);
+    }
+
+    fn select_prev(&mut self) {
+        let count = self.tank().fish.len();
+        if count == 0 {
+            return;
+        }
+        let idx = match self.selected_index() {
+            None | Some(0) => count - 1,
+            Some(idx) => idx - 1,
+        };
+        self.selected_fish = Some(self.tank().fish[idx].id);
+    }
+
+    /// Track a new terminal size. The tank keeps its own size; only the
+    /// view onto it changes.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.area = Rect::new(0, 0, width, height);
+        self.update_camera();
+    }
+
+    /// Width and height of the tank view, in cells.
+    pub fn view_size(&self) -> (u16, u16) {
+        let inner = ui::tank_inner_area(self.area, self.inspector_open, self.accessible, self.tank().dimensions);
+        (inner.width, inner.height)
+    }
+
+    /// Move the camera by a number of cells. Panning by hand stops
+    /// following the selected fish.
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.follow = false;
+        let shift = |value: u16, delta: i32| (value as i32 + delta).max(0) as u16;
+        self.camera = (shift(self.camera.0, dx), shift(self.camera.1, dy));
+        self.update_camera();
+    }
+
+    /// Centre on the followed fish, then keep the view inside the tank.
+    fn update_camera(&mut self) {
+        let (width, height) = self.view_size();
+        if self.follow {
+            match self.selected_fish_id().and_then(|id| self.tank().get_fish(id)) {
+                Some(fish) => {
+                    self.camera = (
+                        fish.position.0.saturating_sub(width / 2),
+                        fish.position.1.saturating_sub(height / 2),
+                    )
+                }
+                None => self.follow = false,
+            }
+        }
+        let (world_width, world_height) = self.tank().dimensions;
+        self.camera = (
+            self.camera.0.min(world_width.saturating_sub(width)),
+            self.camera.1.min(world_height.saturating_sub(height)),
+        );
+    }
+
+    /// Whether the whole tank fits in the view.
+    pub fn tank_fits(&self) -> bool {
+        let (width, height) = self.view_size();
+        self.tank().dimensions.0 <= width && self.tank().dimensions.1 <= height
+    }
+
+    /// Advance the simulation by one tick unless paused.
+    pub fn on_tick(&mut self) {
+        let now = Instant::now();
+        self.toasts.retain(|toast| toast.until > now);
+        if self.paused || self.screen() == Screen::ModeSelect {
+            return;
+        }
+
+        let coins = self.aquarium.wallet.as_ref().map(|w| w.coins);
+        let summaries = self.aquarium.tick();
+        if let (Some(before), Some(wallet)) = (coins, &self.aquarium.wallet)
+            && before >= 0
+            && wallet.coins < 0
+        {
+            let text = format!("Running costs put you {} coins in debt; sell fish to pay", -wallet.coins);
+            self.notify(text, Severity::Warning);
+        }
+        for (index, summary) in summaries.iter().enumerate() {
+            for event in &summary.events {
+                self.record_event(index, event.clone());
+            }
+        }
+        if let Some(outcome) = self.aquarium.update_scenario() {
+            let severity = if outcome.won { Severity::Info } else { Severity::Warning };
+            self.notify(outcome.reason, severity);
+            self.push_screen(Screen::Results);
+        }
+        self.check_achievements(&summaries);
+        self.check_favorites();
+        self.update_camera();
+
+        if self.tank().tick_count.is_multiple_of(HEALTH_SAMPLE_INTERVAL) {
+            self.sample_health();
+        }
+    }
+
+    /// Toast anything newly unlocked and save the profile straight away, so
+    /// a crash doesn't lose it.
+    fn check_achievements(&mut self, summaries: &[TickSummary]) {
+        let unlocked = self.profile.check(&self.aquarium, summaries);
+        if unlocked.is_empty() {
+            return;
+        }
+        for achievement in unlocked {
+            let text = format!("Achievement unlocked: {} - {}", achievement.title(), achievement.description());
+            self.notify(text, Severity::Good);
+        }
+        if let Some(path) = &self.profile_path
+            && let Err(e) = self.profile.save(path)
+        {
+            self.notify(format!("Failed to save achievements: {}", e), Severity::Bad);
+        }
+    }
+
+    /// Show a toast, dropping the oldest if too many are up.
+    pub fn notify(&mut self, text: String, severity: Severity) {
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            text,
+            severity,
+            until: Instant::now() + TOAST_DURATION,
+        });
+    }
+
+    /// Log an event from tank `index` and toast anything worth a look.
+    /// Toasts from tanks that aren't on screen name the tank.
+    fn record_event(&mut self, index: usize, event: TankEvent) {
+        if let TankEvent::Died(death) = &event {
+            self.favorite_alerts.remove(&death.id);
+        }
+        let tank = &self.aquarium.tanks[index];
+        let (tick, name) = (tank.tick_count, tank.name.clone());
+        if event.severity() != Severity::Info {
+            let text = if index == self.aquarium.active {
+                event.describe()
+            } else {
+                format!("{}: {}", name, event.describe())
+            };
+            self.notify(text, event.severity());
+        }
+        if self.event_log.len() == EVENT_LOG_LEN {
+            self.event_log.pop_front();
+        }
+        self.event_log.push_back(TimedEvent { tick, tank: name, event });
+    }
+
+    fn scroll_log_down(&mut self) {
+        self.log_scroll = (self.log_scroll + 1).min(self.event_log.len().saturating_sub(1));
+    }
+
+    /// Tell the player once when a favorite becomes unwell.
+    fn check_favorites(&mut self) {
+        let mut alerts = Vec::new();
+        let fish = self.aquarium.tanks.iter().flat_map(|tank| &tank.fish);
+        for fish in fish.filter(|f| f.favorite) {
+            let unwell = fish.health < 50 || fish.hunger >= 80;
+            if unwell && self.favorite_alerts.insert(fish.id) {
+                alerts.push(format!("{} is unwell (health {}, hunger {})", fish.display_name(), fish.health, fish.hunger));
+            } else if !unwell {
+                self.favorite_alerts.remove(&fish.id);
+            }
+        }
+        for alert in alerts {
+            self.notify(alert, Severity::Warning);
+        }
+    }
+
+    fn sample_health(&mut self) {
+        let alive: Vec<Uuid> = self.aquarium.tanks.iter().flat_map(|tank| &tank.fish).map(|f| f.id).collect();
+        self.health_history.retain(|id, _| alive.contains(id));
+        for fish in self.aquarium.tanks.iter().flat_map(|tank| &tank.fish) {
+            let history = self.health_history.entry(fish.id).or_default();
+            if history.len() == HEALTH_HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(fish.health as u64);
+        }
+    }
+}
+
+/// Apply a typed character or backspace to a text field.
+fn edit_text(text: &mut String, key: &KeyEvent, max_len: usize) {
+    match key.code {
+        KeyCode::Char(c) if text.chars().count() < max_len => text.push(c),
+        KeyCode::Backspace => {
+            text.pop();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn app() -> App {
+        App::new(Aquarium::starter(Tank::with_seed(60, 20, 7)), Keymap::default_layout())
+    }
+
+    /// An app with one fish, selected.
+    fn app_with_fish() -> App {
+        let mut app = app();
+        let fish = Fish::new(app.species[0].name.clone(), (10, 10));
+        app.selected_fish = Some(fish.id);
+        app.tank_mut().add_fish(fish);
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_event(&Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn normal_keys() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char(' '));
+        assert!(app.paused);
+        press(&mut app, KeyCode::Char('+'));
+        assert!(app.aquarium.tanks.iter().all(|tank| tank.simulation_speed > 1.0));
+        press(&mut app, KeyCode::Char(']'));
+        assert_eq!(app.aquarium.active, 1);
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn releases_are_ignored() {
+        let mut app = app();
+        let mut key = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        key.kind = KeyEventKind::Release;
+        app.handle_key(&key);
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn species_picker_adds_a_fish() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.screen(), Screen::SpeciesPicker);
+        press(&mut app, KeyCode::Char('2'));
+        assert_eq!(app.screen(), Screen::Tank);
+        assert_eq!(app.tank().fish[0].species, app.species[1].name);
+    }
+
+    #[test]
+    fn net_picker_moves_the_selected_fish() {
+        let mut app = app_with_fish();
+        let id = app.selected_fish.unwrap();
+        press(&mut app, KeyCode::Char('b'));
+        assert_eq!(app.screen(), Screen::NetPicker);
+        press(&mut app, KeyCode::Char('2'));
+        assert_eq!(app.screen(), Screen::Tank);
+        assert_eq!(app.aquarium.tank_of(id), Some(2));
+    }
+
+    #[test]
+    fn inspector_nets_the_selected_fish() {
+        let mut app = app_with_fish();
+        press(&mut app, KeyCode::Char('i'));
+        assert!(app.inspector_open);
+        press(&mut app, KeyCode::Char('b'));
+        assert_eq!(app.screen(), Screen::NetPicker);
+        assert_eq!(app.net_targets(), vec![1, 2]);
+    }
+
+    #[test]
+    fn help_opens_and_closes() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('?'));
+        assert_eq!(app.screen(), Screen::Help);
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.screen(), Screen::Help);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.screen(), Screen::Tank);
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn rename_prompt_edits_and_confirms() {
+        let mut app = app_with_fish();
+        let id = app.selected_fish.unwrap();
+        app.tank_mut().rename_fish(id, "");
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.screen(), Screen::Rename);
+        // Letters bound elsewhere are typed, not applied
+        type_text(&mut app, "Bubbles");
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.screen(), Screen::Tank);
+        assert_eq!(app.tank().get_fish(id).unwrap().name.as_deref(), Some("Bubble"));
+        assert!(!app.should_quit);
+    }
+
+    #[test]
+    fn roster_search_and_selection() {
+        let mut app = app_with_fish();
+        app.selected_fish = None;
+        press(&mut app, KeyCode::Char('o'));
+        assert_eq!(app.screen(), Screen::Roster);
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "xq");
+        assert_eq!(app.roster.query, "xq");
+        press(&mut app, KeyCode::Esc);
+        assert!(app.roster.query.is_empty());
+        assert_eq!(app.screen(), Screen::Roster);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.screen(), Screen::Tank);
+        assert_eq!(app.selected_fish, Some(app.tank().fish[0].id));
+    }
+
+    #[test]
+    fn stats_cycles_windows() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.screen(), Screen::Stats);
+        press(&mut app, KeyCode::Char('w'));
+        assert_eq!(app.stats_window, 1);
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.screen(), Screen::Tank);
+    }
+
+    #[test]
+    fn event_log_scrolls() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('v'));
+        assert_eq!(app.screen(), Screen::EventLog);
+        press(&mut app, KeyCode::Up);
+        assert_eq!(app.log_scroll, 0);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.screen(), Screen::Tank);
+    }
+
+    #[test]
+    fn lineage_focuses_the_selected_fish() {
+        let mut app = app_with_fish();
+        let id = app.selected_fish.unwrap();
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.screen(), Screen::Lineage);
+        assert_eq!(app.lineage_focus, Some(id));
+        press(&mut app, KeyCode::Char('b'));
+        assert_eq!(app.lineage_focus, Some(id));
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.screen(), Screen::Tank);
+    }
+
+    #[test]
+    fn shop_buys_with_the_wallet() {
+        let mut app = app();
+        app.aquarium = GameMode::Economy.new_game(Tank::with_seed(60, 20, 7));
+        let coins = app.aquarium.wallet.as_ref().unwrap().coins;
+        press(&mut app, KeyCode::Char('$'));
+        assert_eq!(app.screen(), Screen::Shop);
+        press(&mut app, KeyCode::Enter);
+        let price = app.shop_items[0].price();
+        assert_eq!(app.aquarium.wallet.as_ref().unwrap().coins, coins - price as i64);
+        press(&mut app, KeyCode::Char('$'));
+        assert_eq!(app.screen(), Screen::Tank);
+    }
+
+    #[test]
+    fn rule_checks_notify_once_and_change_nothing() {
+        let mut app = app();
+        app.aquarium = GameMode::Economy.new_game(Tank::with_seed(60, 20, 7).stocked());
+        app.aquarium.wallet.as_mut().unwrap().food = 0;
+        app.tank_mut().fish[0].hunger = 60;
+        press(&mut app, KeyCode::Char('f'));
+        assert_eq!(app.tank().fish[0].hunger, 60);
+        assert_eq!(app.toasts.len(), 1);
+        // Adding a fish goes through the shop when there's money
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.screen(), Screen::Shop);
+
+        let mut app = app_with_fish();
+        app.aquarium.mode = GameMode::Survival;
+        let count = app.tank().fish.len();
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.screen(), Screen::Tank);
+        assert_eq!(app.tank().fish.len(), count);
+        assert_eq!(app.toasts.len(), 2);
+    }
+
+    #[test]
+    fn mode_select_starts_a_game() {
+        let mut app = app();
+        app.choose_mode(GameMode::choices(&[]));
+        press(&mut app, KeyCode::Char(' '));
+        assert!(!app.paused);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.screen(), Screen::Tank);
+        assert_eq!(app.aquarium.mode.config_name(), "economy");
+        assert!(app.aquarium.wallet.is_some());
+        assert!(!app.choosing_mode());
+    }
+
+    #[test]
+    fn quitting_from_mode_select_starts_no_game() {
+        let mut app = app();
+        app.choose_mode(GameMode::choices(&[]));
+        press(&mut app, KeyCode::Esc);
+        assert!(app.should_quit);
+        assert!(app.choosing_mode());
+    }
+
+    #[test]
+    fn maintenance_changes_the_tap_water() {
+        let mut app = app();
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.screen(), Screen::Maintenance);
+        let temperature = app.fresh_water.temperature;
+        for _ in 0..Task::ALL.len() {
+            press(&mut app, KeyCode::Down);
+        }
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.fresh_water.temperature, temperature + 1.0);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.screen(), Screen::Tank);
+    }
+}
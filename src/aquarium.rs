@@ -0,0 +1,190 @@
+//! Several tanks run side by side.
+//!
+//! An [`Aquarium`] is what gets saved and ticked: usually a display tank
+//! plus breeding and quarantine tanks, each with its own water and
+//! equipment. Fish can be netted from one tank to another; the change of
+//! water stresses them in proportion to how different it is.
+
+use crate::economy::{self, UPKEEP_INTERVAL, Wallet};
+use crate::fish::Fish;
+use crate::mode::GameMode;
+use crate::scenario::Outcome;
+use crate::lineage::Fate;
+use crate::tank::{Tank, TickSummary};
+use crate::water::Equipment;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Aquarium {
+    pub tanks: Vec<Tank>,
+    /// Index of the tank on screen.
+    #[serde(default)]
+    pub active: usize,
+    /// Money in economy mode; `None` for a sandbox with free fish.
+    #[serde(default)]
+    pub wallet: Option<Wallet>,
+    #[serde(default)]
+    pub mode: GameMode,
+}
+
+/// What happened when a fish was netted into another tank.
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    pub fish: String,
+    pub from: String,
+    pub to: String,
+    /// Acclimation stress the fish picked up.
+    pub stress: u8,
+}
+
+impl Aquarium {
+    /// A collection holding just `tank`.
+    pub fn single(tank: Tank) -> Aquarium {
+        Aquarium {
+            tanks: vec![tank],
+            active: 0,
+            wallet: None,
+            mode: GameMode::Sandbox,
+        }
+    }
+
+    /// `display` plus empty breeding and quarantine tanks of the same size
+    /// and speed, seeded from its RNG. The breeding tank runs warm; the
+    /// quarantine tank is cooler with a small filter.
+    pub fn starter(mut display: Tank) -> Aquarium {
+        let (width, height) = display.dimensions;
+        let mut extra = |name: &str, equipment: Equipment| {
+            let mut tank = Tank::with_seed(width, height, display.rng.next_u64())
+                .named(name)
+                .with_equipment(equipment);
+            tank.simulation_speed = display.simulation_speed;
+            tank.incidents.settings = display.incidents.settings.clone();
+            tank
+        };
+        let breeding = extra(
+            "Breeding",
+            Equipment {
+                heater: Some(27.0),
+                filter: 0.008,
+            },
+        );
+        let quarantine = extra(
+            "Quarantine",
+            Equipment {
+                heater: Some(23.0),
+                filter: 0.005,
+            },
+        );
+        Aquarium {
+            tanks: vec![display, breeding, quarantine],
+            active: 0,
+            wallet: None,
+            mode: GameMode::Sandbox,
+        }
+    }
+
+    pub fn active(&self) -> &Tank {
+        &self.tanks[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Tank {
+        &mut self.tanks[self.active]
+    }
+
+    /// Switch to the next tank, wrapping around; negative steps go back.
+    pub fn cycle(&mut self, step: isize) {
+        let count = self.tanks.len() as isize;
+        self.active = (self.active as isize + step).rem_euclid(count) as usize;
+    }
+
+    /// Run every tank at the preset closest to `speed`.
+    pub fn set_speed(&mut self, speed: f32) {
+        for tank in &mut self.tanks {
+            tank.set_speed(speed);
+        }
+    }
+
+    /// Tick every tank once, billing running costs in economy mode.
+    /// Summaries come back in tank order.
+    pub fn tick(&mut self) -> Vec<TickSummary> {
+        let summaries = self.tanks.iter_mut().map(Tank::tick).collect();
+        if let Some(wallet) = &mut self.wallet {
+            for tank in &self.tanks {
+                if tank.tick_count.is_multiple_of(UPKEEP_INTERVAL) {
+                    wallet.charge(economy::running_cost(&tank.equipment));
+                }
+            }
+        }
+        summaries
+    }
+
+    /// Check a running scenario's conditions, returning the outcome the
+    /// moment it is decided.
+    pub fn update_scenario(&mut self) -> Option<Outcome> {
+        let GameMode::Scenario(run) = &self.mode else {
+            return None;
+        };
+        if run.outcome.is_some() {
+            return None;
+        }
+        let elapsed = self.tanks[0].tick_count.saturating_sub(run.started);
+        let outcome = run.scenario.evaluate(self, elapsed)?;
+        if let GameMode::Scenario(run) = &mut self.mode {
+            run.outcome = Some(outcome.clone());
+        }
+        Some(outcome)
+    }
+
+    /// Ticks since a scenario started, or `None` outside one.
+    pub fn scenario_elapsed(&self) -> Option<u64> {
+        match &self.mode {
+            GameMode::Scenario(run) => Some(self.tanks[0].tick_count.saturating_sub(run.started)),
+            _ => None,
+        }
+    }
+
+    /// Index of the tank a fish is in.
+    pub fn tank_of(&self, id: Uuid) -> Option<usize> {
+        self.tanks.iter().position(|tank| tank.get_fish(id).is_some())
+    }
+
+    pub fn fish_count(&self) -> usize {
+        self.tanks.iter().map(|tank| tank.fish.len()).sum()
+    }
+
+    /// Move a fish into tank `to`. It keeps its family tree and picks up
+    /// stress from the change of water. `None` if the fish isn't found or
+    /// is already there.
+    pub fn net_fish(&mut self, id: Uuid, to: usize) -> Option<Transfer> {
+        let from = self.tank_of(id)?;
+        if from == to || to >= self.tanks.len() {
+            return None;
+        }
+
+        let (source, dest) = if from < to {
+            let (left, right) = self.tanks.split_at_mut(to);
+            (&mut left[from], &mut right[0])
+        } else {
+            let (left, right) = self.tanks.split_at_mut(from);
+            (&mut right[0], &mut left[to])
+        };
+        let index = source.fish.iter().position(|f| f.id == id)?;
+        let mut fish: Fish = source.fish.remove(index);
+        source.lineage.set_fate(id, Fate::Moved { tick: source.tick_count });
+
+        let stress = source.water.acclimation_stress(&dest.water);
+        fish.stress = fish.stress.max(stress);
+        fish.position = dest.clamp_position(fish.position);
+        fish.last_meal = None;
+        dest.lineage.copy_family(&source.lineage, id);
+        let transfer = Transfer {
+            fish: fish.display_name(),
+            from: source.name.clone(),
+            to: dest.name.clone(),
+            stress,
+        };
+        dest.add_fish(fish);
+        Some(transfer)
+    }
+}
@@ -0,0 +1,147 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Parser, Debug)]
+#[command(name = "aquarium-sim", version, about = "Terminal aquarium simulator")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file (default: $AQUARIUM_SIM_CONFIG or ~/.config/aquarium-sim/config.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Save file to load from and write to on quit
+    #[arg(long, value_name = "PATH")]
+    pub save: Option<PathBuf>,
+
+    /// Starting speed multiplier (snapped to the nearest preset)
+    #[arg(long)]
+    pub speed: Option<f32>,
+
+    /// Milliseconds between simulation ticks
+    #[arg(long, value_name = "MS")]
+    pub tick_rate: Option<u64>,
+
+    /// RNG seed for a new tank
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Species a new tank starts with, comma separated
+    #[arg(long, value_delimiter = ',', value_name = "SPECIES")]
+    pub stock: Option<Vec<String>>,
+
+    /// Color theme: default, ocean, high-contrast, colorblind, or a user theme
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// Show status glyphs and text descriptions instead of relying on color
+    #[arg(long)]
+    pub accessible: bool,
+
+    /// Start in the plain-text, screen-reader-friendly tank view
+    #[arg(long)]
+    pub text_view: bool,
+
+    /// Mode for a new game: sandbox, economy, survival or a scenario id
+    #[arg(long, value_name = "MODE")]
+    pub mode: Option<String>,
+
+    /// Serve the HTTP API on 127.0.0.1 at this port
+    #[arg(long, value_name = "PORT")]
+    pub http: Option<u16>,
+
+    /// Don't load the save file on startup
+    #[arg(long)]
+    pub no_autoload: bool,
+
+    /// Start a fresh tank; refused while the save file exists, since quitting
+    /// would overwrite it
+    #[arg(long)]
+    pub new: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the simulation headless and print a summary
+    Simulate(SimulateArgs),
+    /// Send a command to the running game over its control socket
+    Ctl(CtlArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SimulateArgs {
+    /// Number of ticks to simulate
+    #[arg(long, default_value_t = 10_000)]
+    pub ticks: u64,
+
+    /// Seed for the simulation RNG
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Start from a saved tank instead of the starter stock
+    #[arg(long)]
+    pub tank: Option<PathBuf>,
+
+    /// Feed all fish every N ticks (never fed if omitted)
+    #[arg(long, value_name = "N")]
+    pub feed_every: Option<u64>,
+
+    /// Output format for the summary
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CtlArgs {
+    /// Control socket (default: control_socket from the config, or
+    /// $XDG_RUNTIME_DIR/aquarium-sim.sock)
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+
+    /// Print the reply as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: CtlCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CtlCommand {
+    /// Show the mode, wallet and every tank's water
+    Status,
+    /// List the fish in a tank
+    Fish {
+        /// Tank number, counting from 0 (default: the tank on screen)
+        #[arg(long)]
+        tank: Option<usize>,
+    },
+    /// Show population and lifetime births and deaths
+    Stats {
+        #[arg(long)]
+        tank: Option<usize>,
+    },
+    /// Feed every fish in a tank
+    Feed {
+        #[arg(long)]
+        tank: Option<usize>,
+    },
+    /// Add a fish, buying it in economy games
+    Add {
+        species: String,
+        #[arg(long)]
+        tank: Option<usize>,
+    },
+    /// Take a fish out by id
+    Remove { id: Uuid },
+    /// Set the speed, snapped to the nearest preset
+    Speed { speed: f32 },
+}
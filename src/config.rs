@@ -0,0 +1,145 @@
+//! User configuration loaded from `config.toml`.
+//!
+//! Every field is optional in the file; anything left out falls back to the
+//! defaults below. Command-line flags override the file.
+
+use crate::incidents::IncidentSettings;
+use crate::tank::DEFAULT_DIMENSIONS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that points at an alternative config file.
+pub const CONFIG_ENV: &str = "AQUARIUM_SIM_CONFIG";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Where the tank is loaded from and saved to.
+    pub save_path: PathBuf,
+    /// Starting speed preset for new tanks.
+    pub speed: f32,
+    /// Milliseconds between simulation ticks in the TUI.
+    pub tick_rate_ms: u64,
+    /// RNG seed for new tanks; seeded from the clock when unset.
+    pub seed: Option<u64>,
+    /// Species names a new tank starts with.
+    pub stock: Vec<String>,
+    /// Size of the water in new tanks, in cells. The view scrolls when the
+    /// terminal is smaller.
+    pub tank_width: u16,
+    pub tank_height: u16,
+    /// Load the save file on startup if it exists.
+    pub autoload: bool,
+    /// Built-in theme name, a theme in `<config dir>/themes`, or a `.toml` path.
+    pub theme: String,
+    /// `auto`, `truecolor`, `256`, `16` or `none`.
+    pub color_depth: String,
+    /// JSON file of sprites laid over the built-in ones; defaults to
+    /// `<config dir>/sprites.json` when that exists.
+    pub sprites: Option<PathBuf>,
+    /// Mode for new games: `sandbox`, `economy`, `survival` or a scenario
+    /// id. When unset, a new game asks.
+    pub mode: Option<String>,
+    /// Random incidents in new tanks. Survival games always have them.
+    pub incidents: IncidentSettings,
+    /// Mark fish status with glyphs and describe the selected fish in text.
+    pub accessible: bool,
+    /// Start in the plain-text tank view.
+    pub text_view: bool,
+    /// Port for the HTTP API on 127.0.0.1; off when unset.
+    pub http_port: Option<u16>,
+    /// Listen on a Unix socket for `aquarium-sim ctl`.
+    pub control: bool,
+    /// Where the control socket goes; defaults to
+    /// `$XDG_RUNTIME_DIR/aquarium-sim.sock`.
+    pub control_socket: Option<PathBuf>,
+    pub keys: KeysConfig,
+}
+
+/// The `[keys]` table: a layout preset plus per-context overrides mapping
+/// action names to key chords, e.g. `normal = { feed = ["f", "Ctrl+f"] }`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    /// `default`, `vim` or `arrows`.
+    pub preset: Option<String>,
+    pub normal: BTreeMap<String, Vec<String>>,
+    pub picker: BTreeMap<String, Vec<String>>,
+    pub help: BTreeMap<String, Vec<String>>,
+    pub roster: BTreeMap<String, Vec<String>>,
+    pub prompt: BTreeMap<String, Vec<String>>,
+    pub stats: BTreeMap<String, Vec<String>>,
+    pub log: BTreeMap<String, Vec<String>>,
+    pub lineage: BTreeMap<String, Vec<String>>,
+    pub shop: BTreeMap<String, Vec<String>>,
+    pub mode_select: BTreeMap<String, Vec<String>>,
+    pub maintenance: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            save_path: PathBuf::from("tank.json"),
+            speed: 1.0,
+            tick_rate_ms: 100,
+            seed: None,
+            stock: vec![
+                "Goldfish".to_string(),
+                "Guppy".to_string(),
+                "Betta".to_string(),
+            ],
+            tank_width: DEFAULT_DIMENSIONS.0,
+            tank_height: DEFAULT_DIMENSIONS.1,
+            autoload: true,
+            theme: "default".to_string(),
+            color_depth: "auto".to_string(),
+            sprites: None,
+            mode: None,
+            incidents: IncidentSettings::default(),
+            accessible: false,
+            text_view: false,
+            http_port: None,
+            control: true,
+            control_socket: None,
+            keys: KeysConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Read a config file. A missing file is not an error and yields defaults.
+    pub fn load(path: &Path) -> io::Result<Config> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let text = fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })
+    }
+}
+
+/// Config file location: `$AQUARIUM_SIM_CONFIG`, then
+/// `$XDG_CONFIG_HOME/aquarium-sim/config.toml`, then
+/// `~/.config/aquarium-sim/config.toml`.
+pub fn default_config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Directory holding the config file and other per-user files.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("aquarium-sim"))
+}
@@ -0,0 +1,271 @@
+//! A Unix domain socket for controlling the running game from the shell.
+//!
+//! Clients write one command per line and get one line of JSON back: the
+//! command's result, or `{"error": "..."}`. A line is either a [`Command`]
+//! as JSON, `{"command": "add", "species": "Guppy"}`, or words:
+//!
+//! - `state` (or `status`)
+//! - `fish [TANK]`, `stats [TANK]`, `feed [TANK]`
+//! - `add SPECIES [TANK]`, `remove ID`, `speed SPEED`
+//!
+//! `aquarium-sim ctl` is a client, but `socat` or `nc -U` work as well.
+
+use crate::config;
+use crate::remote::{Command, Remote, Reply};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// `$XDG_RUNTIME_DIR/aquarium-sim.sock`, falling back to
+/// `<config dir>/control.sock`.
+pub fn default_socket_path() -> Option<PathBuf> {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("aquarium-sim.sock")),
+        _ => config::config_dir().map(|dir| dir.join("control.sock")),
+    }
+}
+
+/// A listening socket. The socket file is removed when this is dropped.
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Listen at `path` on a background thread. A socket file left behind by
+/// a game that crashed is replaced; one another game is listening on, or
+/// anything that isn't a socket, is an error. Only the current user can
+/// connect.
+pub fn spawn(path: &Path, remote: Remote) -> io::Result<ControlSocket> {
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and isn't a socket", path.display()),
+            ));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("another game is listening on {}", path.display()),
+            ));
+        }
+        fs::remove_file(path)?;
+    }
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    let listener = bind_private(path, dir)?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let remote = remote.clone();
+            thread::spawn(move || {
+                let _ = handle(stream, &remote);
+            });
+        }
+    });
+    Ok(ControlSocket {
+        path: path.to_path_buf(),
+    })
+}
+
+/// Bind the socket inside a directory only the current user can enter and
+/// move it to `path` once its permissions are set, so nobody else can
+/// connect in between.
+fn bind_private(path: &Path, dir: &Path) -> io::Result<UnixListener> {
+    let private = dir.join(format!(".aquarium-sim-{}", std::process::id()));
+    fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let bound = private.join("control.sock");
+    let listener = UnixListener::bind(&bound).and_then(|listener| {
+        fs::set_permissions(&bound, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&bound, path)?;
+        Ok(listener)
+    });
+    // Only left behind if something failed
+    let _ = fs::remove_file(&bound);
+    let _ = fs::remove_dir(&private);
+    listener
+}
+
+fn handle(stream: UnixStream, remote: &Remote) -> io::Result<()> {
+    let mut writer = &stream;
+    for line in BufReader::new(&stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match parse_line(&line) {
+            Ok(command) => remote.run(command),
+            Err(message) => Err(message),
+        };
+        let reply = reply.unwrap_or_else(|message| json!({ "error": message }));
+        writeln!(writer, "{}", reply)?;
+    }
+    Ok(())
+}
+
+/// A command as a JSON object or as words.
+pub fn parse_line(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        return serde_json::from_str(line).map_err(|e| e.to_string());
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some(&name) = words.first() else {
+        return Err("Empty command".to_string());
+    };
+    let tank = |index: usize| -> Result<Option<usize>, String> {
+        words
+            .get(index)
+            .map(|word| word.parse().map_err(|_| format!("'{}' isn't a tank number", word)))
+            .transpose()
+    };
+    let argument = |index: usize, what: &str| {
+        words
+            .get(index)
+            .copied()
+            .ok_or_else(|| format!("{} needs {}", name, what))
+    };
+    // Each command with the most words it takes
+    let (command, most) = match name {
+        "state" | "status" => (Command::State, 1),
+        "fish" => (Command::Fish { tank: tank(1)? }, 2),
+        "stats" => (Command::Stats { tank: tank(1)? }, 2),
+        "feed" => (Command::Feed { tank: tank(1)? }, 2),
+        "add" => {
+            let species = argument(1, "a species")?.to_string();
+            (Command::Add { species, tank: tank(2)? }, 3)
+        }
+        "remove" => {
+            let id = argument(1, "a fish id")?;
+            let id = id.parse().map_err(|_| format!("'{}' isn't a fish id", id))?;
+            (Command::Remove { id }, 2)
+        }
+        "speed" => {
+            let speed = argument(1, "a speed")?;
+            let speed = speed.parse().map_err(|_| format!("'{}' isn't a speed", speed))?;
+            (Command::Speed { speed }, 2)
+        }
+        other => return Err(format!("Unknown command '{}'", other)),
+    };
+    if words.len() > most {
+        return Err(format!("Too many arguments for {}", name));
+    }
+    Ok(command)
+}
+
+/// Send one command to the game listening at `path` and wait for the reply.
+pub fn send(path: &Path, command: &Command) -> io::Result<Reply> {
+    let stream = UnixStream::connect(path).map_err(|e| {
+        io::Error::new(e.kind(), format!("no game is listening on {}: {}", path.display(), e))
+    })?;
+    writeln!(&stream, "{}", serde_json::to_string(command)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let reply: Value = serde_json::from_str(&line)?;
+    Ok(match reply.get("error").and_then(Value::as_str) {
+        Some(message) => Err(message.to_string()),
+        None => Ok(reply),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aquarium::Aquarium;
+    use crate::remote;
+    use crate::tank::Tank;
+    use std::time::Duration;
+
+    /// A fresh directory under the system's temporary directory.
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aquarium-sim-test-{}", uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_words() {
+        assert!(matches!(parse_line("state"), Ok(Command::State)));
+        assert!(matches!(parse_line("  status  "), Ok(Command::State)));
+        assert!(matches!(parse_line("fish 1"), Ok(Command::Fish { tank: Some(1) })));
+        assert!(matches!(parse_line("feed"), Ok(Command::Feed { tank: None })));
+        assert!(matches!(parse_line("add Guppy 2"), Ok(Command::Add { species, tank: Some(2) }) if species == "Guppy"));
+        assert!(matches!(parse_line("speed 2.5"), Ok(Command::Speed { speed }) if speed == 2.5));
+        let id = uuid::Uuid::new_v4();
+        assert!(matches!(parse_line(&format!("remove {}", id)), Ok(Command::Remove { id: removed }) if removed == id));
+        let json = r#"{"command": "add", "species": "Tetra"}"#;
+        assert!(matches!(parse_line(json), Ok(Command::Add { species, tank: None }) if species == "Tetra"));
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        let error = |line: &str| parse_line(line).err().unwrap();
+        assert_eq!(error(""), "Empty command");
+        assert_eq!(error("   "), "Empty command");
+        assert_eq!(error("jump"), "Unknown command 'jump'");
+        assert_eq!(error("fish one"), "'one' isn't a tank number");
+        assert_eq!(error("add"), "add needs a species");
+        assert_eq!(error("add Guppy x"), "'x' isn't a tank number");
+        assert_eq!(error("remove"), "remove needs a fish id");
+        assert_eq!(error("remove 42"), "'42' isn't a fish id");
+        assert_eq!(error("speed fast"), "'fast' isn't a speed");
+        assert_eq!(error("state now"), "Too many arguments for state");
+        assert_eq!(error("feed 1 2"), "Too many arguments for feed");
+        assert!(parse_line(r#"{"command": "jump"}"#).is_err());
+    }
+
+    #[test]
+    fn refuses_to_replace_other_files() {
+        let dir = temp_dir();
+        let path = dir.join("control.sock");
+        fs::write(&path, "keep me").unwrap();
+        let (remote, _requests) = remote::channel();
+        let error = spawn(&path, remote).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn serves_commands_to_the_owner_only() {
+        let dir = temp_dir();
+        let path = dir.join("run").join("control.sock");
+        let (remote, requests) = remote::channel();
+        let socket = spawn(&path, remote.clone()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let mode = fs::metadata(path.parent().unwrap()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert_eq!(spawn(&path, remote).err().unwrap().kind(), io::ErrorKind::AddrInUse);
+
+        let mut aquarium = Aquarium::starter(Tank::with_seed(60, 20, 7));
+        let client = {
+            let path = path.clone();
+            thread::spawn(move || send(&path, &Command::Feed { tank: None }).unwrap())
+        };
+        while !client.is_finished() {
+            requests.handle(&mut aquarium);
+            thread::sleep(Duration::from_millis(5));
+        }
+        let reply = client.join().unwrap().unwrap();
+        assert_eq!(reply["tank"], 0);
+
+        // A socket left behind by a crash is replaced
+        drop(socket);
+        UnixListener::bind(&path).unwrap();
+        assert!(path.exists());
+        let (remote, _requests) = remote::channel();
+        let socket = spawn(&path, remote).unwrap();
+        drop(socket);
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
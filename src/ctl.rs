@@ -0,0 +1,103 @@
+//! `aquarium-sim ctl`: send a command to the running game and print the
+//! reply.
+
+use crate::cli::{CtlArgs, CtlCommand};
+use aquarium_sim::config::Config;
+use aquarium_sim::control;
+use aquarium_sim::remote::Command;
+use serde_json::Value;
+use std::io;
+
+pub fn run(args: CtlArgs, config: &Config) -> io::Result<()> {
+    let path = args
+        .socket
+        .or_else(|| config.control_socket.clone())
+        .or_else(control::default_socket_path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no control socket path; pass --socket"))?;
+    let command = match args.command {
+        CtlCommand::Status => Command::State,
+        CtlCommand::Fish { tank } => Command::Fish { tank },
+        CtlCommand::Stats { tank } => Command::Stats { tank },
+        CtlCommand::Feed { tank } => Command::Feed { tank },
+        CtlCommand::Add { species, tank } => Command::Add { species, tank },
+        CtlCommand::Remove { id } => Command::Remove { id },
+        CtlCommand::Speed { speed } => Command::Speed { speed },
+    };
+    let reply = control::send(&path, &command)?.map_err(io::Error::other)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reply)?);
+    } else {
+        print!("{}", describe(&command, &reply));
+    }
+    Ok(())
+}
+
+/// The reply as lines of text.
+fn describe(command: &Command, reply: &Value) -> String {
+    let text = |value: &Value| value.as_str().unwrap_or("?").to_string();
+    let number = |value: &Value| value.as_f64().unwrap_or(0.0);
+    let count = |value: &Value| value.as_u64().unwrap_or(0);
+    match command {
+        Command::State => {
+            let mut out = format!("Mode: {}\n", text(&reply["mode"]));
+            if let Some(wallet) = reply["wallet"].as_object() {
+                out += &format!("Coins: {}  Food: {}\n", wallet["coins"], wallet["food"]);
+            }
+            let active = reply["active"].as_u64();
+            for tank in reply["tanks"].as_array().into_iter().flatten() {
+                let water = &tank["water"];
+                let marker = if tank["index"].as_u64() == active { "*" } else { " " };
+                out += &format!(
+                    "{} {} {}: tick {}, {} fish, {}x, {:.1} °C, pH {:.1}, NH3 {:.2}, NO3 {:.0}\n",
+                    marker,
+                    tank["index"],
+                    text(&tank["name"]),
+                    tank["tick"],
+                    tank["fish"],
+                    number(&tank["speed"]),
+                    number(&water["temperature"]),
+                    number(&water["ph"]),
+                    number(&water["ammonia"]),
+                    number(&water["nitrate"]),
+                );
+            }
+            out
+        }
+        Command::Fish { .. } => {
+            let mut out = String::new();
+            for fish in reply.as_array().into_iter().flatten() {
+                out += &format!(
+                    "{:<16} {:<10} hunger {:>3}  health {:>3}  age {:>6}  {}\n",
+                    text(&fish["name"]),
+                    text(&fish["species"]),
+                    count(&fish["hunger"]),
+                    count(&fish["health"]),
+                    count(&fish["age"]),
+                    text(&fish["id"]),
+                );
+            }
+            out
+        }
+        Command::Stats { .. } => {
+            let mut out = format!("{} at tick {}\n", text(&reply["tank"]), reply["tick"]);
+            for (species, count) in reply["population"].as_object().into_iter().flatten() {
+                out += &format!("  {:<10} {}\n", species, count);
+            }
+            let totals = &reply["totals"];
+            out += &format!(
+                "Births: {}  Deaths: {} starvation, {} old age, {} poor water, {} disease\n",
+                totals["births"],
+                totals["starvation_deaths"],
+                totals["old_age_deaths"],
+                totals["poor_water_deaths"],
+                totals["disease_deaths"]
+            );
+            out
+        }
+        Command::Feed { .. } => format!("Fed {} fish\n", reply["fed"]),
+        Command::Add { .. } => format!("Added {} ({})\n", text(&reply["name"]), text(&reply["id"])),
+        Command::Remove { .. } => format!("Removed {}\n", text(&reply["removed"])),
+        Command::Speed { .. } => format!("Speed: {}x\n", number(&reply["speed"])),
+    }
+}
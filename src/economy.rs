@@ -0,0 +1,258 @@
+//! Coins, the shop and what things cost.
+//!
+//! Economy mode is optional: an [`Aquarium`](crate::aquarium::Aquarium)
+//! with a [`Wallet`] buys fish, food, equipment and decorations from the
+//! shop, pays for running its heaters and filters, and earns coins by
+//! selling fish. Without a wallet the game is a sandbox and the species
+//! picker is free.
+
+use crate::fish::Fish;
+use crate::species::{self, Species};
+use crate::tank::{DecorationKind, Tank};
+use crate::water::Equipment;
+use serde::{Deserialize, Serialize};
+
+/// Coins a new wallet starts with.
+pub const STARTING_COINS: i64 = 100;
+/// Food portions a new wallet starts with.
+pub const STARTING_FOOD: u32 = 50;
+/// Portions in one food pack.
+pub const FOOD_PACK: u32 = 50;
+const FOOD_PRICE: u32 = 5;
+const HEATER_PRICE: u32 = 40;
+const FILTER_PRICE: u32 = 30;
+/// Share of ammonia a filter upgrade adds, up to [`MAX_FILTER`].
+const FILTER_STEP: f32 = 0.005;
+const MAX_FILTER: f32 = 0.03;
+/// Ticks between running-cost bills.
+pub const UPKEEP_INTERVAL: u64 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Wallet {
+    /// Negative when running costs couldn't be covered.
+    pub coins: i64,
+    /// Portions of food left; feeding one fish uses one.
+    pub food: u32,
+    /// Coins ever received from sales.
+    #[serde(default)]
+    pub earned: u64,
+    /// Coins ever spent in the shop and on running costs.
+    #[serde(default)]
+    pub spent: u64,
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Wallet {
+            coins: STARTING_COINS,
+            food: STARTING_FOOD,
+            earned: 0,
+            spent: 0,
+        }
+    }
+}
+
+impl Wallet {
+    /// Take `price` coins if there are enough.
+    pub fn pay(&mut self, price: u32) -> bool {
+        if self.coins < price as i64 {
+            return false;
+        }
+        self.coins -= price as i64;
+        self.spent += price as u64;
+        true
+    }
+
+    /// Take a bill whether or not it can be covered.
+    pub fn charge(&mut self, amount: u32) {
+        self.coins -= amount as i64;
+        self.spent += amount as u64;
+    }
+
+    pub fn receive(&mut self, amount: u32) {
+        self.coins += amount as i64;
+        self.earned += amount as u64;
+    }
+
+    /// Use up `portions` of food if there are enough.
+    pub fn use_food(&mut self, portions: u32) -> bool {
+        if self.food < portions {
+            return false;
+        }
+        self.food -= portions;
+        true
+    }
+
+    /// "Coins: 120  Food: 35".
+    pub fn summary(&self) -> String {
+        format!("Coins: {}  Food: {}", self.coins, self.food)
+    }
+}
+
+/// Something the shop sells.
+#[derive(Clone, Debug)]
+pub enum ShopItem {
+    Fish(Species),
+    Food,
+    Heater,
+    Filter,
+    Decoration(DecorationKind),
+}
+
+impl ShopItem {
+    /// Everything on the shelves, in display order.
+    pub fn catalogue() -> Vec<ShopItem> {
+        let mut items: Vec<ShopItem> = species::get_all_species().into_iter().map(ShopItem::Fish).collect();
+        items.extend([ShopItem::Food, ShopItem::Heater, ShopItem::Filter]);
+        items.extend(DecorationKind::ALL.map(ShopItem::Decoration));
+        items
+    }
+
+    pub fn price(&self) -> u32 {
+        match self {
+            ShopItem::Fish(species) => species.price,
+            ShopItem::Food => FOOD_PRICE,
+            ShopItem::Heater => HEATER_PRICE,
+            ShopItem::Filter => FILTER_PRICE,
+            ShopItem::Decoration(DecorationKind::Plant) => 8,
+            ShopItem::Decoration(DecorationKind::Rock) => 5,
+            ShopItem::Decoration(DecorationKind::Castle) => 25,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            ShopItem::Fish(species) => species.name.clone(),
+            ShopItem::Food => format!("Food ({} portions)", FOOD_PACK),
+            ShopItem::Heater => "Heater".to_string(),
+            ShopItem::Filter => "Filter upgrade".to_string(),
+            ShopItem::Decoration(kind) => kind.label().to_string(),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ShopItem::Fish(_) => "fish for the tank on screen",
+            ShopItem::Food => "one portion feeds one fish",
+            ShopItem::Heater => "holds the tank at 25 °C",
+            ShopItem::Filter => "clears ammonia faster",
+            ShopItem::Decoration(_) => "decoration",
+        }
+    }
+
+    /// Why the item can't go into `tank`, if it can't.
+    pub fn check(&self, tank: &Tank) -> Result<(), String> {
+        match self {
+            ShopItem::Heater if tank.equipment.heater.is_some() => {
+                Err(format!("{} already has a heater", tank.name))
+            }
+            ShopItem::Filter if tank.equipment.filter >= MAX_FILTER => {
+                Err(format!("{} already has the best filter", tank.name))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Put a non-fish item into `tank` or `wallet`. Fish are placed by the
+    /// caller, which knows where the player is looking.
+    pub fn install(&self, tank: &mut Tank, wallet: &mut Wallet) {
+        match self {
+            ShopItem::Fish(_) => {}
+            ShopItem::Food => wallet.food += FOOD_PACK,
+            ShopItem::Heater => tank.equipment.heater = Equipment::default().heater,
+            ShopItem::Filter => tank.equipment.filter = (tank.equipment.filter + FILTER_STEP).min(MAX_FILTER),
+            ShopItem::Decoration(kind) => tank.decorate(*kind),
+        }
+    }
+}
+
+/// Coins a tank's equipment costs per [`UPKEEP_INTERVAL`]: 1 for a heater
+/// and 1 per started percent of filtering.
+///
+/// The starter tanks each have a heater and a filter under 1%, so a new
+/// economy game pays 6 coins per 100 ticks before it sells anything: the
+/// starting coins last about 1,600 ticks, long enough to raise a first
+/// brood, and the spare tanks aren't free to keep running.
+pub fn running_cost(equipment: &Equipment) -> u32 {
+    let heater = if equipment.heater.is_some() { 1 } else { 0 };
+    // Whole tenths of a percent first, so 0.07 isn't billed as 8%
+    let tenths = (equipment.filter.max(0.0) * 1000.0).round() as u32;
+    heater + tenths.div_ceil(10)
+}
+
+/// What the shop pays for a fish: the species price times its morph's
+/// multiplier, scaled by health. Store-bought fish fetch half.
+pub fn sale_price(fish: &Fish, species: &Species) -> u32 {
+    let full = species.price * fish.morph.price_multiplier() * fish.health.min(100) as u32 / 100;
+    let price = if fish.generation == 0 { full / 2 } else { full };
+    price.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aquarium::Aquarium;
+    use crate::fish::Morph;
+
+    #[test]
+    fn running_costs_bill_started_percents() {
+        let filter = |filter| Equipment { heater: None, filter };
+        assert_eq!(running_cost(&filter(0.0)), 0);
+        assert_eq!(running_cost(&filter(0.005)), 1);
+        assert_eq!(running_cost(&filter(0.07)), 7);
+        assert_eq!(running_cost(&filter(0.015)), 2);
+        assert_eq!(running_cost(&Equipment::default()), 2);
+    }
+
+    #[test]
+    fn the_starter_tanks_cost_six_coins() {
+        let aquarium = Aquarium::starter(Tank::with_seed(60, 20, 1));
+        let total: u32 = aquarium.tanks.iter().map(|tank| running_cost(&tank.equipment)).sum();
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    fn filter_upgrades_stop_at_the_best() {
+        let mut tank = Tank::with_seed(60, 20, 1);
+        let mut wallet = Wallet::default();
+        while ShopItem::Filter.check(&tank).is_ok() {
+            ShopItem::Filter.install(&mut tank, &mut wallet);
+        }
+        assert_eq!(tank.equipment.filter, MAX_FILTER);
+        assert_eq!(running_cost(&tank.equipment), 4);
+    }
+
+    #[test]
+    fn sale_prices_scale_with_health_morph_and_breeding() {
+        let guppy = species::get_species("Guppy").unwrap();
+        let mut fish = Fish::new(guppy.name.clone(), (0, 0));
+        fish.health = 100;
+        assert_eq!(sale_price(&fish, &guppy), guppy.price / 2);
+        fish.generation = 1;
+        assert_eq!(sale_price(&fish, &guppy), guppy.price);
+        fish.morph = Morph::Albino;
+        assert_eq!(sale_price(&fish, &guppy), guppy.price * 5);
+        fish.health = 50;
+        assert_eq!(sale_price(&fish, &guppy), guppy.price * 5 / 2);
+        fish.health = 0;
+        assert_eq!(sale_price(&fish, &guppy), 1);
+    }
+
+    #[test]
+    fn wallets_refuse_what_they_cant_pay_but_take_bills() {
+        let mut wallet = Wallet {
+            coins: 10,
+            ..Wallet::default()
+        };
+        assert!(!wallet.pay(11));
+        assert!(wallet.pay(10));
+        wallet.charge(3);
+        assert_eq!(wallet.coins, -3);
+        assert_eq!(wallet.spent, 13);
+        wallet.receive(5);
+        assert_eq!((wallet.coins, wallet.earned), (2, 5));
+        assert!(!wallet.use_food(STARTING_FOOD + 1));
+        assert!(wallet.use_food(STARTING_FOOD));
+        assert_eq!(wallet.food, 0);
+    }
+}
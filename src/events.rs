@@ -0,0 +1,88 @@
+//! Typed events emitted by [`Tank::tick`](crate::tank::Tank::tick).
+//!
+//! Each tick's events come back in its [`TickSummary`](crate::tank::TickSummary);
+//! [`Tank::subscribe`](crate::tank::Tank::subscribe) delivers the same events,
+//! stamped with their tick, over a channel.
+
+use crate::incidents::IncidentKind;
+use crate::tank::{Death, DeathCause};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum TankEvent {
+    /// A fish hatched in the tank.
+    Born {
+        id: Uuid,
+        name: String,
+        species: String,
+    },
+    /// A pair produced offspring.
+    Bred {
+        parents: [Uuid; 2],
+        names: [String; 2],
+        species: String,
+    },
+    Died(Death),
+    /// A fish's hunger hit the maximum and it has started losing health.
+    Starving {
+        id: Uuid,
+        name: String,
+        favorite: bool,
+    },
+    /// Water quality crossed a warning threshold.
+    WaterAlert { message: String },
+    /// A random incident began.
+    IncidentStarted { kind: IncidentKind, message: String },
+    IncidentEnded { kind: IncidentKind },
+}
+
+/// How much an event matters to the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Good,
+    Warning,
+    Bad,
+}
+
+impl TankEvent {
+    pub fn severity(&self) -> Severity {
+        match self {
+            TankEvent::Born { .. } => Severity::Good,
+            TankEvent::Bred { .. } => Severity::Info,
+            TankEvent::Died(_) => Severity::Bad,
+            TankEvent::Starving { .. } | TankEvent::WaterAlert { .. } => Severity::Warning,
+            TankEvent::IncidentStarted { kind: IncidentKind::Spawning, .. } => Severity::Good,
+            TankEvent::IncidentStarted { .. } => Severity::Warning,
+            TankEvent::IncidentEnded { .. } => Severity::Good,
+        }
+    }
+
+    /// One-line description for toasts and the event log.
+    pub fn describe(&self) -> String {
+        match self {
+            TankEvent::Born { name, species, .. } => format!("{} the {} was born", name, species),
+            TankEvent::Bred { names, .. } => format!("{} and {} spawned", names[0], names[1]),
+            TankEvent::Died(death) => match death.cause {
+                DeathCause::Starvation => format!("{} starved to death", death.name),
+                DeathCause::OldAge => format!("{} died of old age", death.name),
+                DeathCause::PoorWater => format!("{} died from poor water", death.name),
+                DeathCause::Disease => format!("{} died of disease", death.name),
+            },
+            TankEvent::Starving { name, .. } => format!("{} is starving", name),
+            TankEvent::WaterAlert { message } | TankEvent::IncidentStarted { message, .. } => message.clone(),
+            TankEvent::IncidentEnded { kind } => kind.ended().to_string(),
+        }
+    }
+}
+
+/// An event and the tick it happened on.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimedEvent {
+    pub tick: u64,
+    /// Name of the tank it happened in.
+    #[serde(default)]
+    pub tank: String,
+    pub event: TankEvent,
+}
@@ -1,25 +1,186 @@
+use crate::rng::SimRng;
+use crate::species::Species;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Fish below this health are shown as sick.
+pub const SICK_HEALTH: u8 = 50;
+
+/// Which way a fish is swimming; picks the sprite it is drawn with.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum Direction {
     Left,
     Right,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sex {
+    Female,
+    Male,
+}
+
+impl Sex {
+    /// Coin flip from the tank RNG, for reproducible births.
+    pub fn from_rng(rng: &mut SimRng) -> Sex {
+        if rng.below(2) == 0 { Sex::Female } else { Sex::Male }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Sex::Female => "Female",
+            Sex::Male => "Male",
+        }
+    }
+}
+
+/// Inherited color or fin variant. Rare morphs only turn up in bred fish
+/// and fetch a higher price.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Morph {
+    #[default]
+    Common,
+    Golden,
+    Longfin,
+    Albino,
+}
+
+/// Chance in a thousand that a baby shows a rare morph neither parent has.
+const MUTATION_PER_MILLE: u64 = 20;
+
+impl Morph {
+    const RARE: [Morph; 3] = [Morph::Golden, Morph::Longfin, Morph::Albino];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Morph::Common => "Common",
+            Morph::Golden => "Golden",
+            Morph::Longfin => "Longfin",
+            Morph::Albino => "Albino",
+        }
+    }
+
+    /// How much more a fish of this morph sells for than a common one.
+    pub fn price_multiplier(&self) -> u32 {
+        match self {
+            Morph::Common => 1,
+            Morph::Golden => 2,
+            Morph::Longfin => 3,
+            Morph::Albino => 5,
+        }
+    }
+
+    /// A baby's morph: one parent's at random, or occasionally a new rare
+    /// one.
+    pub fn inherit(a: Morph, b: Morph, rng: &mut SimRng) -> Morph {
+        if rng.below(1000) < MUTATION_PER_MILLE {
+            return Morph::RARE[rng.below(Morph::RARE.len() as u64) as usize];
+        }
+        if rng.below(2) == 0 { a } else { b }
+    }
+}
+
+/// Stage of life, from the share of the species lifespan already lived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifeStage {
+    Fry,
+    Juvenile,
+    Adult,
+    Elder,
+}
+
+impl LifeStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LifeStage::Fry => "Fry",
+            LifeStage::Juvenile => "Juvenile",
+            LifeStage::Adult => "Adult",
+            LifeStage::Elder => "Elder",
+        }
+    }
+}
+
+/// Hunger band shared by colors, status glyphs and descriptions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HungerLevel {
+    Fed,
+    Hungry,
+    Critical,
+}
+
+impl HungerLevel {
+    pub fn of(hunger: u8) -> HungerLevel {
+        match hunger {
+            0..50 => HungerLevel::Fed,
+            50..80 => HungerLevel::Hungry,
+            _ => HungerLevel::Critical,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HungerLevel::Fed => "fed",
+            HungerLevel::Hungry => "hungry",
+            HungerLevel::Critical => "starving",
+        }
+    }
+
+    /// Marker drawn next to the sprite so hunger doesn't rely on color.
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            HungerLevel::Fed => "",
+            HungerLevel::Hungry => "~",
+            HungerLevel::Critical => "!",
+        }
+    }
+}
+
+/// A single fish in a tank.
+///
+/// `hunger` climbs from 0 to 100 at the species' rate; at 100 the fish loses
+/// health each tick and dies when `health` reaches 0. `age` counts ticks and
+/// is compared against [`Species::lifespan`](crate::species::Species::lifespan).
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Fish {
     pub id: Uuid,
     pub species: String,
+    /// Column and row inside the tank, from the top-left corner.
     pub position: (u16, u16),
     pub direction: Direction,
     pub hunger: u8,
     pub health: u8,
     pub age: u64,
+    /// Ticks until the fish can breed again.
     pub breeding_cooldown: u32,
+    /// Rolled from the tank's RNG when the fish is added, unless already
+    /// set; fish from old saves are given one when loaded.
+    #[serde(default)]
+    pub sex: Option<Sex>,
+    /// The two parents of a fish bred in the tank; empty for bought fish.
+    #[serde(default)]
+    pub parents: Vec<Uuid>,
+    /// 0 for bought fish, one more than the older parent for bred ones.
+    #[serde(default)]
+    pub generation: u32,
+    /// Player-facing name; fish from old saves may have none.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Highlighted in the tank and lists; the player is told when it gets
+    /// sick or dies.
+    #[serde(default)]
+    pub favorite: bool,
+    /// Tick the fish last ate, for its eating animation.
+    #[serde(default)]
+    pub last_meal: Option<u64>,
+    /// Shock from being moved between tanks with different water, 0-100.
+    /// Wears off over time; a stressed fish won't breed.
+    #[serde(default)]
+    pub stress: u8,
+    #[serde(default)]
+    pub morph: Morph,
 }
 
 impl Fish {
+    /// Create a newborn, fully fed fish of the named species.
     pub fn new(species: String, position: (u16, u16)) -> Self {
         Fish {
             id: Uuid::new_v4(),
@@ -30,6 +191,85 @@ impl Fish {
             health: 100,
             age: 0,
             breeding_cooldown: 0,
+            sex: None,
+            parents: Vec::new(),
+            generation: 0,
+            name: None,
+            favorite: false,
+            last_meal: None,
+            stress: 0,
+            morph: Morph::Common,
+        }
+    }
+
+    /// Healthy, fed, rested and calm enough to spawn with a fish of the same
+    /// species and the other sex.
+    pub fn ready_to_breed(&self) -> bool {
+        self.health > 50 && self.hunger < 50 && self.breeding_cooldown == 0 && self.stress == 0
+    }
+
+    /// Name shown in the UI, falling back to species plus the start of the id.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("{} #{}", self.species, &self.id.simple().to_string()[..4]),
+        }
+    }
+
+    /// Share of the species lifespan lived so far, from 0.0 to 1.0.
+    pub fn age_fraction(&self, species: &Species) -> f32 {
+        if species.lifespan == 0 {
+            return 1.0;
+        }
+        (self.age as f32 / species.lifespan as f32).min(1.0)
+    }
+
+    /// Weak enough to look it: below half health.
+    pub fn is_sick(&self) -> bool {
+        self.health < SICK_HEALTH
+    }
+
+    pub fn hunger_level(&self) -> HungerLevel {
+        HungerLevel::of(self.hunger)
+    }
+
+    /// Everything about the fish in one sentence, for screen readers and
+    /// monochrome terminals.
+    pub fn describe(&self) -> String {
+        let stage = crate::species::get_species(&self.species)
+            .map_or("", |s| self.life_stage(&s).label());
+        let favorite = if self.favorite { ", favorite" } else { "" };
+        let stress = if self.stress > 0 {
+            format!(", stress {}", self.stress)
+        } else {
+            String::new()
+        };
+        let morph = match self.morph {
+            Morph::Common => String::new(),
+            morph => format!("{} ", morph.label().to_lowercase()),
+        };
+        format!(
+            "{}: {} {} {}{}, hunger {} ({}), health {}{}, generation {}{}",
+            self.display_name(),
+            self.sex.map_or("unsexed", |sex| sex.label()).to_lowercase(),
+            stage.to_lowercase(),
+            morph,
+            self.species,
+            self.hunger,
+            self.hunger_level().label(),
+            self.health,
+            stress,
+            self.generation,
+            favorite
+        )
+    }
+
+    pub fn life_stage(&self, species: &Species) -> LifeStage {
+        match self.age_fraction(species) {
+            a if a < 0.1 => LifeStage::Fry,
+            a if a < 0.3 => LifeStage::Juvenile,
+            a if a < 0.8 => LifeStage::Adult,
+            _ => LifeStage::Elder,
         }
     }
 }
@@ -0,0 +1,481 @@
+//! A small HTTP/1.1 server on localhost for dashboards, buttons and scripts.
+//!
+//! Every endpoint is a [`Command`] named by its path. Reads are `GET`s with
+//! arguments in the query string, changes are `POST`s with a JSON object
+//! body, and both answer with JSON:
+//!
+//! - `GET /state`, `GET /fish?tank=1`, `GET /stats?tank=1`
+//! - `POST /feed`, `POST /add {"species": "Guppy"}`,
+//!   `POST /remove {"id": "..."}`, `POST /speed {"speed": 2}`
+//! - `GET /events`: a server-sent event stream of every tank's events, as
+//!   [`TimedEvent`](crate::events::TimedEvent) JSON
+//! - `GET /metrics`: Prometheus metrics, see [`metrics`](crate::metrics)
+//!
+//! The server only listens on 127.0.0.1 and handles one request per
+//! connection. It answers only requests addressed to localhost, so web pages
+//! can't reach it by pointing a DNS name at 127.0.0.1, sends no CORS headers,
+//! and refuses `POST`s from other web pages.
+
+use crate::remote::{Command, Remote};
+use serde_json::{Map, Value, json};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::TryRecvError;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Largest request body accepted.
+const MAX_BODY: usize = 64 * 1024;
+/// Most header lines read before giving up on a request.
+const MAX_HEADERS: usize = 100;
+/// Longest request or header line accepted, in bytes.
+const MAX_LINE: usize = 8 * 1024;
+/// How long a client has to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the event stream checks for new events, and how long it goes
+/// quiet before sending a comment to find out if the client has gone.
+const EVENT_POLL: Duration = Duration::from_millis(100);
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+/// Prometheus text exposition format.
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Listen on `127.0.0.1:port` (any free port for 0) on a background
+/// thread, returning the address bound.
+pub fn spawn(port: u16, remote: Remote) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let address = listener.local_addr()?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let remote = remote.clone();
+            thread::spawn(move || {
+                // Errors here are the client's problem; there's nowhere to
+                // report them while the TUI owns the terminal
+                let _ = handle(stream, &remote, address.port());
+            });
+        }
+    });
+    Ok(address)
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    host: Option<String>,
+    origin: Option<String>,
+    body: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response {
+            status: 200,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Response {
+        Response {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message.into() }).to_string(),
+        }
+    }
+}
+
+fn handle(stream: TcpStream, remote: &Remote, port: u16) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let request = match read_request(&mut reader) {
+        Ok(request) => request,
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            return respond(&stream, Response::error(400, e.to_string()));
+        }
+        Err(e) => return Err(e),
+    };
+
+    if !local_host(request.host.as_deref(), port) {
+        return respond(&stream, Response::error(403, "requests must be addressed to localhost"));
+    }
+    if request.method == "GET" && request.path == "/events" {
+        return stream_events(&stream, remote);
+    }
+    if request.method == "GET" && request.path == "/metrics" {
+        let response = match remote.metrics() {
+            Some(body) => Response {
+                status: 200,
+                content_type: METRICS_CONTENT_TYPE,
+                body,
+            },
+            None => Response::error(503, "The simulation isn't responding"),
+        };
+        return respond(&stream, response);
+    }
+    let response = match parse_command(&request) {
+        Ok(_) if request.method == "POST" && !same_origin(request.origin.as_deref(), port) => {
+            Response::error(403, "POST requests from other web pages are refused")
+        }
+        Ok(command) => match remote.run(command) {
+            Ok(body) => Response::ok(body),
+            Err(message) => Response::error(400, message),
+        },
+        Err(response) => response,
+    };
+    respond(&stream, response)
+}
+
+fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut line = String::new();
+    read_line(reader, &mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        host: None,
+        origin: None,
+        body: Vec::new(),
+    };
+
+    let mut length = 0;
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        read_line(reader, &mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            if length > MAX_BODY {
+                return Err(invalid("request body too large"));
+            }
+            request.body = vec![0; length];
+            reader.read_exact(&mut request.body)?;
+            return Ok(request);
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(invalid("malformed header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value.parse().map_err(|_| invalid("bad content-length"))?;
+        } else if name.eq_ignore_ascii_case("host") {
+            request.host = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("origin") {
+            request.origin = Some(value.to_string());
+        }
+    }
+    Err(invalid("too many headers"))
+}
+
+/// Read one line of at most [`MAX_LINE`] bytes into `line`.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<()> {
+    io::Read::take(reader, MAX_LINE as u64).read_line(line)?;
+    if !line.ends_with('\n') {
+        let message = if line.len() >= MAX_LINE { "line too long" } else { "request ended early" };
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    Ok(())
+}
+
+/// The command a request names: the path, plus query arguments for a `GET`
+/// or the JSON body for a `POST`.
+fn parse_command(request: &Request) -> Result<Command, Response> {
+    let name = request.path.trim_start_matches('/');
+    let mut arguments = match request.method.as_str() {
+        "GET" => query_arguments(&request.query),
+        "POST" if request.body.iter().all(u8::is_ascii_whitespace) => Map::new(),
+        "POST" => serde_json::from_slice(&request.body)
+            .map_err(|e| Response::error(400, format!("body must be a JSON object: {}", e)))?,
+        _ => return Err(Response::error(405, "only GET and POST are supported")),
+    };
+    arguments.insert("command".to_string(), Value::String(name.to_string()));
+    let command: Command = serde_json::from_value(Value::Object(arguments)).map_err(|e| {
+        if e.to_string().contains("unknown variant") {
+            Response::error(404, format!("no endpoint {}", request.path))
+        } else {
+            Response::error(400, e.to_string())
+        }
+    })?;
+    let method = if command.is_query() { "GET" } else { "POST" };
+    if request.method != method {
+        return Err(Response::error(405, format!("{} takes {}", request.path, method)));
+    }
+    Ok(command)
+}
+
+/// `tank=1&species=Neon%20Tetra` as JSON, with values that parse as JSON
+/// (numbers, booleans) kept as such and the rest as strings.
+fn query_arguments(query: &str) -> Map<String, Value> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| {
+            let value = percent_decode(value);
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            (percent_decode(key), value)
+        })
+        .collect()
+}
+
+/// Undo URL encoding: `+` is a space and `%XX` a byte. A `%` not followed
+/// by two hex digits is kept as it is.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let digit = |i: usize| bytes.get(i).and_then(|b| (*b as char).to_digit(16));
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let (Some(high), Some(low)) = (digit(i + 1), digit(i + 2))
+        {
+            decoded.push((high * 16 + low) as u8);
+            i += 3;
+            continue;
+        }
+        decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Whether a `Host` header names this server by a loopback name. Every
+/// HTTP/1.1 client sends one.
+fn local_host(host: Option<&str>, port: u16) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let (name, host_port) = match host.rsplit_once(':') {
+        Some((name, host_port)) => (name, host_port.parse().ok()),
+        None => (host, Some(80)),
+    };
+    (name == "127.0.0.1" || name.eq_ignore_ascii_case("localhost")) && host_port == Some(port)
+}
+
+/// Browsers send an `Origin` with cross-site `POST`s; tools like curl
+/// don't send one at all.
+fn same_origin(origin: Option<&str>, port: u16) -> bool {
+    origin.is_none_or(|origin| {
+        origin == format!("http://127.0.0.1:{}", port) || origin == format!("http://localhost:{}", port)
+    })
+}
+
+fn respond(mut stream: &TcpStream, response: Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Send every tank's events until the client goes away or the tanks are
+/// replaced. Clients such as `EventSource` reconnect on their own.
+fn stream_events(mut stream: &TcpStream, remote: &Remote) -> io::Result<()> {
+    let Some(receivers) = remote.subscribe() else {
+        return respond(stream, Response::error(503, "The simulation has stopped"));
+    };
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+         Connection: close\r\n\r\n"
+    )?;
+    stream.flush()?;
+
+    let mut last_write = Instant::now();
+    loop {
+        let mut open = false;
+        for receiver in &receivers {
+            loop {
+                match receiver.try_recv() {
+                    Ok(event) => {
+                        let json = serde_json::to_string(&event)?;
+                        write!(stream, "data: {}\n\n", json)?;
+                        last_write = Instant::now();
+                    }
+                    Err(TryRecvError::Empty) => {
+                        open = true;
+                        break;
+                    }
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+        }
+        if !open {
+            return Ok(());
+        }
+        if last_write.elapsed() >= KEEP_ALIVE {
+            stream.write_all(b": keep-alive\n\n")?;
+            last_write = Instant::now();
+        }
+        stream.flush()?;
+        thread::sleep(EVENT_POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aquarium::Aquarium;
+    use crate::remote;
+    use crate::tank::Tank;
+    use std::io::{Cursor, Read};
+
+    fn read(text: &str) -> io::Result<Request> {
+        read_request(&mut BufReader::new(Cursor::new(text.as_bytes().to_vec())))
+    }
+
+    #[test]
+    fn reads_requests() {
+        let request = read(
+            "POST /add?x=1 HTTP/1.1\r\nHost: 127.0.0.1:7878\r\nContent-Length: 7\r\n\r\n{\"a\":1}",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/add");
+        assert_eq!(request.query, "x=1");
+        assert_eq!(request.host.as_deref(), Some("127.0.0.1:7878"));
+        assert_eq!(request.body, b"{\"a\":1}");
+    }
+
+    #[test]
+    fn rejects_long_lines_and_cut_off_requests() {
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE));
+        let error = read(&long).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "line too long");
+
+        let header = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(MAX_LINE));
+        assert_eq!(read(&header).err().unwrap().to_string(), "line too long");
+        assert_eq!(read("GET / HTTP/1.1\r\nHost: loc").err().unwrap().to_string(), "request ended early");
+
+        let many = format!("GET / HTTP/1.1\r\n{}\r\n", "X: 1\r\n".repeat(MAX_HEADERS));
+        assert_eq!(read(&many).err().unwrap().to_string(), "too many headers");
+    }
+
+    #[test]
+    fn only_answers_requests_for_localhost() {
+        assert!(local_host(Some("127.0.0.1:7878"), 7878));
+        assert!(local_host(Some("localhost:7878"), 7878));
+        assert!(local_host(Some("LocalHost:7878"), 7878));
+        assert!(local_host(Some("localhost"), 80));
+        assert!(!local_host(Some("localhost"), 7878));
+        assert!(!local_host(Some("localhost:8080"), 7878));
+        assert!(!local_host(Some("evil.example:7878"), 7878));
+        assert!(!local_host(Some("127.0.0.1.evil.example:7878"), 7878));
+        assert!(!local_host(None, 7878));
+    }
+
+    #[test]
+    fn only_takes_posts_from_the_same_origin() {
+        assert!(same_origin(None, 7878));
+        assert!(same_origin(Some("http://localhost:7878"), 7878));
+        assert!(!same_origin(Some("http://evil.example"), 7878));
+    }
+
+    #[test]
+    fn parses_commands() {
+        let request = |method: &str, path: &str, query: &str, body: &str| Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.to_string(),
+            host: None,
+            origin: None,
+            body: body.as_bytes().to_vec(),
+        };
+        let command = parse_command(&request("GET", "/fish", "tank=1", "")).unwrap();
+        assert!(matches!(command, Command::Fish { tank: Some(1) }));
+        let command = parse_command(&request("POST", "/add", "", "{\"species\": \"Guppy\"}")).unwrap();
+        assert!(matches!(command, Command::Add { species, tank: None } if species == "Guppy"));
+        assert!(matches!(parse_command(&request("POST", "/feed", "", "")), Ok(Command::Feed { tank: None })));
+
+        let status = |method, path, body| parse_command(&request(method, path, "", body)).err().unwrap().status;
+        assert_eq!(status("GET", "/nowhere", ""), 404);
+        assert_eq!(status("POST", "/state", ""), 405);
+        assert_eq!(status("GET", "/feed", ""), 405);
+        assert_eq!(status("PUT", "/feed", ""), 405);
+        assert_eq!(status("POST", "/add", "not json"), 400);
+        assert_eq!(status("POST", "/add", "{}"), 400);
+    }
+
+    #[test]
+    fn decodes_query_values() {
+        let arguments = query_arguments("species=Neon%20Tetra&name=Mr+Bubbles&tank=1&note=caf%C3%A9+100%&bad=%zz&sign=%+1");
+        assert_eq!(arguments["species"], "Neon Tetra");
+        assert_eq!(arguments["name"], "Mr Bubbles");
+        assert_eq!(arguments["tank"], 1);
+        assert_eq!(arguments["note"], "café 100%");
+        assert_eq!(arguments["bad"], "%zz");
+        assert_eq!(arguments["sign"], "% 1");
+        assert_eq!(query_arguments("quoted=%221%22")["quoted"], "1");
+        assert_eq!(query_arguments("a%2Bb=c%26d")["a+b"], "c&d");
+    }
+
+    /// Send `request` to a server on a free port while the test thread plays
+    /// the main loop, returning the raw response.
+    fn exchange(request: impl FnOnce(u16) -> String) -> String {
+        let (remote, requests) = remote::channel();
+        let address = spawn(0, remote).unwrap();
+        assert_ne!(address.port(), 0);
+        let request = request(address.port());
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let mut aquarium = Aquarium::starter(Tank::with_seed(60, 20, 7));
+        while !client.is_finished() {
+            requests.handle(&mut aquarium);
+            thread::sleep(Duration::from_millis(5));
+        }
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn serves_metrics() {
+        let response = exchange(|port| format!("GET /metrics HTTP/1.1\r\nHost: 127.0.0.1:{}\r\n\r\n", port));
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains(METRICS_CONTENT_TYPE));
+        assert!(!head.contains("Access-Control"));
+        assert!(body.contains("# TYPE aquarium_fish gauge\n"));
+        assert!(body.contains("# TYPE aquarium_ticks_total counter\n"));
+        assert!(body.contains("aquarium_ticks_total{tank_id=\"0\",tank=\"Display\"} 0\n"));
+        assert!(body.contains("aquarium_ticks_total{tank_id=\"2\",tank=\"Quarantine\"} 0\n"));
+        assert!(body.contains("aquarium_tick_duration_seconds_count 0\n"));
+    }
+
+    #[test]
+    fn refuses_other_hosts() {
+        let response = exchange(|_| "GET /state HTTP/1.1\r\nHost: evil.example\r\n\r\n".to_string());
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+        let response = exchange(|_| "GET /state HTTP/1.1\r\n\r\n".to_string());
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+    }
+}
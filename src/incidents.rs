@@ -0,0 +1,504 @@
+//! Random trouble: power outages, heatwaves, snails, disease and spawning.
+//!
+//! A tank with incidents switched on rolls for a new one each tick with its
+//! own seeded RNG, so seeded runs stay reproducible. Each incident lasts a
+//! while and works through the normal systems: an outage switches the
+//! equipment off, a heatwave warms the room, snails add waste, disease
+//! stresses fish until the stress wears their health down, and spawning
+//! clears breeding cooldowns.
+
+use crate::events::TankEvent;
+use crate::fish::Fish;
+use crate::rng::SimRng;
+use crate::water::{Equipment, ROOM_TEMPERATURE};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Average ticks between incidents in a tank.
+const DEFAULT_INTERVAL: u64 = 3000;
+/// Room temperature during a heatwave, in °C.
+const HEATWAVE_TEMPERATURE: f32 = 31.0;
+/// Snails at the start of an infestation, the most there can be, and the
+/// ticks it takes for one more to hatch.
+const SNAILS: (u32, u32) = (3, 30);
+const SNAIL_BREEDING_TICKS: u64 = 100;
+/// Snails that make as much waste as one fish.
+const SNAILS_PER_FISH: u32 = 2;
+/// Ticks after a fish arrives from outside that it can bring in disease.
+const ARRIVAL_WINDOW: u64 = 500;
+/// Ticks between disease checks, stress added to each sick fish per check,
+/// and the per-mille chance each sick fish passes it on.
+const DISEASE_INTERVAL: u64 = 10;
+const DISEASE_STRESS: u8 = 3;
+const CONTAGION_PER_MILLE: u64 = 50;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentKind {
+    /// Heater and filter stop.
+    PowerOutage,
+    /// The room heats up; a heater can't cool the tank.
+    Heatwave,
+    /// Snails multiply and add to the waste.
+    Snails,
+    /// A new arrival brings in a disease that spreads between fish.
+    Disease,
+    /// Every fish is ready to breed at once.
+    Spawning,
+}
+
+impl IncidentKind {
+    pub const ALL: [IncidentKind; 5] = [
+        IncidentKind::PowerOutage,
+        IncidentKind::Heatwave,
+        IncidentKind::Snails,
+        IncidentKind::Disease,
+        IncidentKind::Spawning,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IncidentKind::PowerOutage => "Power outage",
+            IncidentKind::Heatwave => "Heatwave",
+            IncidentKind::Snails => "Snail infestation",
+            IncidentKind::Disease => "Disease outbreak",
+            IncidentKind::Spawning => "Spawning",
+        }
+    }
+
+    /// Shortest and longest run, in ticks.
+    fn duration(&self) -> (u64, u64) {
+        match self {
+            IncidentKind::PowerOutage => (100, 400),
+            IncidentKind::Heatwave => (800, 2000),
+            IncidentKind::Snails => (1000, 3000),
+            IncidentKind::Disease => (400, 1000),
+            IncidentKind::Spawning => (100, 300),
+        }
+    }
+
+    /// What the player is told when it ends.
+    pub fn ended(&self) -> &'static str {
+        match self {
+            IncidentKind::PowerOutage => "The power is back on",
+            IncidentKind::Heatwave => "The heatwave has broken",
+            IncidentKind::Snails => "The snails have died back",
+            IncidentKind::Disease => "The disease has run its course",
+            IncidentKind::Spawning => "The spawning season is over",
+        }
+    }
+}
+
+/// An incident under way.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Incident {
+    pub kind: IncidentKind,
+    pub started: u64,
+    pub ends: u64,
+    /// Snails in the tank, for an infestation.
+    #[serde(default)]
+    pub snails: u32,
+    /// Sick fish, for an outbreak.
+    #[serde(default)]
+    pub infected: Vec<Uuid>,
+}
+
+/// The `[incidents]` config table, also kept per tank.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct IncidentSettings {
+    pub enabled: bool,
+    /// Average ticks between incidents.
+    pub interval: u64,
+    /// Kinds that can happen: `power_outage`, `heatwave`, `snails`,
+    /// `disease` and `spawning`.
+    pub kinds: Vec<IncidentKind>,
+}
+
+impl Default for IncidentSettings {
+    fn default() -> Self {
+        IncidentSettings {
+            enabled: false,
+            interval: DEFAULT_INTERVAL,
+            kinds: IncidentKind::ALL.to_vec(),
+        }
+    }
+}
+
+/// A tank's incident settings and whatever is going on.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Incidents {
+    pub settings: IncidentSettings,
+    pub active: Vec<Incident>,
+    /// The last fish to come in from outside, and when.
+    pub arrival: Option<(Uuid, u64)>,
+}
+
+impl Incidents {
+    pub fn is_active(&self, kind: IncidentKind) -> bool {
+        self.active.iter().any(|incident| incident.kind == kind)
+    }
+
+    /// Whether a fish is sick in an outbreak under way.
+    pub fn is_infected(&self, fish: Uuid) -> bool {
+        self.active
+            .iter()
+            .any(|incident| incident.kind == IncidentKind::Disease && incident.infected.contains(&fish))
+    }
+
+    /// "Power outage, Snail infestation (5 snails)", or empty when all is
+    /// quiet.
+    pub fn summary(&self) -> String {
+        let labels: Vec<String> = self
+            .active
+            .iter()
+            .map(|incident| match incident.kind {
+                IncidentKind::Snails => format!("{} ({} snails)", incident.kind.label(), incident.snails),
+                IncidentKind::Disease => format!("{} ({} sick)", incident.kind.label(), incident.infected.len()),
+                kind => kind.label().to_string(),
+            })
+            .collect();
+        labels.join(", ")
+    }
+
+    /// Note a fish arriving from outside the tank.
+    pub fn arrived(&mut self, fish: Uuid, tick: u64) {
+        self.arrival = Some((fish, tick));
+    }
+
+    /// The equipment as it runs now: off in a power outage.
+    pub fn equipment(&self, installed: &Equipment) -> Equipment {
+        if self.is_active(IncidentKind::PowerOutage) {
+            Equipment {
+                heater: None,
+                filter: 0.0,
+            }
+        } else {
+            installed.clone()
+        }
+    }
+
+    /// Temperature an unheated tank drifts towards.
+    pub fn room_temperature(&self) -> f32 {
+        if self.is_active(IncidentKind::Heatwave) {
+            HEATWAVE_TEMPERATURE
+        } else {
+            ROOM_TEMPERATURE
+        }
+    }
+
+    /// Extra waste from snails, counted in fish.
+    pub fn waste(&self) -> usize {
+        let snails: u32 = self.active.iter().map(|incident| incident.snails).sum();
+        (snails / SNAILS_PER_FISH) as usize
+    }
+
+    /// One tick: end incidents that are over, maybe start a new one, and
+    /// apply the ongoing ones to `fish`. Returns what the player should
+    /// hear about.
+    pub fn update(&mut self, tick: u64, fish: &mut [Fish], rng: &mut SimRng) -> Vec<TankEvent> {
+        let mut events = Vec::new();
+        self.active.retain(|incident| {
+            let over = tick >= incident.ends;
+            if over {
+                events.push(TankEvent::IncidentEnded { kind: incident.kind });
+            }
+            !over
+        });
+        if self.settings.enabled
+            && let Some(event) = self.roll(tick, fish, rng)
+        {
+            events.push(event);
+        }
+
+        for incident in &mut self.active {
+            match incident.kind {
+                IncidentKind::Snails => {
+                    let age = tick - incident.started;
+                    if age > 0 && age.is_multiple_of(SNAIL_BREEDING_TICKS) {
+                        incident.snails = (incident.snails + 1).min(SNAILS.1);
+                    }
+                }
+                IncidentKind::Disease => {
+                    incident.infected.retain(|id| fish.iter().any(|f| f.id == *id));
+                    if !tick.is_multiple_of(DISEASE_INTERVAL) {
+                        continue;
+                    }
+                    let mut caught = Vec::new();
+                    for _ in &incident.infected {
+                        if rng.below(1000) < CONTAGION_PER_MILLE {
+                            let healthy: Vec<Uuid> = fish
+                                .iter()
+                                .map(|f| f.id)
+                                .filter(|id| !incident.infected.contains(id) && !caught.contains(id))
+                                .collect();
+                            if !healthy.is_empty() {
+                                caught.push(healthy[rng.below(healthy.len() as u64) as usize]);
+                            }
+                        }
+                    }
+                    incident.infected.extend(caught);
+                    for f in fish.iter_mut().filter(|f| incident.infected.contains(&f.id)) {
+                        f.stress = f.stress.saturating_add(DISEASE_STRESS).min(100);
+                    }
+                }
+                IncidentKind::Spawning => {
+                    for f in fish.iter_mut() {
+                        f.breeding_cooldown = f.breeding_cooldown.saturating_sub(1);
+                    }
+                }
+                IncidentKind::PowerOutage | IncidentKind::Heatwave => {}
+            }
+        }
+        events
+    }
+
+    /// Start an incident with a one in `interval` chance, picked from the
+    /// kinds that aren't already under way and make sense right now.
+    fn roll(&mut self, tick: u64, fish: &mut [Fish], rng: &mut SimRng) -> Option<TankEvent> {
+        if rng.below(self.settings.interval.max(1)) != 0 {
+            return None;
+        }
+        let carrier = self
+            .arrival
+            .filter(|(_, arrived)| tick.saturating_sub(*arrived) <= ARRIVAL_WINDOW)
+            .and_then(|(id, _)| fish.iter().find(|f| f.id == id))
+            .map(|f| (f.id, f.display_name()));
+        let eligible: Vec<IncidentKind> = self
+            .settings
+            .kinds
+            .iter()
+            .copied()
+            .filter(|kind| !self.is_active(*kind))
+            .filter(|kind| match kind {
+                IncidentKind::Disease => carrier.is_some(),
+                IncidentKind::Snails | IncidentKind::Spawning => !fish.is_empty(),
+                IncidentKind::PowerOutage | IncidentKind::Heatwave => true,
+            })
+            .collect();
+        if eligible.is_empty() {
+            return None;
+        }
+        let kind = eligible[rng.below(eligible.len() as u64) as usize];
+        let (shortest, longest) = kind.duration();
+        let mut incident = Incident {
+            kind,
+            started: tick,
+            ends: tick + shortest + rng.below(longest - shortest + 1),
+            snails: 0,
+            infected: Vec::new(),
+        };
+        let message = match kind {
+            IncidentKind::PowerOutage => "Power outage: the heater and filter are off".to_string(),
+            IncidentKind::Heatwave => "Heatwave: the room is warming up".to_string(),
+            IncidentKind::Snails => {
+                incident.snails = SNAILS.0;
+                "Snail infestation: snails are fouling the water".to_string()
+            }
+            IncidentKind::Disease => {
+                let (id, name) = carrier?;
+                incident.infected.push(id);
+                self.arrival = None;
+                format!("Disease outbreak: {} brought in a disease", name)
+            }
+            IncidentKind::Spawning => {
+                for f in fish.iter_mut() {
+                    f.breeding_cooldown = 0;
+                }
+                "Spawning: the fish are ready to breed".to_string()
+            }
+        };
+        self.active.push(incident);
+        Some(TankEvent::IncidentStarted { kind, message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tank::Tank;
+
+    /// Incidents switched on for `kinds`, rolling a new one every tick.
+    fn always(kinds: &[IncidentKind]) -> Incidents {
+        Incidents {
+            settings: IncidentSettings {
+                enabled: true,
+                interval: 1,
+                kinds: kinds.to_vec(),
+            },
+            ..Incidents::default()
+        }
+    }
+
+    fn guppies(count: usize) -> Vec<Fish> {
+        (0..count).map(|n| Fish::new("Guppy".to_string(), (n as u16, 5))).collect()
+    }
+
+    fn started(events: &[TankEvent]) -> Vec<IncidentKind> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TankEvent::IncidentStarted { kind, .. } => Some(*kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_seed_rolls_the_same_incidents() {
+        let run = |seed| {
+            let mut incidents = Incidents {
+                settings: IncidentSettings { enabled: true, interval: 200, ..IncidentSettings::default() },
+                ..Incidents::default()
+            };
+            let mut fish = guppies(6);
+            incidents.arrived(fish[0].id, 0);
+            let mut rng = SimRng::new(seed);
+            let mut log = Vec::new();
+            for tick in 1..=5000 {
+                for event in incidents.update(tick, &mut fish, &mut rng) {
+                    log.push(format!("{} {:?}", tick, event));
+                }
+            }
+            let stress: Vec<u8> = fish.iter().map(|f| f.stress).collect();
+            (log, stress)
+        };
+        let (log, stress) = run(11);
+        assert!(!log.is_empty());
+        assert_eq!(run(11), (log.clone(), stress));
+        assert_ne!(run(12).0, log);
+    }
+
+    #[test]
+    fn nothing_happens_when_switched_off() {
+        let mut incidents = always(&IncidentKind::ALL);
+        incidents.settings.enabled = false;
+        let mut fish = guppies(3);
+        incidents.arrived(fish[0].id, 0);
+        let mut rng = SimRng::new(1);
+        for tick in 1..=1000 {
+            assert!(incidents.update(tick, &mut fish, &mut rng).is_empty());
+        }
+        assert!(incidents.active.is_empty());
+
+        let mut incidents = always(&[]);
+        for tick in 1..=1000 {
+            assert!(incidents.update(tick, &mut fish, &mut rng).is_empty());
+        }
+    }
+
+    #[test]
+    fn an_outage_switches_the_equipment_off_until_it_ends() {
+        let mut incidents = always(&[IncidentKind::PowerOutage]);
+        let mut rng = SimRng::new(1);
+        assert_eq!(started(&incidents.update(1, &mut [], &mut rng)), [IncidentKind::PowerOutage]);
+        let off = incidents.equipment(&Equipment::default());
+        assert_eq!((off.heater, off.filter), (None, 0.0));
+
+        // Only one of a kind at a time
+        assert!(incidents.update(2, &mut [], &mut rng).is_empty());
+        incidents.settings.enabled = false;
+        let ends = incidents.active[0].ends;
+        let events = incidents.update(ends, &mut [], &mut rng);
+        assert!(matches!(events[..], [TankEvent::IncidentEnded { kind: IncidentKind::PowerOutage }]));
+        assert_eq!(incidents.equipment(&Equipment::default()).heater, Equipment::default().heater);
+    }
+
+    #[test]
+    fn a_heated_tank_cools_in_an_outage() {
+        let temperature = |outage: bool| {
+            let mut tank = Tank::with_seed(60, 20, 1);
+            if outage {
+                tank.incidents.active.push(Incident {
+                    kind: IncidentKind::PowerOutage,
+                    started: 0,
+                    ends: 10_000,
+                    snails: 0,
+                    infected: Vec::new(),
+                });
+            }
+            for _ in 0..2000 {
+                tank.tick();
+            }
+            tank.water.temperature
+        };
+        assert!(temperature(true) < temperature(false) - 1.0);
+    }
+
+    #[test]
+    fn a_heatwave_warms_the_room() {
+        let mut incidents = always(&[IncidentKind::Heatwave]);
+        assert_eq!(incidents.room_temperature(), ROOM_TEMPERATURE);
+        incidents.update(1, &mut [], &mut SimRng::new(1));
+        assert_eq!(incidents.room_temperature(), HEATWAVE_TEMPERATURE);
+        assert!(incidents.equipment(&Equipment::default()).heater.is_some());
+    }
+
+    #[test]
+    fn snails_breed_and_add_waste() {
+        let mut incidents = always(&[IncidentKind::Snails]);
+        let mut rng = SimRng::new(1);
+        // Snails need fish to feed on
+        assert!(incidents.update(1, &mut [], &mut rng).is_empty());
+
+        let mut fish = guppies(1);
+        assert_eq!(started(&incidents.update(1, &mut fish, &mut rng)), [IncidentKind::Snails]);
+        assert_eq!(incidents.active[0].snails, SNAILS.0);
+        assert_eq!(incidents.waste(), 1);
+        incidents.settings.enabled = false;
+        incidents.active[0].ends = u64::MAX;
+        for tick in 2..=1 + SNAIL_BREEDING_TICKS {
+            incidents.update(tick, &mut fish, &mut rng);
+        }
+        assert_eq!(incidents.active[0].snails, SNAILS.0 + 1);
+        assert_eq!(incidents.waste(), 2);
+        for tick in 2 + SNAIL_BREEDING_TICKS..=SNAIL_BREEDING_TICKS * 40 {
+            incidents.update(tick, &mut fish, &mut rng);
+        }
+        assert_eq!(incidents.active[0].snails, SNAILS.1);
+    }
+
+    #[test]
+    fn disease_comes_with_a_new_arrival_and_spreads() {
+        let mut incidents = always(&[IncidentKind::Disease]);
+        let mut rng = SimRng::new(1);
+        let mut fish = guppies(8);
+        // Nobody new has come in to carry it
+        assert!(incidents.update(1, &mut fish, &mut rng).is_empty());
+        incidents.arrived(fish[0].id, 1);
+        assert!(incidents.update(1 + ARRIVAL_WINDOW + 1, &mut fish, &mut rng).is_empty());
+
+        incidents.arrived(fish[0].id, 1000);
+        assert_eq!(started(&incidents.update(1001, &mut fish, &mut rng)), [IncidentKind::Disease]);
+        assert!(incidents.is_infected(fish[0].id));
+        assert!(incidents.arrival.is_none());
+
+        incidents.settings.enabled = false;
+        incidents.active[0].ends = u64::MAX;
+        for tick in 1002..=3000 {
+            incidents.update(tick, &mut fish, &mut rng);
+        }
+        assert!(incidents.active[0].infected.len() > 1);
+        assert!(fish.iter().all(|f| incidents.is_infected(f.id) == (f.stress > 0)));
+        assert_eq!(fish[0].stress, 100);
+    }
+
+    #[test]
+    fn spawning_readies_every_fish_to_breed() {
+        let mut incidents = always(&[IncidentKind::Spawning]);
+        let mut fish = guppies(3);
+        for f in &mut fish {
+            f.breeding_cooldown = 500;
+        }
+        let events = incidents.update(1, &mut fish, &mut SimRng::new(1));
+        assert_eq!(started(&events), [IncidentKind::Spawning]);
+        assert!(fish.iter().all(|f| f.breeding_cooldown == 0));
+
+        // Cooldowns from breeding during the season run out quickly
+        fish[0].breeding_cooldown = 500;
+        incidents.settings.enabled = false;
+        incidents.update(2, &mut fish, &mut SimRng::new(1));
+        assert_eq!(fish[0].breeding_cooldown, 499);
+    }
+}
@@ -0,0 +1,1007 @@
+//! Key chords mapped to actions, per input context.
+//!
+//! The TUI never matches on `KeyCode` directly: it asks the active [`Keymap`]
+//! which [`Action`] a key means in the current [`Context`]. The footer and
+//! help overlay are generated from the same map, so rebinding a key in
+//! `config.toml` updates them too.
+
+use crate::config::KeysConfig;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Which screen is receiving input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Context {
+    Normal,
+    Picker,
+    Help,
+    Roster,
+    /// Text entry (rename, search). Unbound keys are typed as text.
+    Prompt,
+    Stats,
+    EventLog,
+    Lineage,
+    Shop,
+    /// Choosing the mode for a new game.
+    ModeSelect,
+    /// Water changes and other tank care.
+    Maintenance,
+}
+
+impl Context {
+    fn config_name(&self) -> &'static str {
+        match self {
+            Context::Normal => "normal",
+            Context::Picker => "picker",
+            Context::Help => "help",
+            Context::Roster => "roster",
+            Context::Prompt => "prompt",
+            Context::Stats => "stats",
+            Context::EventLog => "log",
+            Context::Lineage => "lineage",
+            Context::Shop => "shop",
+            Context::ModeSelect => "mode_select",
+            Context::Maintenance => "maintenance",
+        }
+    }
+}
+
+/// Everything a key press can ask the app to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    TogglePause,
+    Feed,
+    SpeedUp,
+    SpeedDown,
+    SelectNext,
+    SelectPrev,
+    AddFish,
+    RemoveFish,
+    /// Toggle feed mode, where clicking the water drops food.
+    ToggleFeedMode,
+    /// Show or hide the selected-fish inspector panel.
+    ToggleInspector,
+    /// Feed only the selected fish.
+    FeedSelected,
+    /// Open a prompt to rename the selected fish.
+    Rename,
+    ToggleFavorite,
+    OpenHelp,
+    /// Open the full-screen fish roster.
+    OpenRoster,
+    /// Open the population statistics dashboard.
+    OpenStats,
+    /// Open the scrollable log of births, deaths and alerts.
+    OpenEventLog,
+    /// Open the family tree of the selected fish.
+    OpenLineage,
+    /// Refocus the family tree on the previous fish.
+    Back,
+    /// Write the family tree to a Graphviz DOT file.
+    ExportDot,
+    /// Show status glyphs and full text descriptions.
+    ToggleAccessible,
+    /// Swap the tank picture for a plain-text, line-by-line view.
+    ToggleTextView,
+    /// Move the camera over a tank larger than the view.
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    /// Keep the camera centred on the selected fish.
+    ToggleFollow,
+    /// Show or hide the whole-tank overview.
+    ToggleMinimap,
+    /// Switch to the next or previous tank.
+    NextTank,
+    PrevTank,
+    /// Move the selected fish into another tank.
+    NetFish,
+    /// Open the shop (economy mode only).
+    OpenShop,
+    /// Buy the item under the shop cursor.
+    Buy,
+    /// Sell the selected fish to the shop.
+    SellFish,
+    /// Open the list of achievements.
+    OpenAchievements,
+    /// Turn random incidents on or off in the tank on screen.
+    ToggleIncidents,
+    /// Open the maintenance screen for the tank on screen.
+    OpenMaintenance,
+    /// Lower or raise the highlighted setting.
+    Decrease,
+    Increase,
+    /// Switch the stats screen to the next time window.
+    CycleWindow,
+    /// Pick the nth species (0-based) in the picker.
+    PickSpecies(usize),
+    CursorUp,
+    CursorDown,
+    CycleSort,
+    ReverseSort,
+    CycleFilter,
+    /// Start typing a search query.
+    StartSearch,
+    /// Mark or unmark the row under the cursor.
+    ToggleMark,
+    MarkAll,
+    /// Remove the marked fish (or the cursor row if none are marked).
+    RemoveMarked,
+    /// Select the fish under the cursor and return to the tank.
+    Confirm,
+    Close,
+}
+
+/// Actions in the order they are listed in the help overlay.
+const NORMAL_ACTIONS: [Action; 34] = [
+    Action::Feed,
+    Action::ToggleFeedMode,
+    Action::ToggleInspector,
+    Action::OpenRoster,
+    Action::OpenStats,
+    Action::OpenEventLog,
+    Action::OpenLineage,
+    Action::OpenAchievements,
+    Action::OpenMaintenance,
+    Action::FeedSelected,
+    Action::Rename,
+    Action::ToggleFavorite,
+    Action::AddFish,
+    Action::RemoveFish,
+    Action::NextTank,
+    Action::PrevTank,
+    Action::NetFish,
+    Action::OpenShop,
+    Action::ToggleIncidents,
+    Action::SelectNext,
+    Action::SelectPrev,
+    Action::TogglePause,
+    Action::PanLeft,
+    Action::PanRight,
+    Action::PanUp,
+    Action::PanDown,
+    Action::ToggleFollow,
+    Action::ToggleMinimap,
+    Action::ToggleAccessible,
+    Action::ToggleTextView,
+    Action::SpeedUp,
+    Action::SpeedDown,
+    Action::OpenHelp,
+    Action::Quit,
+];
+
+/// Roster actions shown in its footer (cursor keys are left implied).
+pub const ROSTER_ACTIONS: [Action; 9] = [
+    Action::CycleSort,
+    Action::ReverseSort,
+    Action::CycleFilter,
+    Action::StartSearch,
+    Action::ToggleMark,
+    Action::MarkAll,
+    Action::RemoveMarked,
+    Action::Confirm,
+    Action::Close,
+];
+
+/// Actions shown at the bottom of the stats screen.
+pub const STATS_ACTIONS: [Action; 2] = [Action::CycleWindow, Action::Close];
+
+/// Actions shown at the bottom of the event log.
+pub const LOG_ACTIONS: [Action; 3] = [Action::CursorUp, Action::CursorDown, Action::Close];
+
+/// Actions shown at the bottom of the family tree.
+pub const LINEAGE_ACTIONS: [Action; 4] = [Action::Confirm, Action::Back, Action::ExportDot, Action::Close];
+
+/// Actions shown at the bottom of the shop.
+pub const SHOP_ACTIONS: [Action; 3] = [Action::Buy, Action::SellFish, Action::Close];
+
+/// Actions shown at the bottom of the maintenance screen.
+pub const MAINTENANCE_ACTIONS: [Action; 4] = [Action::Confirm, Action::Decrease, Action::Increase, Action::Close];
+
+/// Actions shown under the mode selector.
+pub const MODE_ACTIONS: [Action; 2] = [Action::Confirm, Action::Quit];
+
+/// The subset that fits in the footer.
+const FOOTER_ACTIONS: [Action; 9] = [
+    Action::Feed,
+    Action::AddFish,
+    Action::RemoveFish,
+    Action::SelectNext,
+    Action::TogglePause,
+    Action::SpeedUp,
+    Action::SpeedDown,
+    Action::OpenHelp,
+    Action::Quit,
+];
+
+impl Action {
+    /// Name used in `config.toml`, e.g. `feed` or `pick_species_2`.
+    pub fn config_name(&self) -> String {
+        match self {
+            Action::Quit => "quit".to_string(),
+            Action::TogglePause => "toggle_pause".to_string(),
+            Action::Feed => "feed".to_string(),
+            Action::SpeedUp => "speed_up".to_string(),
+            Action::SpeedDown => "speed_down".to_string(),
+            Action::SelectNext => "select_next".to_string(),
+            Action::SelectPrev => "select_prev".to_string(),
+            Action::AddFish => "add_fish".to_string(),
+            Action::RemoveFish => "remove_fish".to_string(),
+            Action::ToggleFeedMode => "feed_mode".to_string(),
+            Action::ToggleInspector => "inspector".to_string(),
+            Action::FeedSelected => "feed_selected".to_string(),
+            Action::Rename => "rename".to_string(),
+            Action::ToggleFavorite => "favorite".to_string(),
+            Action::OpenHelp => "help".to_string(),
+            Action::OpenRoster => "roster".to_string(),
+            Action::OpenStats => "stats".to_string(),
+            Action::OpenEventLog => "log".to_string(),
+            Action::OpenLineage => "lineage".to_string(),
+            Action::ToggleAccessible => "accessible".to_string(),
+            Action::ToggleTextView => "text_view".to_string(),
+            Action::PanLeft => "pan_left".to_string(),
+            Action::PanRight => "pan_right".to_string(),
+            Action::PanUp => "pan_up".to_string(),
+            Action::PanDown => "pan_down".to_string(),
+            Action::ToggleFollow => "follow".to_string(),
+            Action::ToggleMinimap => "minimap".to_string(),
+            Action::NextTank => "next_tank".to_string(),
+            Action::PrevTank => "prev_tank".to_string(),
+            Action::NetFish => "net".to_string(),
+            Action::OpenShop => "shop".to_string(),
+            Action::Buy => "buy".to_string(),
+            Action::SellFish => "sell".to_string(),
+            Action::OpenAchievements => "achievements".to_string(),
+            Action::ToggleIncidents => "incidents".to_string(),
+            Action::OpenMaintenance => "maintenance".to_string(),
+            Action::Decrease => "decrease".to_string(),
+            Action::Increase => "increase".to_string(),
+            Action::Back => "back".to_string(),
+            Action::ExportDot => "export".to_string(),
+            Action::CycleWindow => "window".to_string(),
+            Action::PickSpecies(n) => format!("pick_species_{}", n + 1),
+            Action::CursorUp => "up".to_string(),
+            Action::CursorDown => "down".to_string(),
+            Action::CycleSort => "sort".to_string(),
+            Action::ReverseSort => "reverse_sort".to_string(),
+            Action::CycleFilter => "filter".to_string(),
+            Action::StartSearch => "search".to_string(),
+            Action::ToggleMark => "mark".to_string(),
+            Action::MarkAll => "mark_all".to_string(),
+            Action::RemoveMarked => "remove_marked".to_string(),
+            Action::Confirm => "confirm".to_string(),
+            Action::Close => "close".to_string(),
+        }
+    }
+
+    fn from_config_name(name: &str) -> Option<Action> {
+        let action = match name {
+            "quit" => Action::Quit,
+            "toggle_pause" => Action::TogglePause,
+            "feed" => Action::Feed,
+            "speed_up" => Action::SpeedUp,
+            "speed_down" => Action::SpeedDown,
+            "select_next" => Action::SelectNext,
+            "select_prev" => Action::SelectPrev,
+            "add_fish" => Action::AddFish,
+            "remove_fish" => Action::RemoveFish,
+            "feed_mode" => Action::ToggleFeedMode,
+            "inspector" => Action::ToggleInspector,
+            "feed_selected" => Action::FeedSelected,
+            "rename" => Action::Rename,
+            "favorite" => Action::ToggleFavorite,
+            "help" => Action::OpenHelp,
+            "roster" => Action::OpenRoster,
+            "stats" => Action::OpenStats,
+            "log" => Action::OpenEventLog,
+            "lineage" => Action::OpenLineage,
+            "accessible" => Action::ToggleAccessible,
+            "text_view" => Action::ToggleTextView,
+            "pan_left" => Action::PanLeft,
+            "pan_right" => Action::PanRight,
+            "pan_up" => Action::PanUp,
+            "pan_down" => Action::PanDown,
+            "follow" => Action::ToggleFollow,
+            "minimap" => Action::ToggleMinimap,
+            "next_tank" => Action::NextTank,
+            "prev_tank" => Action::PrevTank,
+            "net" => Action::NetFish,
+            "shop" => Action::OpenShop,
+            "buy" => Action::Buy,
+            "sell" => Action::SellFish,
+            "achievements" => Action::OpenAchievements,
+            "incidents" => Action::ToggleIncidents,
+            "maintenance" => Action::OpenMaintenance,
+            "decrease" => Action::Decrease,
+            "increase" => Action::Increase,
+            "back" => Action::Back,
+            "export" => Action::ExportDot,
+            "window" => Action::CycleWindow,
+            "up" => Action::CursorUp,
+            "down" => Action::CursorDown,
+            "sort" => Action::CycleSort,
+            "reverse_sort" => Action::ReverseSort,
+            "filter" => Action::CycleFilter,
+            "search" => Action::StartSearch,
+            "mark" => Action::ToggleMark,
+            "mark_all" => Action::MarkAll,
+            "remove_marked" => Action::RemoveMarked,
+            "confirm" => Action::Confirm,
+            "close" => Action::Close,
+            _ => {
+                let n: usize = name.strip_prefix("pick_species_")?.parse().ok()?;
+                return (n > 0).then(|| Action::PickSpecies(n - 1));
+            }
+        };
+        Some(action)
+    }
+
+    /// Short label for the footer.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::TogglePause => "Pause",
+            Action::Feed => "Feed",
+            Action::SpeedUp => "Faster",
+            Action::SpeedDown => "Slower",
+            Action::SelectNext => "Select",
+            Action::SelectPrev => "Prev",
+            Action::AddFish => "Add",
+            Action::RemoveFish => "Remove",
+            Action::ToggleFeedMode => "Drop food",
+            Action::ToggleInspector => "Inspect",
+            Action::FeedSelected => "Feed this fish",
+            Action::Rename => "Rename",
+            Action::ToggleFavorite => "Favorite",
+            Action::OpenHelp => "Help",
+            Action::OpenRoster => "Roster",
+            Action::OpenStats => "Stats",
+            Action::OpenEventLog => "Log",
+            Action::OpenLineage => "Family",
+            Action::ToggleAccessible => "Accessible",
+            Action::ToggleTextView => "Text view",
+            Action::PanLeft => "Pan left",
+            Action::PanRight => "Pan right",
+            Action::PanUp => "Pan up",
+            Action::PanDown => "Pan down",
+            Action::ToggleFollow => "Follow",
+            Action::ToggleMinimap => "Minimap",
+            Action::NextTank => "Next tank",
+            Action::PrevTank => "Prev tank",
+            Action::NetFish => "Net",
+            Action::OpenShop => "Shop",
+            Action::Buy => "Buy",
+            Action::SellFish => "Sell",
+            Action::OpenAchievements => "Achievements",
+            Action::ToggleIncidents => "Incidents",
+            Action::OpenMaintenance => "Maintenance",
+            Action::Decrease => "Less",
+            Action::Increase => "More",
+            Action::Back => "Back",
+            Action::ExportDot => "Export",
+            Action::CycleWindow => "Window",
+            Action::PickSpecies(_) => "Pick",
+            Action::CursorUp => "Up",
+            Action::CursorDown => "Down",
+            Action::CycleSort => "Sort",
+            Action::ReverseSort => "Reverse",
+            Action::CycleFilter => "Filter",
+            Action::StartSearch => "Search",
+            Action::ToggleMark => "Mark",
+            Action::MarkAll => "Mark all",
+            Action::RemoveMarked => "Remove",
+            Action::Confirm => "Select",
+            Action::Close => "Close",
+        }
+    }
+
+    /// Full description for the help overlay.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit (auto-saves)",
+            Action::TogglePause => "Pause/Resume simulation",
+            Action::Feed => "Feed all fish",
+            Action::SpeedUp => "Increase simulation speed",
+            Action::SpeedDown => "Decrease simulation speed",
+            Action::SelectNext => "Select next fish",
+            Action::SelectPrev => "Select previous fish",
+            Action::AddFish => "Add fish (opens species picker)",
+            Action::RemoveFish => "Remove selected fish",
+            Action::ToggleFeedMode => "Feed mode (click water to drop food)",
+            Action::ToggleInspector => "Toggle fish inspector panel",
+            Action::FeedSelected => "Feed selected fish",
+            Action::Rename => "Rename selected fish",
+            Action::ToggleFavorite => "Mark selected fish as favorite",
+            Action::OpenHelp => "Toggle this help",
+            Action::OpenRoster => "Open the fish roster",
+            Action::OpenStats => "Open population statistics",
+            Action::OpenEventLog => "Open the event log",
+            Action::OpenLineage => "Open the selected fish's family tree",
+            Action::ToggleAccessible => "Toggle status glyphs and text descriptions",
+            Action::ToggleTextView => "Toggle the plain-text tank view",
+            Action::PanLeft => "Move the camera left",
+            Action::PanRight => "Move the camera right",
+            Action::PanUp => "Move the camera up",
+            Action::PanDown => "Move the camera down",
+            Action::ToggleFollow => "Keep the camera on the selected fish",
+            Action::ToggleMinimap => "Show or hide the minimap",
+            Action::NextTank => "Switch to the next tank",
+            Action::PrevTank => "Switch to the previous tank",
+            Action::NetFish => "Net the selected fish into another tank",
+            Action::OpenShop => "Open the shop (economy mode)",
+            Action::Buy => "Buy the highlighted item",
+            Action::SellFish => "Sell the selected fish",
+            Action::OpenAchievements => "Open achievements",
+            Action::ToggleIncidents => "Turn random incidents on or off in this tank",
+            Action::OpenMaintenance => "Open water changes and tank maintenance",
+            Action::Decrease => "Lower the highlighted setting",
+            Action::Increase => "Raise the highlighted setting",
+            Action::Back => "Go back to the previous fish",
+            Action::ExportDot => "Export the family tree as Graphviz DOT",
+            Action::CycleWindow => "Change time window",
+            Action::PickSpecies(_) => "Pick species",
+            Action::CursorUp => "Move cursor up",
+            Action::CursorDown => "Move cursor down",
+            Action::CycleSort => "Sort by next column",
+            Action::ReverseSort => "Reverse sort order",
+            Action::CycleFilter => "Cycle filter",
+            Action::StartSearch => "Search by name",
+            Action::ToggleMark => "Mark fish",
+            Action::MarkAll => "Mark all visible fish",
+            Action::RemoveMarked => "Remove marked fish",
+            Action::Confirm => "Select fish",
+            Action::Close => "Close",
+        }
+    }
+}
+
+/// A key plus the modifiers that must be held with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        KeyChord { code, modifiers }
+    }
+
+    /// Parse `q`, `Space`, `Tab`, `Esc`, `Up`, `F1`, `Ctrl+c`, `Alt+Enter`...
+    pub fn parse(text: &str) -> Result<KeyChord, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = text;
+        // A lone "+" or a chord ending in "++" names the plus key itself
+        while let Some((prefix, rest)) = key.split_once('+') {
+            if prefix.is_empty() {
+                break;
+            }
+            modifiers |= match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", prefix, text)),
+            };
+            key = rest;
+        }
+
+        let code = match key.to_ascii_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "delete" | "del" => KeyCode::Delete,
+            lower => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                        Some(n) => KeyCode::F(n),
+                        None => return Err(format!("unknown key '{}'", text)),
+                    },
+                }
+            }
+        };
+
+        // Terminals fold Shift into characters and Tab, so a chord keeping it
+        // would never match
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                return Err(if c.is_alphabetic() {
+                    format!("'{}' never matches; use '{}'", text, c.to_uppercase())
+                } else {
+                    format!("'{}' never matches; use the character Shift types", text)
+                });
+            }
+            KeyCode::Tab | KeyCode::BackTab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers -= KeyModifiers::SHIFT;
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Ok(KeyChord::new(code, modifiers))
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        // Shift is already folded into the character (or BackTab)
+        let relevant = if matches!(event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            event.modifiers - KeyModifiers::SHIFT
+        } else {
+            event.modifiers
+        };
+        self.code == event.code && self.modifiers == relevant
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Left => write!(f, "Left"),
+            KeyCode::Right => write!(f, "Right"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: BTreeMap<Context, Vec<(KeyChord, Action)>>,
+}
+
+impl Keymap {
+    /// The stock bindings, matching the original hardcoded keys.
+    pub fn default_layout() -> Keymap {
+        let mut keymap = Keymap {
+            bindings: BTreeMap::new(),
+        };
+        let normal = [
+            ("q", Action::Quit),
+            ("Q", Action::Quit),
+            ("Space", Action::TogglePause),
+            ("?", Action::OpenHelp),
+            ("f", Action::Feed),
+            ("F", Action::Feed),
+            ("+", Action::SpeedUp),
+            ("=", Action::SpeedUp),
+            ("-", Action::SpeedDown),
+            ("Tab", Action::SelectNext),
+            ("BackTab", Action::SelectPrev),
+            ("a", Action::AddFish),
+            ("A", Action::AddFish),
+            ("r", Action::RemoveFish),
+            ("R", Action::RemoveFish),
+            ("d", Action::ToggleFeedMode),
+            ("D", Action::ToggleFeedMode),
+            ("i", Action::ToggleInspector),
+            ("I", Action::ToggleInspector),
+            ("e", Action::FeedSelected),
+            ("E", Action::FeedSelected),
+            ("n", Action::Rename),
+            ("N", Action::Rename),
+            ("*", Action::ToggleFavorite),
+        ];
+        for (key, action) in normal {
+            keymap.bind(Context::Normal, key, action);
+        }
+        keymap.bind(Context::Picker, "Esc", Action::Close);
+        for n in 0..crate::species::get_all_species().len().min(9) {
+            keymap.bind(Context::Picker, &(n + 1).to_string(), Action::PickSpecies(n));
+        }
+        keymap.bind(Context::Help, "?", Action::Close);
+        keymap.bind(Context::Help, "Esc", Action::Close);
+        keymap.bind(Context::Normal, "o", Action::OpenRoster);
+        keymap.bind(Context::Normal, "O", Action::OpenRoster);
+        let roster = [
+            ("Up", Action::CursorUp),
+            ("k", Action::CursorUp),
+            ("Down", Action::CursorDown),
+            ("j", Action::CursorDown),
+            ("s", Action::CycleSort),
+            ("r", Action::ReverseSort),
+            ("f", Action::CycleFilter),
+            ("/", Action::StartSearch),
+            ("Space", Action::ToggleMark),
+            ("a", Action::MarkAll),
+            ("x", Action::RemoveMarked),
+            ("Delete", Action::RemoveMarked),
+            ("Enter", Action::Confirm),
+            ("Esc", Action::Close),
+            ("q", Action::Close),
+        ];
+        for (key, action) in roster {
+            keymap.bind(Context::Roster, key, action);
+        }
+        keymap.bind(Context::Normal, "g", Action::OpenStats);
+        keymap.bind(Context::Normal, "G", Action::OpenStats);
+        keymap.bind(Context::Stats, "w", Action::CycleWindow);
+        keymap.bind(Context::Stats, "Tab", Action::CycleWindow);
+        keymap.bind(Context::Stats, "Esc", Action::Close);
+        keymap.bind(Context::Stats, "q", Action::Close);
+        keymap.bind(Context::Stats, "g", Action::Close);
+        keymap.bind(Context::Normal, "v", Action::OpenEventLog);
+        keymap.bind(Context::Normal, "V", Action::OpenEventLog);
+        for (key, action) in [
+            ("Up", Action::CursorUp),
+            ("k", Action::CursorUp),
+            ("Down", Action::CursorDown),
+            ("j", Action::CursorDown),
+            ("Esc", Action::Close),
+            ("q", Action::Close),
+            ("v", Action::Close),
+        ] {
+            keymap.bind(Context::EventLog, key, action);
+        }
+        keymap.bind(Context::Normal, "F2", Action::ToggleAccessible);
+        keymap.bind(Context::Normal, "F3", Action::ToggleTextView);
+        keymap.bind(Context::Normal, "t", Action::OpenLineage);
+        keymap.bind(Context::Normal, "T", Action::OpenLineage);
+        for (key, action) in [
+            ("Up", Action::CursorUp),
+            ("k", Action::CursorUp),
+            ("Down", Action::CursorDown),
+            ("j", Action::CursorDown),
+            ("Enter", Action::Confirm),
+            ("Backspace", Action::Back),
+            ("b", Action::Back),
+            ("x", Action::ExportDot),
+            ("Esc", Action::Close),
+            ("q", Action::Close),
+            ("t", Action::Close),
+        ] {
+            keymap.bind(Context::Lineage, key, action);
+        }
+        for (key, action) in [
+            ("Left", Action::PanLeft),
+            ("Right", Action::PanRight),
+            ("Up", Action::PanUp),
+            ("Down", Action::PanDown),
+            ("c", Action::ToggleFollow),
+            ("C", Action::ToggleFollow),
+            ("m", Action::ToggleMinimap),
+            ("M", Action::ToggleMinimap),
+            ("]", Action::NextTank),
+            ("[", Action::PrevTank),
+            ("b", Action::NetFish),
+            ("B", Action::NetFish),
+            ("$", Action::OpenShop),
+            ("y", Action::OpenAchievements),
+            ("Y", Action::OpenAchievements),
+            ("w", Action::ToggleIncidents),
+            ("W", Action::ToggleIncidents),
+            ("u", Action::OpenMaintenance),
+            ("U", Action::OpenMaintenance),
+        ] {
+            keymap.bind(Context::Normal, key, action);
+        }
+        for (key, action) in [
+            ("Up", Action::CursorUp),
+            ("k", Action::CursorUp),
+            ("Down", Action::CursorDown),
+            ("j", Action::CursorDown),
+            ("Enter", Action::Buy),
+            ("s", Action::SellFish),
+            ("Esc", Action::Close),
+            ("q", Action::Close),
+            ("$", Action::Close),
+        ] {
+            keymap.bind(Context::Shop, key, action);
+        }
+        for (key, action) in [
+            ("Up", Action::CursorUp),
+            ("k", Action::CursorUp),
+            ("Down", Action::CursorDown),
+            ("j", Action::CursorDown),
+            ("Enter", Action::Confirm),
+            ("q", Action::Quit),
+            ("Esc", Action::Quit),
+        ] {
+            keymap.bind(Context::ModeSelect, key, action);
+        }
+        for (key, action) in [
+            ("Up", Action::CursorUp),
+            ("k", Action::CursorUp),
+            ("Down", Action::CursorDown),
+            ("j", Action::CursorDown),
+            ("Left", Action::Decrease),
+            ("-", Action::Decrease),
+            ("Right", Action::Increase),
+            ("+", Action::Increase),
+            ("Enter", Action::Confirm),
+            ("Esc", Action::Close),
+            ("q", Action::Close),
+            ("u", Action::Close),
+        ] {
+            keymap.bind(Context::Maintenance, key, action);
+        }
+        keymap.bind(Context::Prompt, "Enter", Action::Confirm);
+        keymap.bind(Context::Prompt, "Esc", Action::Close);
+        keymap
+    }
+
+    /// Default layout plus `hjkl` navigation.
+    pub fn vim_layout() -> Keymap {
+        let mut keymap = Keymap::default_layout();
+        keymap.bind(Context::Normal, "j", Action::SelectNext);
+        keymap.bind(Context::Normal, "k", Action::SelectPrev);
+        keymap.bind(Context::Normal, "l", Action::SpeedUp);
+        keymap.bind(Context::Normal, "h", Action::SpeedDown);
+        keymap.bind(Context::Normal, "x", Action::RemoveFish);
+        keymap.bind(Context::Picker, "q", Action::Close);
+        keymap.bind(Context::Help, "q", Action::Close);
+        keymap
+    }
+
+    /// Default layout plus arrow-key navigation.
+    pub fn arrows_layout() -> Keymap {
+        let mut keymap = Keymap::default_layout();
+        keymap.bind(Context::Normal, "Down", Action::SelectNext);
+        keymap.bind(Context::Normal, "Up", Action::SelectPrev);
+        keymap.bind(Context::Normal, "Right", Action::SpeedUp);
+        keymap.bind(Context::Normal, "Left", Action::SpeedDown);
+        keymap.bind(Context::Normal, "Delete", Action::RemoveFish);
+        keymap.bind(Context::Normal, "Shift+Left", Action::PanLeft);
+        keymap.bind(Context::Normal, "Shift+Right", Action::PanRight);
+        keymap.bind(Context::Normal, "Shift+Up", Action::PanUp);
+        keymap.bind(Context::Normal, "Shift+Down", Action::PanDown);
+        keymap
+    }
+
+    /// Build the keymap from a preset and the per-action overrides in
+    /// `[keys]`. Listing an action replaces all of its default keys.
+    pub fn from_config(config: &KeysConfig) -> Result<Keymap, String> {
+        let mut keymap = match config.preset.as_deref() {
+            None | Some("default") => Keymap::default_layout(),
+            Some("vim") => Keymap::vim_layout(),
+            Some("arrows") => Keymap::arrows_layout(),
+            Some(other) => return Err(format!("unknown key preset '{}'", other)),
+        };
+
+        let sections = [
+            (Context::Normal, &config.normal),
+            (Context::Picker, &config.picker),
+            (Context::Help, &config.help),
+            (Context::Roster, &config.roster),
+            (Context::Prompt, &config.prompt),
+            (Context::Stats, &config.stats),
+            (Context::EventLog, &config.log),
+            (Context::Lineage, &config.lineage),
+            (Context::Shop, &config.shop),
+            (Context::Maintenance, &config.maintenance),
+            (Context::ModeSelect, &config.mode_select),
+        ];
+        for (context, overrides) in sections {
+            for (name, keys) in overrides {
+                let action = Action::from_config_name(name).ok_or_else(|| {
+                    format!("unknown action '{}' in [keys.{}]", name, context.config_name())
+                })?;
+                let chords = keys
+                    .iter()
+                    .map(|k| KeyChord::parse(k))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let list = keymap.bindings.entry(context).or_default();
+                list.retain(|(chord, a)| *a != action && !chords.contains(chord));
+                list.extend(chords.into_iter().map(|chord| (chord, action)));
+            }
+        }
+        Ok(keymap)
+    }
+
+    fn bind(&mut self, context: Context, key: &str, action: Action) {
+        let chord = KeyChord::parse(key).expect("built-in key binding");
+        let list = self.bindings.entry(context).or_default();
+        list.retain(|(c, _)| *c != chord);
+        list.push((chord, action));
+    }
+
+    /// The action bound to `event` in `context`, if any.
+    pub fn lookup(&self, context: Context, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .get(&context)?
+            .iter()
+            .find(|(chord, _)| chord.matches(event))
+            .map(|(_, action)| *action)
+    }
+
+    /// Keys bound to `action`, without case duplicates (`f` and `F` show once).
+    pub fn keys_for(&self, context: Context, action: Action) -> Vec<KeyChord> {
+        let mut keys: Vec<KeyChord> = Vec::new();
+        for (chord, _) in self
+            .bindings
+            .get(&context)
+            .into_iter()
+            .flatten()
+            .filter(|(_, a)| *a == action)
+        {
+            if !keys.iter().any(|k| k.to_string() == chord.to_string()) {
+                keys.push(*chord);
+            }
+        }
+        keys
+    }
+
+    /// Footer hints for the normal screen, e.g. `[F]Feed [A]Add`.
+    pub fn footer_hints(&self) -> String {
+        self.hints(Context::Normal, &FOOTER_ACTIONS)
+    }
+
+    /// `[key]Label` hints for the given actions, skipping unbound ones.
+    pub fn hints(&self, context: Context, actions: &[Action]) -> String {
+        actions
+            .iter()
+            .filter_map(|&action| {
+                let key = self.keys_for(context, action).into_iter().next()?;
+                Some(format!("[{}]{}", key, action.label()))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Help overlay lines for the normal screen, e.g. `F - Feed all fish`.
+    pub fn help_lines(&self) -> Vec<String> {
+        NORMAL_ACTIONS
+            .iter()
+            .filter_map(|&action| {
+                let keys = self.keys_for(Context::Normal, action);
+                if keys.is_empty() {
+                    return None;
+                }
+                let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+                Some(format!("{} - {}", keys.join(" / "), action.description()))
+            })
+            .collect()
+    }
+
+    /// Keys that close the current modal, for "Press X to close" hints.
+    pub fn close_hint(&self, context: Context) -> String {
+        let keys: Vec<String> = self
+            .keys_for(context, Action::Close)
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        keys.join(" or ")
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::default_layout()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn parses_keys_and_modifiers() {
+        assert_eq!(KeyChord::parse("q"), Ok(KeyChord::new(KeyCode::Char('q'), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("Space"), Ok(KeyChord::new(KeyCode::Char(' '), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("F12"), Ok(KeyChord::new(KeyCode::F(12), KeyModifiers::NONE)));
+        assert_eq!(
+            KeyChord::parse("Ctrl+Alt+Enter"),
+            Ok(KeyChord::new(KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::ALT))
+        );
+        assert_eq!(KeyChord::parse("+"), Ok(KeyChord::new(KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(
+            KeyChord::parse("Ctrl++"),
+            Ok(KeyChord::new(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_modifiers() {
+        assert!(KeyChord::parse("Hyper+q").unwrap_err().contains("unknown modifier"));
+        assert!(KeyChord::parse("Banana").unwrap_err().contains("unknown key"));
+        assert!(KeyChord::parse("").is_err());
+    }
+
+    #[test]
+    fn shift_can_only_be_spelled_for_non_characters() {
+        assert_eq!(KeyChord::parse("Shift+x").unwrap_err(), "'Shift+x' never matches; use 'X'");
+        assert!(KeyChord::parse("Ctrl+Shift+1").unwrap_err().contains("use the character Shift types"));
+        assert_eq!(KeyChord::parse("Shift+Tab"), KeyChord::parse("BackTab"));
+        assert_eq!(
+            KeyChord::parse("Shift+Left"),
+            Ok(KeyChord::new(KeyCode::Left, KeyModifiers::SHIFT))
+        );
+
+        let back_tab = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert!(KeyChord::parse("Shift+Tab").unwrap().matches(&back_tab));
+    }
+
+    #[test]
+    fn shift_is_folded_into_characters() {
+        let keymap = Keymap::default_layout();
+        let shifted = KeyEvent::new(KeyCode::Char('F'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.lookup(Context::Normal, &shifted), Some(Action::Feed));
+    }
+
+    #[test]
+    fn overrides_replace_an_actions_default_keys() {
+        let mut config = KeysConfig::default();
+        config.normal.insert("feed".to_string(), vec!["g".to_string()]);
+        let keymap = Keymap::from_config(&config).unwrap();
+        assert_eq!(keymap.lookup(Context::Normal, &key(KeyCode::Char('g'))), Some(Action::Feed));
+        assert_eq!(keymap.lookup(Context::Normal, &key(KeyCode::Char('f'))), None);
+        assert!(keymap.footer_hints().contains("[G]Feed"));
+    }
+
+    #[test]
+    fn an_override_takes_the_key_from_other_actions() {
+        let mut config = KeysConfig::default();
+        config.normal.insert("feed".to_string(), vec!["q".to_string()]);
+        let keymap = Keymap::from_config(&config).unwrap();
+        assert_eq!(keymap.lookup(Context::Normal, &key(KeyCode::Char('q'))), Some(Action::Feed));
+    }
+
+    #[test]
+    fn presets_add_their_bindings() {
+        let config = KeysConfig {
+            preset: Some("vim".to_string()),
+            ..KeysConfig::default()
+        };
+        let keymap = Keymap::from_config(&config).unwrap();
+        assert_eq!(keymap.lookup(Context::Normal, &key(KeyCode::Char('j'))), Some(Action::SelectNext));
+    }
+
+    #[test]
+    fn rejects_bad_config() {
+        let preset = KeysConfig {
+            preset: Some("emacs".to_string()),
+            ..KeysConfig::default()
+        };
+        assert!(Keymap::from_config(&preset).unwrap_err().contains("unknown key preset"));
+
+        let mut action = KeysConfig::default();
+        action.shop.insert("fly".to_string(), vec!["x".to_string()]);
+        assert_eq!(
+            Keymap::from_config(&action).unwrap_err(),
+            "unknown action 'fly' in [keys.shop]"
+        );
+
+        let mut chord = KeysConfig::default();
+        chord.normal.insert("feed".to_string(), vec!["Meta+f".to_string()]);
+        assert!(Keymap::from_config(&chord).is_err());
+    }
+}
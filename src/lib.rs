@@ -0,0 +1,61 @@
+//! Aquarium simulation library.
+//!
+//! The simulation lives in [`tank::Tank`], which owns the [`fish::Fish`] and
+//! advances them one step per [`tank::Tank::tick`]. Species stats come from
+//! [`species`], and [`save`] reads and writes tanks as JSON. The terminal UI
+//! in [`ui`] is behind the default `tui` feature; build with
+//! `default-features = false` to embed only the simulation.
+//!
+//! Births, deaths and alerts are reported as [`events::TankEvent`]s, both in
+//! the summary each tick returns and to receivers from
+//! [`tank::Tank::subscribe`].
+//!
+//! ```
+//! use aquarium_sim::tank::Tank;
+//!
+//! let mut tank = Tank::with_seed(80, 24, 42).stocked();
+//! let events = tank.subscribe();
+//! for _ in 0..100 {
+//!     tank.tick();
+//! }
+//! assert_eq!(tank.fish.len(), 3);
+//! for timed in events.try_iter() {
+//!     println!("tick {}: {}", timed.tick, timed.event.describe());
+//! }
+//! ```
+
+pub mod achievements;
+#[cfg(feature = "tui")]
+pub mod app;
+pub mod aquarium;
+pub mod config;
+#[cfg(unix)]
+pub mod control;
+pub mod economy;
+pub mod events;
+pub mod fish;
+pub mod http;
+pub mod incidents;
+#[cfg(feature = "tui")]
+pub mod keymap;
+pub mod lineage;
+pub mod maintenance;
+pub mod metrics;
+pub mod mode;
+pub mod names;
+pub mod remote;
+pub mod rng;
+#[cfg(feature = "tui")]
+pub mod roster;
+pub mod save;
+pub mod scenario;
+pub mod simulate;
+pub mod species;
+pub mod sprite;
+pub mod stats;
+pub mod tank;
+#[cfg(feature = "tui")]
+pub mod theme;
+#[cfg(feature = "tui")]
+pub mod ui;
+pub mod water;
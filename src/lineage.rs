@@ -0,0 +1,361 @@
+//! Family records for every fish that has lived in a tank.
+//!
+//! Fish leave `Tank::fish` when they die, so the tank keeps a [`Lineage`]
+//! archive alongside it: one [`LineageRecord`] per fish ever added, updated
+//! when it is renamed and marked when it dies, is taken out, sold or moved
+//! to another tank. Ancestry can then be walked past dead fish and exported as
+//! Graphviz DOT.
+
+use crate::fish::{Fish, Sex};
+use crate::tank::DeathCause;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use uuid::Uuid;
+
+/// What became of a fish.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fate {
+    Alive,
+    Died { tick: u64, cause: DeathCause },
+    /// Taken out of the tank by the player.
+    Removed { tick: u64 },
+    /// Netted into another tank.
+    Moved { tick: u64 },
+    /// Sold from the shop.
+    Sold { tick: u64, price: u32 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LineageRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub species: String,
+    pub sex: Option<Sex>,
+    pub generation: u32,
+    pub parents: Vec<Uuid>,
+    /// Tick the fish entered the tank.
+    pub added: u64,
+    pub fate: Fate,
+}
+
+impl LineageRecord {
+    /// Name plus what became of the fish, e.g. "Bubbles (died t3000, starvation)".
+    pub fn summary(&self) -> String {
+        match self.fate {
+            Fate::Alive => self.name.clone(),
+            Fate::Died { tick, cause } => format!("{} (died t{}, {})", self.name, tick, cause.label()),
+            Fate::Removed { tick } => format!("{} (removed t{})", self.name, tick),
+            Fate::Moved { tick } => format!("{} (moved out t{})", self.name, tick),
+            Fate::Sold { tick, price } => format!("{} (sold t{} for {})", self.name, tick, price),
+        }
+    }
+}
+
+/// A fish found while walking the family tree, `depth` steps from the start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Relative {
+    pub id: Uuid,
+    pub depth: usize,
+}
+
+/// Which side of the tree a row is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Branch {
+    Ancestor,
+    Root,
+    Descendant,
+}
+
+/// One row of a family tree view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeRow {
+    pub id: Uuid,
+    pub depth: usize,
+    pub branch: Branch,
+}
+
+impl TreeRow {
+    /// "Parent", "Grandchild", "Great-great-grandparent" and so on.
+    pub fn relation(&self) -> String {
+        let base = match self.branch {
+            Branch::Root => return "Self".to_string(),
+            Branch::Ancestor => "parent",
+            Branch::Descendant => "child",
+        };
+        let relation = match self.depth {
+            0 | 1 => base.to_string(),
+            n => format!("{}grand{}", "great-".repeat(n - 2), base),
+        };
+        let mut chars = relation.chars();
+        chars.next().map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Lineage {
+    records: BTreeMap<Uuid, LineageRecord>,
+}
+
+impl Lineage {
+    /// Add or refresh the record for a fish in the tank.
+    pub fn record(&mut self, fish: &Fish, tick: u64) {
+        let record = self.records.entry(fish.id).or_insert_with(|| LineageRecord {
+            id: fish.id,
+            name: String::new(),
+            species: fish.species.clone(),
+            sex: fish.sex,
+            generation: fish.generation,
+            parents: fish.parents.clone(),
+            added: tick,
+            fate: Fate::Alive,
+        });
+        record.name = fish.display_name();
+        record.fate = Fate::Alive;
+    }
+
+    /// Copy a fish's record and its ancestors' from another tank's archive,
+    /// so its family tree comes along when it is moved.
+    pub fn copy_family(&mut self, from: &Lineage, id: Uuid) {
+        let ancestors = from.ancestors(id, usize::MAX);
+        for id in std::iter::once(id).chain(ancestors.iter().map(|r| r.id)) {
+            if let Some(record) = from.get(id) {
+                self.records.entry(id).or_insert_with(|| record.clone());
+            }
+        }
+    }
+
+    pub fn set_fate(&mut self, id: Uuid, fate: Fate) {
+        if let Some(record) = self.records.get_mut(&id) {
+            record.fate = fate;
+        }
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<&LineageRecord> {
+        self.records.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Tick of the most recent death on record.
+    pub fn last_death(&self) -> Option<u64> {
+        self.records
+            .values()
+            .filter_map(|r| match r.fate {
+                Fate::Died { tick, .. } => Some(tick),
+                _ => None,
+            })
+            .max()
+    }
+
+    /// Known children of a fish, oldest first.
+    pub fn children(&self, id: Uuid) -> Vec<&LineageRecord> {
+        let mut children: Vec<&LineageRecord> =
+            self.records.values().filter(|r| r.parents.contains(&id)).collect();
+        children.sort_by_key(|r| r.added);
+        children
+    }
+
+    /// Parents, grandparents and so on, depth first, up to `max_depth`
+    /// generations back. Parents without a record are skipped.
+    pub fn ancestors(&self, id: Uuid, max_depth: usize) -> Vec<Relative> {
+        let mut found = Vec::new();
+        self.walk(id, 1, max_depth, &mut HashSet::new(), &mut found, |record| {
+            record.parents.clone()
+        });
+        found
+    }
+
+    /// Children, grandchildren and so on, depth first, up to `max_depth`
+    /// generations down.
+    pub fn descendants(&self, id: Uuid, max_depth: usize) -> Vec<Relative> {
+        let mut found = Vec::new();
+        self.walk(id, 1, max_depth, &mut HashSet::new(), &mut found, |record| {
+            self.children(record.id).iter().map(|r| r.id).collect()
+        });
+        found
+    }
+
+    fn walk(
+        &self,
+        id: Uuid,
+        depth: usize,
+        max_depth: usize,
+        seen: &mut HashSet<Uuid>,
+        found: &mut Vec<Relative>,
+        next: impl Fn(&LineageRecord) -> Vec<Uuid> + Copy,
+    ) {
+        if depth > max_depth {
+            return;
+        }
+        let Some(record) = self.records.get(&id) else {
+            return;
+        };
+        for relative in next(record) {
+            if self.records.contains_key(&relative) && seen.insert(relative) {
+                found.push(Relative { id: relative, depth });
+                self.walk(relative, depth + 1, max_depth, seen, found, next);
+            }
+        }
+    }
+
+    /// Rows for a tree view of `id`: its ancestors, the fish itself, then
+    /// its descendants, each side `max_depth` generations deep.
+    pub fn tree(&self, id: Uuid, max_depth: usize) -> Vec<TreeRow> {
+        let side = |relatives: Vec<Relative>, branch| {
+            relatives.into_iter().map(move |r| TreeRow {
+                id: r.id,
+                depth: r.depth,
+                branch,
+            })
+        };
+        let mut rows: Vec<TreeRow> = side(self.ancestors(id, max_depth), Branch::Ancestor).collect();
+        rows.push(TreeRow {
+            id,
+            depth: 0,
+            branch: Branch::Root,
+        });
+        rows.extend(side(self.descendants(id, max_depth), Branch::Descendant));
+        rows
+    }
+
+    /// Graphviz DOT for a fish's whole family line: every recorded ancestor
+    /// and descendant, with edges from parent to child.
+    pub fn to_dot(&self, id: Uuid) -> String {
+        let mut members: Vec<Uuid> = vec![id];
+        members.extend(self.ancestors(id, usize::MAX).iter().map(|r| r.id));
+        members.extend(self.descendants(id, usize::MAX).iter().map(|r| r.id));
+
+        let mut dot = String::from("digraph lineage {\n    rankdir=TB;\n    node [shape=box];\n");
+        for member in &members {
+            let Some(record) = self.records.get(member) else {
+                continue;
+            };
+            let style = match (record.fate, *member == id) {
+                (_, true) => ", style=bold",
+                (Fate::Alive, _) => "",
+                _ => ", style=dashed",
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\\n{} {} gen {}\"{}];",
+                record.id,
+                escape(&record.summary()),
+                record.species,
+                record.sex.map_or("Unknown", |sex| sex.label()),
+                record.generation,
+                style
+            );
+        }
+        for member in &members {
+            let Some(record) = self.records.get(member) else {
+                continue;
+            };
+            for parent in record.parents.iter().filter(|p| members.contains(p)) {
+                let _ = writeln!(dot, "    \"{}\" -> \"{}\";", parent, record.id);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fish of the generation after `parents`, recorded at `tick`.
+    fn born(lineage: &mut Lineage, name: &str, parents: &[&Fish], tick: u64) -> Fish {
+        let mut fish = Fish::new("Guppy".to_string(), (0, 0));
+        fish.name = Some(name.to_string());
+        fish.parents = parents.iter().map(|p| p.id).collect();
+        fish.generation = parents.iter().map(|p| p.generation + 1).max().unwrap_or(0);
+        lineage.record(&fish, tick);
+        fish
+    }
+
+    /// Two grandparents, a parent, two children and a grandchild.
+    fn family() -> (Lineage, [Fish; 6]) {
+        let mut lineage = Lineage::default();
+        let grandma = born(&mut lineage, "Grandma", &[], 0);
+        let grandpa = born(&mut lineage, "Grandpa", &[], 0);
+        let parent = born(&mut lineage, "Parent", &[&grandma, &grandpa], 10);
+        let first = born(&mut lineage, "First", &[&parent], 20);
+        let second = born(&mut lineage, "Second", &[&parent], 30);
+        let grandchild = born(&mut lineage, "Grandchild", &[&first], 40);
+        (lineage, [grandma, grandpa, parent, first, second, grandchild])
+    }
+
+    #[test]
+    fn ancestors_are_walked_past_dead_fish() {
+        let (mut lineage, [grandma, grandpa, parent, first, ..]) = family();
+        lineage.set_fate(parent.id, Fate::Died { tick: 50, cause: DeathCause::OldAge });
+        let ancestors = lineage.ancestors(first.id, usize::MAX);
+        assert_eq!(
+            ancestors,
+            vec![
+                Relative { id: parent.id, depth: 1 },
+                Relative { id: grandma.id, depth: 2 },
+                Relative { id: grandpa.id, depth: 2 },
+            ]
+        );
+        assert_eq!(lineage.ancestors(first.id, 1).len(), 1);
+        assert_eq!(lineage.last_death(), Some(50));
+    }
+
+    #[test]
+    fn descendants_are_oldest_first_and_depth_limited() {
+        let (lineage, [grandma, _, parent, first, second, grandchild]) = family();
+        let ids = |relatives: Vec<Relative>| relatives.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(lineage.descendants(parent.id, usize::MAX)), vec![first.id, grandchild.id, second.id]);
+        assert_eq!(ids(lineage.descendants(parent.id, 1)), vec![first.id, second.id]);
+        assert_eq!(lineage.descendants(grandma.id, usize::MAX).len(), 4);
+    }
+
+    #[test]
+    fn tree_rows_name_each_relation() {
+        let (lineage, [_, _, parent, first, ..]) = family();
+        let relations: Vec<String> = lineage.tree(first.id, 4).iter().map(TreeRow::relation).collect();
+        assert_eq!(relations, ["Parent", "Grandparent", "Grandparent", "Self", "Child"]);
+        let rows = lineage.tree(parent.id, 4);
+        assert_eq!(rows.iter().filter(|r| r.branch == Branch::Descendant).count(), 3);
+        let row = TreeRow {
+            id: parent.id,
+            depth: 4,
+            branch: Branch::Ancestor,
+        };
+        assert_eq!(row.relation(), "Great-great-grandparent");
+    }
+
+    #[test]
+    fn moved_fish_bring_their_ancestors() {
+        let (lineage, [grandma, _, parent, first, second, _]) = family();
+        let mut other = Lineage::default();
+        other.copy_family(&lineage, first.id);
+        assert_eq!(other.len(), 4);
+        assert!(other.get(grandma.id).is_some());
+        assert!(other.get(second.id).is_none());
+        assert_eq!(other.ancestors(first.id, usize::MAX)[0].id, parent.id);
+    }
+
+    #[test]
+    fn dot_links_parents_to_children() {
+        let (mut lineage, [_, _, parent, first, second, _]) = family();
+        lineage.set_fate(second.id, Fate::Sold { tick: 60, price: 4 });
+        let dot = lineage.to_dot(parent.id);
+        assert!(dot.starts_with("digraph lineage {"));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\";", parent.id, first.id)));
+        assert!(dot.contains("Second (sold t60 for 4)"));
+        assert_eq!(dot.matches("->").count(), 5);
+    }
+}
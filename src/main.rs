@@ -1,170 +1,413 @@
-mod fish;
-mod save;
-mod species;
-mod tank;
-mod ui;
-
-use crossterm::{
-    event::{self, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{
-    backend::CrosstermBackend,
-    Terminal,
-};
+mod cli;
+#[cfg(unix)]
+mod ctl;
+
+use aquarium_sim::config::{self, Config};
+use aquarium_sim::simulate;
+use clap::Parser;
+use cli::{Cli, Command, CtlArgs, OutputFormat, SimulateArgs};
 use std::io;
 
 fn main() -> Result<(), io::Error> {
+    let cli = Cli::parse();
+    let config_path = cli.config.clone().or_else(config::default_config_path);
+    let mut config = match &config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+
+    match cli.command {
+        Some(Command::Simulate(args)) => run_simulate(args),
+        Some(Command::Ctl(args)) => run_ctl(args, &config),
+        None => {
+            let options = RunOptions::from_cli(&cli, &mut config)?;
+            run_tui(config, options)
+        }
+    }
+}
+
+/// Smallest `tank_width` and `tank_height` that leave room to swim.
+const MIN_TANK_SIZE: (u16, u16) = (20, 8);
+
+/// Startup choices that only make sense for a single run.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+struct RunOptions {
+    /// Start a fresh game instead of loading; the save file doesn't exist.
+    new_game: bool,
+}
+
+impl RunOptions {
+    /// Fold command-line overrides into the config.
+    fn from_cli(cli: &Cli, config: &mut Config) -> io::Result<RunOptions> {
+        if let Some(path) = &cli.save {
+            config.save_path = path.clone();
+        }
+        if let Some(speed) = cli.speed {
+            config.speed = speed;
+        }
+        if let Some(tick_rate) = cli.tick_rate {
+            config.tick_rate_ms = tick_rate;
+        }
+        if let Some(seed) = cli.seed {
+            config.seed = Some(seed);
+        }
+        if let Some(stock) = &cli.stock {
+            config.stock = stock.clone();
+        }
+        if let Some(theme) = &cli.theme {
+            config.theme = theme.clone();
+        }
+        if cli.accessible {
+            config.accessible = true;
+        }
+        if cli.text_view {
+            config.text_view = true;
+        }
+        if let Some(mode) = &cli.mode {
+            config.mode = Some(mode.clone());
+        }
+        if let Some(port) = cli.http {
+            config.http_port = Some(port);
+        }
+        if cli.no_autoload {
+            config.autoload = false;
+        }
+
+        if let Some(unknown) = config
+            .stock
+            .iter()
+            .find(|name| aquarium_sim::species::get_species(name).is_none())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown species in stock: {}", unknown),
+            ));
+        }
+
+        if config.tank_width < MIN_TANK_SIZE.0 || config.tank_height < MIN_TANK_SIZE.1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("tank size must be at least {}x{}", MIN_TANK_SIZE.0, MIN_TANK_SIZE.1),
+            ));
+        }
+
+        // Quitting saves, so a new game would overwrite the old one
+        if cli.new && config.save_path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "--new would overwrite {} on quit; pass --save with a new file to keep both",
+                    config.save_path.display()
+                ),
+            ));
+        }
+
+        Ok(RunOptions { new_game: cli.new })
+    }
+}
+
+fn run_simulate(args: SimulateArgs) -> Result<(), io::Error> {
+    let config = simulate::SimulationConfig {
+        ticks: args.ticks,
+        seed: args.seed,
+        tank_path: args.tank,
+        feed_every: args.feed_every,
+    };
+    let report = simulate::run(&config)?;
+
+    match args.format {
+        OutputFormat::Table => print!("{}", report.to_table()),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn run_ctl(args: CtlArgs, config: &Config) -> Result<(), io::Error> {
+    ctl::run(args, config)
+}
+
+#[cfg(not(unix))]
+fn run_ctl(_args: CtlArgs, _config: &Config) -> Result<(), io::Error> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "ctl needs Unix domain sockets",
+    ))
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_config: Config, _options: RunOptions) -> Result<(), io::Error> {
+    Err(io::Error::other(
+        "built without the `tui` feature; use a subcommand such as `simulate`",
+    ))
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(config: Config, options: RunOptions) -> Result<(), io::Error> {
+    use crossterm::{
+        event::{DisableMouseCapture, EnableMouseCapture},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    };
+    use ratatui::{backend::CrosstermBackend, Terminal};
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Run app
-    let result = run_app(&mut terminal);
+    let result = run_app(&mut terminal, &config, &options);
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
     terminal.show_cursor()?;
 
     result
 }
 
-fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> Result<(), io::Error> {
-    // Initialize tank - load from save or create new
-    let size = terminal.size()?;
-    let mut tank = if let Some(mut loaded_tank) = save::load_tank() {
-        // Update dimensions to current terminal size
-        loaded_tank.dimensions = (size.width, size.height);
-        loaded_tank
+#[cfg(feature = "tui")]
+fn run_app<B: ratatui::backend::Backend>(
+    terminal: &mut ratatui::Terminal<B>,
+    config: &Config,
+    options: &RunOptions,
+) -> Result<(), io::Error> {
+    use aquarium_sim::achievements::{self, Profile};
+    use aquarium_sim::app::App;
+    use aquarium_sim::aquarium::Aquarium;
+    use aquarium_sim::mode::GameMode;
+    use aquarium_sim::scenario::Scenario;
+    use aquarium_sim::keymap::Keymap;
+    use aquarium_sim::sprite::Sprites;
+    use aquarium_sim::theme::{ColorDepth, Theme};
+    #[cfg(unix)]
+    use aquarium_sim::control;
+    use aquarium_sim::events::Severity;
+    use aquarium_sim::{http, remote, save, ui};
+    use crossterm::event;
+    use std::time::{Duration, Instant};
+
+    let keymap = Keymap::from_config(&config.keys)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let depth = ColorDepth::from_config(&config.color_depth).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown color_depth '{}'", config.color_depth),
+        )
+    })?;
+    let theme = Theme::load(&config.theme)?.for_depth(depth);
+    let sprites = Sprites::load(config.sprites.as_deref())?;
+
+    // Initialize tanks - load from save or create new
+    let loaded = if config.autoload && !options.new_game && config.save_path.exists() {
+        Some(save::load_aquarium_from(&config.save_path)?)
     } else {
-        // Create new tank with test fish
-        let mut tank = tank::Tank::new(size.width, size.height);
-        let test_fish1 = fish::Fish::new("Goldfish".to_string(), (10, 5));
-        let test_fish2 = fish::Fish::new("Guppy".to_string(), (30, 10));
-        let test_fish3 = fish::Fish::new("Betta".to_string(), (50, 8));
-        tank.add_fish(test_fish1);
-        tank.add_fish(test_fish2);
-        tank.add_fish(test_fish3);
-        tank
+        None
+    };
+    let scenarios = Scenario::load_all()?;
+    let mode = match &config.mode {
+        Some(name) => Some(GameMode::from_config(name, &scenarios).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("unknown mode '{}'", name))
+        })?),
+        None => None,
+    };
+    let new_game = loaded.is_none();
+    let aquarium = match (loaded, &mode) {
+        (Some(loaded), _) => loaded,
+        (None, Some(mode)) => mode.clone().new_game(new_tank(config)),
+        (None, None) => Aquarium::starter(new_tank(config)),
     };
-    let mut selected_fish_index: Option<usize> = None;
-    let mut paused = false;
-    let mut species_picker_open = false;
-    let mut help_open = false;
-
-    loop {
-        // Calculate selected_fish_id from index
-        let selected_fish_id = selected_fish_index
-            .and_then(|idx| tank.fish.get(idx))
-            .map(|f| f.id);
-
-        // Render UI
-        terminal.draw(|f| {
-            ui::render(f, &tank, selected_fish_id, paused, species_picker_open, help_open);
-        })?;
-
-        // Handle input
-        if event::poll(std::time::Duration::from_millis(100))? {
-            let event = event::read()?;
-            match event {
-                Event::Resize(width, height) => {
-                    tank.dimensions = (width, height);
-                    // Clamp fish positions to new boundaries
-                    for fish in &mut tank.fish {
-                        fish.position.0 = fish.position.0.min(width.saturating_sub(10));
-                        fish.position.1 = fish.position.1.min(height.saturating_sub(5));
-                    }
-                }
-                Event::Key(key) => {
-                    // Handle modal-specific keys first
-                    if help_open {
-                        match key.code {
-                            KeyCode::Char('?') | KeyCode::Esc => help_open = false,
-                            _ => {}
-                        }
-                        continue;
-                    }
-
-                    if species_picker_open {
-                        match key.code {
-                            KeyCode::Esc => species_picker_open = false,
-                            KeyCode::Char('1') => {
-                                let position = (10 + (tank.fish.len() as u16 * 3) % 60, 5 + (tank.fish.len() as u16 % 10));
-                                tank.add_fish(fish::Fish::new("Goldfish".to_string(), position));
-                                species_picker_open = false;
-                            }
-                            KeyCode::Char('2') => {
-                                let position = (10 + (tank.fish.len() as u16 * 3) % 60, 5 + (tank.fish.len() as u16 % 10));
-                                tank.add_fish(fish::Fish::new("Guppy".to_string(), position));
-                                species_picker_open = false;
-                            }
-                            KeyCode::Char('3') => {
-                                let position = (10 + (tank.fish.len() as u16 * 3) % 60, 5 + (tank.fish.len() as u16 % 10));
-                                tank.add_fish(fish::Fish::new("Betta".to_string(), position));
-                                species_picker_open = false;
-                            }
-                            KeyCode::Char('4') => {
-                                let position = (10 + (tank.fish.len() as u16 * 3) % 60, 5 + (tank.fish.len() as u16 % 10));
-                                tank.add_fish(fish::Fish::new("Tetra".to_string(), position));
-                                species_picker_open = false;
-                            }
-                            KeyCode::Char('5') => {
-                                let position = (10 + (tank.fish.len() as u16 * 3) % 60, 5 + (tank.fish.len() as u16 % 10));
-                                tank.add_fish(fish::Fish::new("Angelfish".to_string(), position));
-                                species_picker_open = false;
-                            }
-                            _ => {}
-                        }
-                        continue;
-                    }
-
-                    // Normal keys
-                    match key.code {
-                    KeyCode::Char('q') | KeyCode::Char('Q') => break,
-                    KeyCode::Char(' ') => paused = !paused,
-                    KeyCode::Char('?') => help_open = true,
-                    KeyCode::Char('f') | KeyCode::Char('F') => tank.feed(),
-                    KeyCode::Char('+') | KeyCode::Char('=') => tank.increase_speed(),
-                    KeyCode::Char('-') => tank.decrease_speed(),
-                    KeyCode::Tab => {
-                        if !tank.fish.is_empty() {
-                            selected_fish_index = Some(match selected_fish_index {
-                                None => 0,
-                                Some(idx) => (idx + 1) % tank.fish.len(),
-                            });
-                        }
-                    }
-                    KeyCode::Char('a') | KeyCode::Char('A') => {
-                        species_picker_open = true;
-                    }
-                    KeyCode::Char('r') | KeyCode::Char('R') => {
-                        if let Some(fish_id) = selected_fish_id {
-                            tank.remove_fish(fish_id);
-                            selected_fish_index = None;
-                        }
-                    }
-                    _ => {}
-                }
-                }
-                _ => {}
+    let mut app = App::new(aquarium, keymap);
+    if new_game && mode.is_none() {
+        app.choose_mode(GameMode::choices(&scenarios));
+    }
+    app.theme = theme;
+    app.sprites = sprites;
+    app.profile_path = achievements::profile_path();
+    if let Some(path) = &app.profile_path {
+        app.profile = Profile::load(path)?;
+    }
+    // Without color, hunger is only visible through the glyphs
+    app.accessible = config.accessible || depth == ColorDepth::Monochrome;
+    app.text_view = config.text_view;
+    if let Some(dir) = config.save_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        app.export_dir = dir.to_path_buf();
+    }
+    let (remote, mut requests) = remote::channel();
+    if let Some(port) = config.http_port {
+        let address = http::spawn(port, remote.clone())
+            .map_err(|e| io::Error::new(e.kind(), format!("HTTP server on port {}: {}", port, e)))?;
+        // With port 0 this is the only way to learn which port was picked
+        app.notify(format!("HTTP API on http://{}", address), Severity::Info);
+    }
+    #[cfg(unix)]
+    let _control = match config.control_socket.clone().or_else(control::default_socket_path) {
+        Some(path) if config.control => match control::spawn(&path, remote) {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                // Most likely another game has it; this one runs without
+                app.notify(format!("Control socket unavailable: {}", e), Severity::Warning);
+                None
             }
+        },
+        _ => None,
+    };
+    let size = terminal.size()?;
+    app.resize(size.width, size.height);
+
+    let tick_rate = Duration::from_millis(config.tick_rate_ms);
+    let mut last_tick = Instant::now();
+
+    while !app.should_quit {
+        terminal.draw(|f| ui::render(f, &app))?;
+
+        // Faster speeds tick more often; wait for input until the next
+        // tick is due
+        let interval = tick_rate.div_f32(app.tank().simulation_speed.max(0.1));
+        let timeout = interval.saturating_sub(last_tick.elapsed());
+        if event::poll(timeout)? {
+            app.handle_event(&event::read()?);
         }
+        requests.handle(&mut app.aquarium);
 
-        // Update simulation (only if not paused)
-        if !paused {
-            tank.tick();
+        if last_tick.elapsed() >= interval {
+            // Paused and menu frames don't tick, so they aren't timed
+            let ticks = app.tank().tick_count;
+            let started = Instant::now();
+            app.on_tick();
+            if app.tank().tick_count != ticks {
+                requests.tick_times.observe(started.elapsed());
+            }
+            last_tick = Instant::now();
         }
     }
 
-    // Save tank state on exit
-    if let Err(e) = save::save_tank(&tank) {
-        eprintln!("Failed to save tank: {}", e);
+    // Quitting from the mode selector leaves no game to keep; saving the
+    // placeholder tanks would skip the selector on every later launch
+    if app.choosing_mode() {
+        return Ok(());
+    }
+
+    // Save every tank on exit
+    if let Err(e) = save::save_aquarium_to(&app.aquarium, &config.save_path) {
+        eprintln!("Failed to save tanks: {}", e);
+    }
+    // Progress towards locked achievements is only saved here
+    if let Some(path) = &app.profile_path
+        && let Err(e) = app.profile.save(path)
+    {
+        eprintln!("Failed to save achievements: {}", e);
     }
 
     Ok(())
 }
+
+/// Build a new tank from the configured seed, speed and stock.
+#[cfg(feature = "tui")]
+fn new_tank(config: &Config) -> aquarium_sim::tank::Tank {
+    use aquarium_sim::{fish::Fish, tank::Tank};
+
+    let (width, height) = (config.tank_width, config.tank_height);
+    let mut tank = match config.seed {
+        Some(seed) => Tank::with_seed(width, height, seed),
+        None => Tank::new(width, height),
+    };
+    tank.set_speed(config.speed);
+    tank.incidents.settings = config.incidents.clone();
+    for species in &config.stock {
+        let position = tank.spawn_position();
+        tank.add_fish(Fish::new(species.clone(), position));
+    }
+    tank
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::parse_from(std::iter::once("aquarium-sim").chain(args.iter().copied()))
+    }
+
+    /// Defaults with a save path that doesn't exist.
+    fn fresh_config() -> Config {
+        Config {
+            save_path: std::env::temp_dir().join(format!("aquarium-sim-test-{}.json", uuid::Uuid::new_v4().simple())),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn flags_override_the_config() {
+        let mut config = Config {
+            speed: 2.0,
+            seed: Some(1),
+            theme: "ocean".to_string(),
+            stock: vec!["Betta".to_string()],
+            ..fresh_config()
+        };
+        let cli = parse(&["--speed", "5", "--seed", "9", "--stock", "Tetra,Guppy", "--no-autoload", "--http", "0"]);
+        let options = RunOptions::from_cli(&cli, &mut config).unwrap();
+        assert!(!options.new_game);
+        assert_eq!(config.speed, 5.0);
+        assert_eq!(config.seed, Some(9));
+        assert_eq!(config.stock, ["Tetra", "Guppy"]);
+        assert!(!config.autoload);
+        assert_eq!(config.http_port, Some(0));
+        // Settings without a flag keep the file's value
+        assert_eq!(config.theme, "ocean");
+
+        let save = std::env::temp_dir().join("elsewhere.json");
+        let cli = parse(&["--save", save.to_str().unwrap()]);
+        RunOptions::from_cli(&cli, &mut config).unwrap();
+        assert_eq!(config.save_path, save);
+    }
+
+    #[test]
+    fn rejects_unknown_species_in_the_stock() {
+        let error = RunOptions::from_cli(&parse(&["--stock", "Guppy,Shark"]), &mut fresh_config()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "unknown species in stock: Shark");
+
+        let mut config = Config {
+            stock: vec!["Kraken".to_string()],
+            ..fresh_config()
+        };
+        assert!(RunOptions::from_cli(&parse(&[]), &mut config).is_err());
+    }
+
+    #[test]
+    fn rejects_tanks_smaller_than_the_minimum() {
+        let mut config = Config {
+            tank_width: MIN_TANK_SIZE.0 - 1,
+            ..fresh_config()
+        };
+        let error = RunOptions::from_cli(&parse(&[]), &mut config).err().unwrap();
+        assert_eq!(error.to_string(), "tank size must be at least 20x8");
+
+        let mut config = Config {
+            tank_width: MIN_TANK_SIZE.0,
+            tank_height: MIN_TANK_SIZE.1,
+            ..fresh_config()
+        };
+        assert!(RunOptions::from_cli(&parse(&[]), &mut config).is_ok());
+    }
+
+    #[test]
+    fn new_refuses_to_overwrite_a_save() {
+        let mut config = fresh_config();
+        let options = RunOptions::from_cli(&parse(&["--new"]), &mut config).unwrap();
+        assert!(options.new_game);
+
+        fs::write(&config.save_path, "{}").unwrap();
+        let error = RunOptions::from_cli(&parse(&["--new"]), &mut config).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        fs::remove_file(&config.save_path).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+//! Tank care beyond feeding: water changes, topping off, vacuuming the
+//! gravel, rinsing the filter and dosing conditioner.
+//!
+//! Each [`Task`] works on the tank's [`Water`](crate::water::Water), and
+//! anything that swaps water stresses the fish by as much as moving them
+//! into the new water would. Every task done is kept in the tank's
+//! [`MaintenanceLog`].
+
+use crate::tank::Tank;
+use crate::water::ROOM_TEMPERATURE;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Records kept per tank.
+const LOG_LEN: usize = 200;
+/// Debris a gravel vacuum removes, and the water it siphons out with it.
+const VACUUM_DEBRIS: f32 = 0.8;
+const VACUUM_WATER: f32 = 0.1;
+/// Stress from a net and siphon being waved about the tank.
+const DISTURBANCE_STRESS: u8 = 5;
+/// Bacteria washed out by a filter rinse.
+const RINSE_BACTERIA: f32 = 0.1;
+/// Ammonia a conditioner dose binds.
+const CONDITIONER_AMMONIA: f32 = 0.5;
+/// Smallest, largest and step of the water change share, in percent.
+pub const WATER_CHANGE_PERCENT: (u8, u8, u8) = (5, 90, 5);
+/// Coldest and warmest tap water, in °C.
+const TAP_TEMPERATURE: (f32, f32) = (10.0, 35.0);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Task {
+    WaterChange,
+    TopOff,
+    GravelVacuum,
+    FilterRinse,
+    Conditioner,
+}
+
+impl Task {
+    pub const ALL: [Task; 5] = [
+        Task::WaterChange,
+        Task::TopOff,
+        Task::GravelVacuum,
+        Task::FilterRinse,
+        Task::Conditioner,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Task::WaterChange => "Water change",
+            Task::TopOff => "Top off",
+            Task::GravelVacuum => "Gravel vacuum",
+            Task::FilterRinse => "Filter rinse",
+            Task::Conditioner => "Conditioner",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Task::WaterChange => "swap old water for fresh tap water",
+            Task::TopOff => "replace evaporated water",
+            Task::GravelVacuum => "siphon rotting debris out of the gravel",
+            Task::FilterRinse => "unclog the filter; some bacteria wash out",
+            Task::Conditioner => "neutralise chlorine and bind some ammonia",
+        }
+    }
+
+    /// Do the task on `tank`, using tap water as set in `fresh`, and log it.
+    pub fn perform(&self, tank: &mut Tank, fresh: &FreshWater) -> Record {
+        let before = tank.water.clone();
+        let water = &mut tank.water;
+        let detail = match self {
+            Task::WaterChange => {
+                water.change(fresh.percent as f32 / 100.0, fresh.temperature);
+                format!("{}% at {:.0} °C", fresh.percent, fresh.temperature)
+            }
+            Task::TopOff => {
+                let added = (1.0 - water.level) * 100.0;
+                water.top_off(fresh.temperature);
+                format!("{:.0}% at {:.0} °C", added, fresh.temperature)
+            }
+            Task::GravelVacuum => {
+                let removed = water.debris * VACUUM_DEBRIS;
+                water.vacuum(VACUUM_DEBRIS, VACUUM_WATER);
+                format!("{:.1} debris removed", removed)
+            }
+            Task::FilterRinse => {
+                let clog = water.clog * 100.0;
+                water.rinse_filter(RINSE_BACTERIA);
+                format!("was {:.0}% clogged", clog)
+            }
+            Task::Conditioner => {
+                water.condition(CONDITIONER_AMMONIA);
+                format!("{:.2} ppm chlorine neutralised", before.chlorine)
+            }
+        };
+
+        let mut stress = before.acclimation_stress(&tank.water);
+        if matches!(self, Task::WaterChange | Task::GravelVacuum) {
+            stress = stress.saturating_add(DISTURBANCE_STRESS);
+        }
+        for fish in &mut tank.fish {
+            fish.stress = fish.stress.saturating_add(stress).min(100);
+        }
+        let detail = if stress > 0 {
+            format!("{}, stress +{}", detail, stress)
+        } else {
+            detail
+        };
+
+        let record = Record {
+            task: *self,
+            tick: tank.tick_count,
+            detail,
+        };
+        tank.maintenance.push(record.clone());
+        record
+    }
+}
+
+/// The tap water used for water changes and top-offs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FreshWater {
+    /// Share of the tank a water change replaces.
+    pub percent: u8,
+    /// °C.
+    pub temperature: f32,
+}
+
+impl Default for FreshWater {
+    /// A 25% change straight from the tap, at room temperature.
+    fn default() -> Self {
+        FreshWater {
+            percent: 25,
+            temperature: ROOM_TEMPERATURE,
+        }
+    }
+}
+
+impl FreshWater {
+    /// Step the water change share by `steps` of [`WATER_CHANGE_PERCENT`].
+    pub fn adjust_percent(&mut self, steps: i32) {
+        let (low, high, step) = WATER_CHANGE_PERCENT;
+        let percent = self.percent as i32 + steps * step as i32;
+        self.percent = percent.clamp(low as i32, high as i32) as u8;
+    }
+
+    /// Warm or cool the tap water by `degrees`.
+    pub fn adjust_temperature(&mut self, degrees: f32) {
+        self.temperature = (self.temperature + degrees).clamp(TAP_TEMPERATURE.0, TAP_TEMPERATURE.1);
+    }
+}
+
+/// A task done, and when.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Record {
+    pub task: Task,
+    pub tick: u64,
+    /// "25% at 24 °C, stress +6".
+    pub detail: String,
+}
+
+impl Record {
+    /// "t5230 Water change: 25% at 24 °C".
+    pub fn summary(&self) -> String {
+        format!("t{} {}: {}", self.tick, self.task.label(), self.detail)
+    }
+}
+
+/// Every task done in a tank, oldest first.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MaintenanceLog {
+    records: VecDeque<Record>,
+}
+
+impl MaintenanceLog {
+    pub fn push(&mut self, record: Record) {
+        if self.records.len() == LOG_LEN {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// The last time `task` was done.
+    pub fn last(&self, task: Task) -> Option<&Record> {
+        self.records.iter().rev().find(|record| record.task == task)
+    }
+
+    /// Newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &Record> {
+        self.records.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fish::Fish;
+
+    fn tank_with_a_fish() -> Tank {
+        let mut tank = Tank::with_seed(60, 20, 1);
+        tank.add_fish(Fish::new("Guppy".to_string(), (5, 5)));
+        tank
+    }
+
+    /// Stress a water change gives the fish.
+    fn change_stress(percent: u8, temperature: Option<f32>) -> u8 {
+        let mut tank = tank_with_a_fish();
+        let temperature = temperature.unwrap_or(tank.water.temperature);
+        Task::WaterChange.perform(&mut tank, &FreshWater { percent, temperature });
+        tank.fish[0].stress
+    }
+
+    #[test]
+    fn cold_water_changes_stress_fish_more() {
+        let matched = change_stress(60, None);
+        let cold = change_stress(60, Some(15.0));
+        let small = change_stress(10, Some(15.0));
+        assert!(matched >= DISTURBANCE_STRESS);
+        assert!(cold > matched, "60% cold {} vs matched {}", cold, matched);
+        assert!(cold > small, "60% cold {} vs 10% cold {}", cold, small);
+
+        let mut tank = tank_with_a_fish();
+        let record = Task::WaterChange.perform(&mut tank, &FreshWater { percent: 60, temperature: 15.0 });
+        assert_eq!(record.detail, format!("60% at 15 °C, stress +{}", cold));
+        assert_eq!(tank.maintenance.last(Task::WaterChange).unwrap().detail, record.detail);
+    }
+
+    #[test]
+    fn the_log_drops_the_oldest_records() {
+        let mut log = MaintenanceLog::default();
+        for tick in 0..LOG_LEN as u64 + 5 {
+            let task = if tick % 2 == 0 { Task::TopOff } else { Task::FilterRinse };
+            log.push(Record { task, tick, detail: String::new() });
+        }
+        assert_eq!(log.recent().count(), LOG_LEN);
+        assert_eq!(log.recent().last().unwrap().tick, 5);
+        assert_eq!(log.recent().next().unwrap().tick, LOG_LEN as u64 + 4);
+    }
+
+    #[test]
+    fn last_finds_the_latest_of_a_task() {
+        let mut log = MaintenanceLog::default();
+        let tasks = [Task::WaterChange, Task::Conditioner, Task::WaterChange, Task::TopOff];
+        for (tick, task) in [10, 20, 30, 40].into_iter().zip(tasks) {
+            log.push(Record { task, tick, detail: String::new() });
+        }
+        assert_eq!(log.last(Task::WaterChange).unwrap().tick, 30);
+        assert_eq!(log.last(Task::Conditioner).unwrap().tick, 20);
+        assert!(log.last(Task::GravelVacuum).is_none());
+    }
+}
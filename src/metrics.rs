@@ -0,0 +1,194 @@
+//! Every tank as Prometheus metrics, in the text exposition format served
+//! at `/metrics`.
+//!
+//! Every per-tank series is labelled with the tank's index as `tank_id`,
+//! which stays unique when tanks share a name, and its name as `tank`.
+//! Gauges cover fish, hunger, health and water; counters come from each
+//! tank's lifetime [`EventCounts`](crate::stats::EventCounts), so they only
+//! reset when a new game replaces the tanks. Tick durations are timed by
+//! the main loop into [`TickTimes`].
+
+use crate::aquarium::Aquarium;
+use crate::fish::Fish;
+use crate::species;
+use crate::tank::Tank;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Upper bounds of the tick duration buckets, in seconds.
+const BUCKETS: [f64; 13] = [
+    0.00001, 0.000025, 0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1,
+];
+
+/// A histogram of how long ticks take.
+#[derive(Clone, Debug, Default)]
+pub struct TickTimes {
+    /// Ticks at or under each bound in [`BUCKETS`], not cumulative.
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    /// Seconds.
+    sum: f64,
+}
+
+impl TickTimes {
+    pub fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if let Some(bucket) = BUCKETS.iter().position(|&bound| seconds <= bound) {
+            self.buckets[bucket] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// The exposition text for `aquarium` and the ticks timed so far.
+pub fn render(aquarium: &Aquarium, ticks: &TickTimes) -> String {
+    let mut out = String::new();
+    let tanks = &aquarium.tanks;
+
+    family(&mut out, "aquarium_fish", "gauge", "Fish in the tank by species.");
+    for (index, tank) in tanks.iter().enumerate() {
+        for species in species::get_all_species() {
+            let count = tank.fish.iter().filter(|f| f.species == species.name).count();
+            let labels = format!("{},species=\"{}\"", tank_labels(index, tank), escape(&species.name));
+            sample(&mut out, "aquarium_fish", &labels, count);
+        }
+    }
+    gauge(&mut out, tanks, "aquarium_fish_hunger_average", "Average hunger of the fish, 0-100.", |tank| {
+        average(tank, |f| f.hunger)
+    });
+    gauge(&mut out, tanks, "aquarium_fish_health_average", "Average health of the fish, 0-100.", |tank| {
+        average(tank, |f| f.health)
+    });
+
+    family(&mut out, "aquarium_births_total", "counter", "Fish born in the tank.");
+    for (index, tank) in tanks.iter().enumerate() {
+        sample(&mut out, "aquarium_births_total", &tank_labels(index, tank), tank.stats.totals.births);
+    }
+    family(&mut out, "aquarium_deaths_total", "counter", "Fish that died in the tank, by cause.");
+    for (index, tank) in tanks.iter().enumerate() {
+        let totals = &tank.stats.totals;
+        for (cause, count) in [
+            ("starvation", totals.starvation_deaths),
+            ("old_age", totals.old_age_deaths),
+            ("poor_water", totals.poor_water_deaths),
+            ("disease", totals.disease_deaths),
+        ] {
+            let labels = format!("{},cause=\"{}\"", tank_labels(index, tank), cause);
+            sample(&mut out, "aquarium_deaths_total", &labels, count);
+        }
+    }
+    family(&mut out, "aquarium_ticks_total", "counter", "Ticks the tank has run.");
+    for (index, tank) in tanks.iter().enumerate() {
+        sample(&mut out, "aquarium_ticks_total", &tank_labels(index, tank), tank.tick_count);
+    }
+
+    gauge(&mut out, tanks, "aquarium_water_temperature_celsius", "Water temperature.", |tank| {
+        tank.water.temperature
+    });
+    gauge(&mut out, tanks, "aquarium_water_ph", "Water pH.", |tank| tank.water.ph);
+    gauge(&mut out, tanks, "aquarium_water_ammonia_ppm", "Ammonia in the water.", |tank| {
+        tank.water.ammonia
+    });
+    gauge(&mut out, tanks, "aquarium_water_nitrate_ppm", "Nitrate in the water.", |tank| {
+        tank.water.nitrate
+    });
+    gauge(&mut out, tanks, "aquarium_water_chlorine_ppm", "Chlorine in the water.", |tank| {
+        tank.water.chlorine
+    });
+    gauge(&mut out, tanks, "aquarium_water_level_ratio", "Share of the tank filled with water.", |tank| {
+        tank.water.level
+    });
+
+    let name = "aquarium_tick_duration_seconds";
+    family(&mut out, name, "histogram", "Time taken by each tick of every tank, with its bookkeeping.");
+    let mut cumulative = 0;
+    for (bound, count) in BUCKETS.iter().zip(ticks.buckets) {
+        cumulative += count;
+        sample(&mut out, &format!("{}_bucket", name), &format!("le=\"{}\"", bound), cumulative);
+    }
+    sample(&mut out, &format!("{}_bucket", name), "le=\"+Inf\"", ticks.count);
+    sample(&mut out, &format!("{}_sum", name), "", ticks.sum);
+    sample(&mut out, &format!("{}_count", name), "", ticks.count);
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: impl std::fmt::Display) {
+    if labels.is_empty() {
+        let _ = writeln!(out, "{} {}", name, value);
+    } else {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+    }
+}
+
+/// A gauge with one sample per tank.
+fn gauge(out: &mut String, tanks: &[Tank], name: &str, help: &str, value: impl Fn(&Tank) -> f32) {
+    family(out, name, "gauge", help);
+    for (index, tank) in tanks.iter().enumerate() {
+        sample(out, name, &tank_labels(index, tank), value(tank));
+    }
+}
+
+/// The labels naming a tank: its index, then its name.
+fn tank_labels(index: usize, tank: &Tank) -> String {
+    format!("tank_id=\"{}\",tank=\"{}\"", index, escape(&tank.name))
+}
+
+/// Mean of `field` over the tank's fish; 0 for an empty tank.
+fn average(tank: &Tank, field: impl Fn(&Fish) -> u8) -> f32 {
+    let total: u32 = tank.fish.iter().map(|f| field(f) as u32).sum();
+    total as f32 / tank.fish.len().max(1) as f32
+}
+
+/// A label value with backslashes, quotes and newlines escaped.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tanks_sharing_a_name_get_their_own_series() {
+        let mut aquarium = Aquarium::starter(Tank::with_seed(60, 20, 7).named("Reef"));
+        aquarium.tanks[1].name = "Reef".to_string();
+        aquarium.tanks[2].name = "Say \"hi\"".to_string();
+        aquarium.tanks[1].tick_count = 12;
+        let text = render(&aquarium, &TickTimes::default());
+
+        assert!(text.contains("aquarium_ticks_total{tank_id=\"0\",tank=\"Reef\"} 0\n"));
+        assert!(text.contains("aquarium_ticks_total{tank_id=\"1\",tank=\"Reef\"} 12\n"));
+        assert!(text.contains("aquarium_ticks_total{tank_id=\"2\",tank=\"Say \\\"hi\\\"\"} 0\n"));
+        assert!(text.contains("aquarium_fish{tank_id=\"1\",tank=\"Reef\",species=\"Guppy\"} 0\n"));
+        assert!(text.contains("aquarium_deaths_total{tank_id=\"0\",tank=\"Reef\",cause=\"disease\"} 0\n"));
+        assert!(text.contains("aquarium_water_ph{tank_id=\"2\","));
+        let series: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| line.rsplit_once(' ').unwrap().0)
+            .collect();
+        let unique: std::collections::HashSet<&str> = series.iter().copied().collect();
+        assert_eq!(unique.len(), series.len());
+    }
+
+    #[test]
+    fn tick_buckets_are_cumulative() {
+        let mut ticks = TickTimes::default();
+        ticks.observe(Duration::from_micros(5));
+        ticks.observe(Duration::from_millis(2));
+        ticks.observe(Duration::from_secs(1));
+        let text = render(&Aquarium::single(Tank::with_seed(60, 20, 7)), &ticks);
+        assert!(text.contains("# TYPE aquarium_tick_duration_seconds histogram\n"));
+        assert!(text.contains("aquarium_tick_duration_seconds_bucket{le=\"0.00001\"} 1\n"));
+        assert!(text.contains("aquarium_tick_duration_seconds_bucket{le=\"0.0025\"} 2\n"));
+        assert!(text.contains("aquarium_tick_duration_seconds_bucket{le=\"0.1\"} 2\n"));
+        assert!(text.contains("aquarium_tick_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("aquarium_tick_duration_seconds_count 3\n"));
+    }
+}
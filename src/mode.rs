@@ -0,0 +1,132 @@
+//! How a game is played: the free sandbox, the economy, survival, or a
+//! scenario with win and lose conditions.
+
+use crate::aquarium::Aquarium;
+use crate::economy::Wallet;
+use crate::scenario::{Outcome, Scenario};
+use crate::tank::Tank;
+use serde::{Deserialize, Serialize};
+
+/// Coins and food a survival game starts with.
+const SURVIVAL_COINS: i64 = 60;
+const SURVIVAL_FOOD: u32 = 30;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    /// Free fish, food and equipment; anything goes.
+    #[default]
+    Sandbox,
+    /// Sandbox with a wallet: everything is bought in the shop.
+    Economy,
+    /// A tight wallet and no god actions.
+    Survival,
+    Scenario(Box<ScenarioRun>),
+}
+
+/// A scenario being played, saved with the aquarium so it resumes.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScenarioRun {
+    pub scenario: Scenario,
+    /// Tick of the first tank when the scenario began.
+    pub started: u64,
+    /// Set once the game is won or lost.
+    #[serde(default)]
+    pub outcome: Option<Outcome>,
+}
+
+impl GameMode {
+    /// Every mode offered for a new game, scenarios last.
+    pub fn choices(scenarios: &[Scenario]) -> Vec<GameMode> {
+        let mut modes = vec![GameMode::Sandbox, GameMode::Economy, GameMode::Survival];
+        modes.extend(scenarios.iter().map(|scenario| {
+            GameMode::Scenario(Box::new(ScenarioRun {
+                scenario: scenario.clone(),
+                started: 0,
+                outcome: None,
+            }))
+        }));
+        modes
+    }
+
+    /// Look up a mode by config name: `sandbox`, `economy`, `survival` or a
+    /// scenario id.
+    pub fn from_config(name: &str, scenarios: &[Scenario]) -> Option<GameMode> {
+        GameMode::choices(scenarios).into_iter().find(|mode| mode.config_name() == name)
+    }
+
+    pub fn config_name(&self) -> &str {
+        match self {
+            GameMode::Sandbox => "sandbox",
+            GameMode::Economy => "economy",
+            GameMode::Survival => "survival",
+            GameMode::Scenario(run) => &run.scenario.id,
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            GameMode::Sandbox => "Sandbox",
+            GameMode::Economy => "Economy",
+            GameMode::Survival => "Survival",
+            GameMode::Scenario(run) => &run.scenario.name,
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            GameMode::Sandbox => "Free fish, food and equipment",
+            GameMode::Economy => "Buy everything in the shop and sell fish you breed",
+            GameMode::Survival => "Little money, random trouble, and no removing or moving fish by hand",
+            GameMode::Scenario(run) => &run.scenario.description,
+        }
+    }
+
+    /// Whether the player may add fish for free, remove them and drag them
+    /// about.
+    pub fn god_actions(&self) -> bool {
+        match self {
+            GameMode::Sandbox | GameMode::Economy => true,
+            GameMode::Survival => false,
+            GameMode::Scenario(run) => run.scenario.god_actions,
+        }
+    }
+
+    /// Whether random incidents are forced on in every tank.
+    pub fn random_events(&self) -> bool {
+        matches!(self, GameMode::Survival)
+    }
+
+    /// A new game in this mode. Scenarios build their own tank of the same
+    /// size; the other modes get `display` plus breeding and quarantine
+    /// tanks. Every tank takes `display`'s incident settings.
+    pub fn new_game(self, mut display: Tank) -> Aquarium {
+        let mut incidents = display.incidents.settings.clone();
+        incidents.enabled |= self.random_events();
+        let mut aquarium = match &self {
+            GameMode::Scenario(run) => {
+                let (width, height) = display.dimensions;
+                let mut aquarium = run.scenario.build(width, height, display.rng.next_u64());
+                aquarium.set_speed(display.simulation_speed);
+                aquarium
+            }
+            _ => Aquarium::starter(display),
+        };
+        match &self {
+            GameMode::Sandbox | GameMode::Scenario(_) => {}
+            GameMode::Economy => aquarium.wallet = Some(Wallet::default()),
+            GameMode::Survival => {
+                aquarium.wallet = Some(Wallet {
+                    coins: SURVIVAL_COINS,
+                    food: SURVIVAL_FOOD,
+                    ..Wallet::default()
+                })
+            }
+        }
+        for tank in &mut aquarium.tanks {
+            tank.incidents.settings = incidents.clone();
+        }
+        aquarium.mode = self;
+        aquarium
+    }
+}
@@ -0,0 +1,124 @@
+//! Name lists used to auto-name fish as they arrive in a tank.
+
+use crate::rng::SimRng;
+
+const GOLDFISH: &[&str] = &[
+    "Goldie", "Nugget", "Sunny", "Marmalade", "Tangerine", "Butterscotch", "Amber", "Biscuit",
+];
+const GUPPY: &[&str] = &[
+    "Pip", "Zippy", "Dot", "Flick", "Sprinkle", "Minnow", "Skipper", "Twinkle",
+];
+const BETTA: &[&str] = &[
+    "Ruby", "Duke", "Sapphire", "Velvet", "Baron", "Crimson", "Indigo", "Plume",
+];
+const TETRA: &[&str] = &[
+    "Neon", "Spark", "Blink", "Comet", "Glimmer", "Flash", "Pixel", "Ember",
+];
+const ANGELFISH: &[&str] = &[
+    "Halo", "Seraph", "Grace", "Aurora", "Stripe", "Celeste", "Willow", "Luna",
+];
+const GENERIC: &[&str] = &["Bubbles", "Finn", "Splash", "Coral", "Nemo", "Shelly"];
+
+/// The name list for a species, or a generic one for unknown species.
+pub fn name_pool(species: &str) -> &'static [&'static str] {
+    match species {
+        "Goldfish" => GOLDFISH,
+        "Guppy" => GUPPY,
+        "Betta" => BETTA,
+        "Tetra" => TETRA,
+        "Angelfish" => ANGELFISH,
+        _ => GENERIC,
+    }
+}
+
+/// Pick a name from the species list that isn't in `taken`, adding a
+/// numeral ("Pip II") once every plain name is in use.
+pub fn generate_name(species: &str, taken: &[&str], rng: &mut SimRng) -> String {
+    let pool = name_pool(species);
+    let start = rng.below(pool.len() as u64) as usize;
+    for generation in 1.. {
+        for offset in 0..pool.len() {
+            let base = pool[(start + offset) % pool.len()];
+            let candidate = if generation == 1 {
+                base.to_string()
+            } else {
+                format!("{} {}", base, roman(generation))
+            };
+            if !taken.contains(&candidate.as_str()) {
+                return candidate;
+            }
+        }
+    }
+    unreachable!("generations are unbounded")
+}
+
+fn roman(mut n: u32) -> String {
+    const NUMERALS: [(u32, &str); 9] = [
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            out.push_str(numeral);
+            n -= value;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fish::Fish;
+    use crate::tank::Tank;
+    use std::collections::HashSet;
+
+    #[test]
+    fn names_are_unique_within_a_tank() {
+        let mut tank = Tank::with_seed(60, 20, 4);
+        for _ in 0..30 {
+            tank.add_fish(Fish::new("Guppy".to_string(), (5, 5)));
+        }
+        let names: HashSet<String> = tank.fish.iter().map(Fish::display_name).collect();
+        assert_eq!(names.len(), 30);
+    }
+
+    #[test]
+    fn numerals_are_added_once_the_pool_is_used_up() {
+        let mut rng = SimRng::new(1);
+        let mut taken: Vec<String> = Vec::new();
+        let mut next = |taken: &[String]| {
+            let taken: Vec<&str> = taken.iter().map(String::as_str).collect();
+            generate_name("Guppy", &taken, &mut rng)
+        };
+        for _ in 0..GUPPY.len() {
+            let name = next(&taken);
+            assert!(GUPPY.contains(&name.as_str()));
+            taken.push(name);
+        }
+        let name = next(&taken);
+        let (base, numeral) = name.split_once(' ').unwrap();
+        assert!(GUPPY.contains(&base));
+        assert_eq!(numeral, "II");
+
+        assert_eq!(roman(3), "III");
+        assert_eq!(roman(14), "XIV");
+        assert_eq!(roman(49), "XLIX");
+    }
+
+    #[test]
+    fn a_seed_picks_the_same_name() {
+        let pick = |seed| generate_name("Betta", &[], &mut SimRng::new(seed));
+        assert_eq!(pick(8), pick(8));
+        assert!(BETTA.contains(&pick(8).as_str()));
+        assert!(GENERIC.contains(&generate_name("Pufferfish", &[], &mut SimRng::new(8)).as_str()));
+    }
+}
@@ -0,0 +1,254 @@
+//! Commands from outside the TUI: the HTTP server and the control socket.
+//!
+//! Servers run on their own threads and hold a [`Remote`]. Each request is
+//! sent over a channel to the main loop, which applies it to the aquarium
+//! between frames with [`Requests::handle`] and sends the reply back, so
+//! the simulation is only ever touched from one thread. Commands follow the
+//! rules of the game being played: economy games pay for fish and food,
+//! and modes without god actions can't remove fish.
+
+use crate::aquarium::Aquarium;
+use crate::events::TimedEvent;
+use crate::fish::Fish;
+use crate::metrics::{self, TickTimes};
+use crate::species;
+use crate::tank::Tank;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How long a server waits for the main loop to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Something to read from or do to the running aquarium. Commands without a
+/// tank use the one on screen.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "command", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Command {
+    /// Mode, wallet, and each tank's water, equipment and incidents.
+    #[serde(alias = "status")]
+    State,
+    /// Every fish in a tank.
+    Fish { tank: Option<usize> },
+    /// Population, lifetime births and deaths and the latest sample.
+    Stats { tank: Option<usize> },
+    /// Feed every fish in a tank.
+    Feed { tank: Option<usize> },
+    /// Add, or in economy games buy, a fish of a species.
+    Add { species: String, tank: Option<usize> },
+    /// Take a fish out of whichever tank it is in.
+    Remove { id: Uuid },
+    /// Run every tank at the preset closest to `speed`.
+    Speed { speed: f32 },
+}
+
+/// A command's JSON result, or why it couldn't be done.
+pub type Reply = Result<Value, String>;
+
+impl Command {
+    /// Whether the command only reads.
+    pub fn is_query(&self) -> bool {
+        matches!(self, Command::State | Command::Fish { .. } | Command::Stats { .. })
+    }
+
+    pub fn apply(self, aquarium: &mut Aquarium) -> Reply {
+        match self {
+            Command::State => Ok(state(aquarium)),
+            Command::Fish { tank } => {
+                let tank = tank_index(aquarium, tank)?;
+                serde_json::to_value(&aquarium.tanks[tank].fish).map_err(|e| e.to_string())
+            }
+            Command::Stats { tank } => {
+                let tank = tank_index(aquarium, tank)?;
+                Ok(stats(&aquarium.tanks[tank]))
+            }
+            Command::Feed { tank } => {
+                let tank = tank_index(aquarium, tank)?;
+                let count = aquarium.tanks[tank].fish.len();
+                if let Some(wallet) = &mut aquarium.wallet
+                    && !wallet.use_food(count as u32)
+                {
+                    return Err(format!("Not enough food ({} portions left)", wallet.food));
+                }
+                aquarium.tanks[tank].feed();
+                Ok(json!({ "tank": tank, "fed": count }))
+            }
+            Command::Add { species, tank } => {
+                let tank = tank_index(aquarium, tank)?;
+                let species = species::get_all_species()
+                    .into_iter()
+                    .find(|s| s.name.eq_ignore_ascii_case(&species))
+                    .ok_or_else(|| format!("Unknown species '{}'", species))?;
+                let price = species.price;
+                match &mut aquarium.wallet {
+                    Some(wallet) => {
+                        if !wallet.pay(price) {
+                            return Err(format!("{} costs {}; you have {}", species.name, price, wallet.coins));
+                        }
+                    }
+                    None => god_actions(aquarium)?,
+                }
+                let tank = &mut aquarium.tanks[tank];
+                let fish = Fish::new(species.name, tank.spawn_position());
+                let id = fish.id;
+                tank.add_fish(fish);
+                let name = tank.get_fish(id).map(Fish::display_name);
+                Ok(json!({ "id": id, "name": name }))
+            }
+            Command::Remove { id } => {
+                god_actions(aquarium)?;
+                let tank = aquarium.tank_of(id).ok_or_else(|| format!("No fish with id {}", id))?;
+                let tank = &mut aquarium.tanks[tank];
+                let name = tank.get_fish(id).map(Fish::display_name);
+                tank.remove_fish(id);
+                Ok(json!({ "removed": name }))
+            }
+            Command::Speed { speed } => {
+                aquarium.set_speed(speed);
+                Ok(json!({ "speed": aquarium.active().simulation_speed }))
+            }
+        }
+    }
+}
+
+/// `tank`, checked, or the one on screen.
+fn tank_index(aquarium: &Aquarium, tank: Option<usize>) -> Result<usize, String> {
+    match tank {
+        Some(index) if index >= aquarium.tanks.len() => {
+            Err(format!("No tank {}; there are {}", index, aquarium.tanks.len()))
+        }
+        Some(index) => Ok(index),
+        None => Ok(aquarium.active),
+    }
+}
+
+fn god_actions(aquarium: &Aquarium) -> Result<(), String> {
+    if aquarium.mode.god_actions() {
+        Ok(())
+    } else {
+        Err(format!("{} doesn't allow that", aquarium.mode.label()))
+    }
+}
+
+fn state(aquarium: &Aquarium) -> Value {
+    let tanks: Vec<Value> = aquarium
+        .tanks
+        .iter()
+        .enumerate()
+        .map(|(index, tank)| {
+            json!({
+                "index": index,
+                "name": tank.name,
+                "tick": tank.tick_count,
+                "speed": tank.simulation_speed,
+                "fish": tank.fish.len(),
+                "water": tank.water,
+                "issues": tank.water.issues(),
+                "equipment": tank.equipment,
+                "incidents": tank.incidents.active,
+            })
+        })
+        .collect();
+    json!({
+        "mode": aquarium.mode.config_name(),
+        "active": aquarium.active,
+        "wallet": aquarium.wallet,
+        "tanks": tanks,
+    })
+}
+
+fn stats(tank: &Tank) -> Value {
+    let mut population: BTreeMap<&str, u32> = BTreeMap::new();
+    for fish in &tank.fish {
+        *population.entry(fish.species.as_str()).or_default() += 1;
+    }
+    json!({
+        "tank": tank.name,
+        "tick": tank.tick_count,
+        "population": population,
+        "totals": tank.stats.totals,
+        "latest": tank.stats.samples.back(),
+    })
+}
+
+enum Request {
+    Run(Command, Sender<Reply>),
+    /// Events from every tank, one receiver each.
+    Subscribe(Sender<Vec<Receiver<TimedEvent>>>),
+    /// Prometheus exposition text.
+    Metrics(Sender<String>),
+}
+
+/// The server side of the channel to the main loop.
+#[derive(Clone)]
+pub struct Remote {
+    sender: Sender<Request>,
+}
+
+/// The main loop's side: requests waiting to be applied.
+pub struct Requests {
+    receiver: Receiver<Request>,
+    /// How long ticks take, timed by the main loop for `/metrics`.
+    pub tick_times: TickTimes,
+}
+
+/// A connected [`Remote`] and [`Requests`] pair.
+pub fn channel() -> (Remote, Requests) {
+    let (sender, receiver) = mpsc::channel();
+    let requests = Requests {
+        receiver,
+        tick_times: TickTimes::default(),
+    };
+    (Remote { sender }, requests)
+}
+
+impl Remote {
+    /// Have the main loop run `command` and wait for its reply.
+    pub fn run(&self, command: Command) -> Reply {
+        let (sender, receiver) = mpsc::channel();
+        self.sender
+            .send(Request::Run(command, sender))
+            .map_err(|_| "The simulation has stopped".to_string())?;
+        receiver
+            .recv_timeout(REPLY_TIMEOUT)
+            .map_err(|_| "The simulation isn't responding".to_string())?
+    }
+
+    /// Receivers for every tank's events. They disconnect when the tanks
+    /// are replaced, as when a new game starts.
+    pub fn subscribe(&self) -> Option<Vec<Receiver<TimedEvent>>> {
+        let (sender, receiver) = mpsc::channel();
+        self.sender.send(Request::Subscribe(sender)).ok()?;
+        receiver.recv_timeout(REPLY_TIMEOUT).ok()
+    }
+
+    /// Every tank's metrics in the Prometheus text format.
+    pub fn metrics(&self) -> Option<String> {
+        let (sender, receiver) = mpsc::channel();
+        self.sender.send(Request::Metrics(sender)).ok()?;
+        receiver.recv_timeout(REPLY_TIMEOUT).ok()
+    }
+}
+
+impl Requests {
+    /// Apply every request waiting, without blocking.
+    pub fn handle(&self, aquarium: &mut Aquarium) {
+        for request in self.receiver.try_iter() {
+            // A server that gave up waiting has dropped its receiver
+            match request {
+                Request::Run(command, reply) => {
+                    let _ = reply.send(command.apply(aquarium));
+                }
+                Request::Subscribe(reply) => {
+                    let _ = reply.send(aquarium.tanks.iter_mut().map(Tank::subscribe).collect());
+                }
+                Request::Metrics(reply) => {
+                    let _ = reply.send(metrics::render(aquarium, &self.tick_times));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Small seeded PRNG (SplitMix64) used by the simulation.
+///
+/// The state is a single u64 so it serializes with the tank, which keeps a
+/// resumed save on the same random sequence it would have had.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        SimRng { state: seed }
+    }
+
+    /// Seed from the system clock, for interactive sessions that don't care
+    /// about reproducibility.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        SimRng::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..upper`. Returns 0 when `upper` is 0.
+    pub fn below(&mut self, upper: u64) -> u64 {
+        if upper == 0 {
+            return 0;
+        }
+        self.next_u64() % upper
+    }
+}
+
+impl Default for SimRng {
+    fn default() -> Self {
+        SimRng::from_time()
+    }
+}
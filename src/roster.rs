@@ -0,0 +1,360 @@
+//! Sorting, filtering and search for the full-screen fish roster.
+
+use crate::fish::Fish;
+use crate::species;
+use crate::tank::Tank;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortColumn {
+    Name,
+    Species,
+    Age,
+    Hunger,
+    Health,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 5] = [
+        SortColumn::Name,
+        SortColumn::Species,
+        SortColumn::Age,
+        SortColumn::Hunger,
+        SortColumn::Health,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortColumn::Name => "Name",
+            SortColumn::Species => "Species",
+            SortColumn::Age => "Age",
+            SortColumn::Hunger => "Hunger",
+            SortColumn::Health => "Health",
+        }
+    }
+
+    fn next(&self) -> SortColumn {
+        let idx = SortColumn::ALL.iter().position(|c| c == self).unwrap_or(0);
+        SortColumn::ALL[(idx + 1) % SortColumn::ALL.len()]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RosterFilter {
+    All,
+    Species(String),
+    Hungry,
+    Critical,
+    Breedable,
+}
+
+impl RosterFilter {
+    pub fn label(&self) -> String {
+        match self {
+            RosterFilter::All => "all".to_string(),
+            RosterFilter::Species(name) => name.clone(),
+            RosterFilter::Hungry => "hungry".to_string(),
+            RosterFilter::Critical => "critical".to_string(),
+            RosterFilter::Breedable => "breedable".to_string(),
+        }
+    }
+
+    fn matches(&self, fish: &Fish) -> bool {
+        match self {
+            RosterFilter::All => true,
+            RosterFilter::Species(name) => &fish.species == name,
+            RosterFilter::Hungry => fish.hunger >= 50,
+            RosterFilter::Critical => fish.hunger >= 80 || fish.health <= 30,
+            RosterFilter::Breedable => fish.ready_to_breed(),
+        }
+    }
+
+    /// All, then each species, then the condition filters.
+    fn next(&self) -> RosterFilter {
+        let mut cycle = vec![RosterFilter::All];
+        cycle.extend(
+            species::get_all_species()
+                .into_iter()
+                .map(|s| RosterFilter::Species(s.name)),
+        );
+        cycle.extend([
+            RosterFilter::Hungry,
+            RosterFilter::Critical,
+            RosterFilter::Breedable,
+        ]);
+        let idx = cycle.iter().position(|f| f == self).unwrap_or(0);
+        cycle[(idx + 1) % cycle.len()].clone()
+    }
+}
+
+/// Case-insensitive subsequence match, so "gf3a" finds "Goldfish #3a1c".
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|q| chars.any(|c| c == q))
+}
+
+#[derive(Clone, Debug)]
+pub struct RosterState {
+    pub sort: SortColumn,
+    pub descending: bool,
+    pub filter: RosterFilter,
+    pub query: String,
+    /// Typing goes into `query` instead of the keymap.
+    pub searching: bool,
+    /// Row under the cursor, tracked by id so births and deaths don't move it.
+    pub cursor: Option<Uuid>,
+    pub marked: HashSet<Uuid>,
+}
+
+impl Default for RosterState {
+    fn default() -> Self {
+        RosterState {
+            sort: SortColumn::Name,
+            descending: false,
+            filter: RosterFilter::All,
+            query: String::new(),
+            searching: false,
+            cursor: None,
+            marked: HashSet::new(),
+        }
+    }
+}
+
+impl RosterState {
+    /// Fish that pass the filter and search, in display order.
+    pub fn rows<'a>(&self, tank: &'a Tank) -> Vec<&'a Fish> {
+        let mut rows: Vec<&Fish> = tank
+            .fish
+            .iter()
+            .filter(|f| self.filter.matches(f))
+            .filter(|f| fuzzy_match(&self.query, &f.display_name()))
+            .collect();
+        rows.sort_by(|a, b| {
+            let order = match self.sort {
+                SortColumn::Name => a.display_name().cmp(&b.display_name()),
+                SortColumn::Species => a.species.cmp(&b.species),
+                SortColumn::Age => a.age.cmp(&b.age),
+                SortColumn::Hunger => a.hunger.cmp(&b.hunger),
+                SortColumn::Health => a.health.cmp(&b.health),
+            };
+            if self.descending { order.reverse() } else { order }
+        });
+        rows
+    }
+
+    /// Index of the cursor row, falling back to the first row.
+    pub fn cursor_index(&self, rows: &[&Fish]) -> Option<usize> {
+        if rows.is_empty() {
+            return None;
+        }
+        Some(
+            self.cursor
+                .and_then(|id| rows.iter().position(|f| f.id == id))
+                .unwrap_or(0),
+        )
+    }
+
+    /// Move the cursor by `delta` rows, clamped to the list.
+    pub fn move_cursor(&mut self, tank: &Tank, delta: isize) {
+        let rows = self.rows(tank);
+        let Some(idx) = self.cursor_index(&rows) else {
+            self.cursor = None;
+            return;
+        };
+        let target = (idx as isize + delta).clamp(0, rows.len() as isize - 1) as usize;
+        self.cursor = Some(rows[target].id);
+    }
+
+    pub fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+    }
+
+    pub fn cycle_filter(&mut self) {
+        self.filter = self.filter.next();
+    }
+
+    pub fn toggle_mark(&mut self, tank: &Tank) {
+        let rows = self.rows(tank);
+        if let Some(idx) = self.cursor_index(&rows) {
+            let id = rows[idx].id;
+            if !self.marked.remove(&id) {
+                self.marked.insert(id);
+            }
+        }
+    }
+
+    /// Mark every visible row, or clear the marks if they all are marked.
+    pub fn mark_all(&mut self, tank: &Tank) {
+        let visible: Vec<Uuid> = self.rows(tank).iter().map(|f| f.id).collect();
+        if visible.iter().all(|id| self.marked.contains(id)) {
+            self.marked.clear();
+        } else {
+            self.marked.extend(visible);
+        }
+    }
+
+    /// Marked fish still in the tank, or the cursor row if none are marked.
+    pub fn targets(&self, tank: &Tank) -> Vec<Uuid> {
+        let marked: Vec<Uuid> = tank
+            .fish
+            .iter()
+            .map(|f| f.id)
+            .filter(|id| self.marked.contains(id))
+            .collect();
+        if !marked.is_empty() {
+            return marked;
+        }
+        let rows = self.rows(tank);
+        self.cursor_index(&rows)
+            .map(|idx| vec![rows[idx].id])
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tank of named guppies and tetras with the given hunger.
+    fn tank(fish: &[(&str, &str, u8)]) -> Tank {
+        let mut tank = Tank::with_seed(60, 20, 1);
+        for (name, species, hunger) in fish {
+            let mut f = Fish::new(species.to_string(), (5, 5));
+            f.name = Some(name.to_string());
+            f.hunger = *hunger;
+            tank.add_fish(f);
+        }
+        tank
+    }
+
+    fn names(roster: &RosterState, tank: &Tank) -> Vec<String> {
+        roster.rows(tank).iter().map(|f| f.display_name()).collect()
+    }
+
+    #[test]
+    fn sorts_by_each_column() {
+        let tank = tank(&[("Cleo", "Guppy", 70), ("Bubbles", "Tetra", 10), ("Angel", "Guppy", 40)]);
+        let mut roster = RosterState::default();
+        assert_eq!(names(&roster, &tank), ["Angel", "Bubbles", "Cleo"]);
+        roster.descending = true;
+        assert_eq!(names(&roster, &tank), ["Cleo", "Bubbles", "Angel"]);
+        roster.descending = false;
+        roster.cycle_sort();
+        assert_eq!(roster.sort, SortColumn::Species);
+        assert_eq!(roster.rows(&tank)[2].species, "Tetra");
+        roster.cycle_sort();
+        roster.cycle_sort();
+        assert_eq!(roster.sort, SortColumn::Hunger);
+        assert_eq!(names(&roster, &tank), ["Bubbles", "Angel", "Cleo"]);
+        roster.cycle_sort();
+        roster.cycle_sort();
+        assert_eq!(roster.sort, SortColumn::Name);
+    }
+
+    #[test]
+    fn filters_and_searches() {
+        let tank = tank(&[("Cleo", "Guppy", 90), ("Bubbles", "Tetra", 10), ("Angel", "Guppy", 55)]);
+        let mut roster = RosterState {
+            filter: RosterFilter::Species("Guppy".to_string()),
+            ..RosterState::default()
+        };
+        assert_eq!(names(&roster, &tank), ["Angel", "Cleo"]);
+        roster.filter = RosterFilter::Hungry;
+        assert_eq!(names(&roster, &tank), ["Angel", "Cleo"]);
+        roster.filter = RosterFilter::Critical;
+        assert_eq!(names(&roster, &tank), ["Cleo"]);
+
+        roster.filter = RosterFilter::All;
+        roster.query = "BBL".to_string();
+        assert_eq!(names(&roster, &tank), ["Bubbles"]);
+        roster.query = "c o".to_string();
+        assert_eq!(names(&roster, &tank), ["Cleo"]);
+        assert!(fuzzy_match("gf3a", "Goldfish #3a1c"));
+        assert!(!fuzzy_match("fg", "Goldfish"));
+    }
+
+    #[test]
+    fn filters_cycle_through_every_species() {
+        let mut filter = RosterFilter::All;
+        let mut seen = vec![];
+        loop {
+            filter = filter.next();
+            if filter == RosterFilter::All {
+                break;
+            }
+            seen.push(filter.label());
+        }
+        assert_eq!(seen.len(), species::get_all_species().len() + 3);
+        assert_eq!(seen.last().unwrap(), "breedable");
+    }
+
+    #[test]
+    fn breedable_uses_the_tanks_breeding_rule() {
+        let mut tank = tank(&[("Calm", "Guppy", 0), ("Shaken", "Guppy", 0), ("Tired", "Guppy", 0)]);
+        tank.fish[1].stress = 10;
+        tank.fish[2].breeding_cooldown = 100;
+        let roster = RosterState {
+            filter: RosterFilter::Breedable,
+            ..RosterState::default()
+        };
+        assert_eq!(names(&roster, &tank), ["Calm"]);
+        assert!(tank.fish.iter().all(|f| roster.filter.matches(f) == f.ready_to_breed()));
+    }
+
+    #[test]
+    fn cursor_follows_its_fish() {
+        let mut tank = tank(&[("Angel", "Guppy", 0), ("Bubbles", "Guppy", 0), ("Cleo", "Guppy", 0)]);
+        let mut roster = RosterState::default();
+        assert_eq!(roster.cursor_index(&roster.rows(&tank)), Some(0));
+        roster.move_cursor(&tank, 1);
+        roster.move_cursor(&tank, 1);
+        roster.move_cursor(&tank, 1);
+        let cleo = tank.fish[2].id;
+        assert_eq!(roster.cursor, Some(cleo));
+
+        // Removing an earlier fish keeps the cursor on Cleo
+        let angel = tank.fish[0].id;
+        tank.remove_fish(angel);
+        let rows = roster.rows(&tank);
+        assert_eq!(rows[roster.cursor_index(&rows).unwrap()].id, cleo);
+        roster.move_cursor(&tank, -5);
+        assert_eq!(roster.cursor, Some(tank.fish[0].id));
+
+        // Losing the cursor's fish falls back to the first row
+        tank.remove_fish(roster.cursor.unwrap());
+        let rows = roster.rows(&tank);
+        assert_eq!(roster.cursor_index(&rows), Some(0));
+        tank.remove_fish(cleo);
+        roster.move_cursor(&tank, 1);
+        assert_eq!(roster.cursor, None);
+    }
+
+    #[test]
+    fn marks_pick_the_targets() {
+        let mut tank = tank(&[("Angel", "Guppy", 0), ("Bubbles", "Tetra", 0), ("Cleo", "Guppy", 0)]);
+        let mut roster = RosterState::default();
+        // With nothing marked the cursor row is the target
+        assert_eq!(roster.targets(&tank), [tank.fish[0].id]);
+
+        roster.move_cursor(&tank, 1);
+        roster.toggle_mark(&tank);
+        assert_eq!(roster.targets(&tank), [tank.fish[1].id]);
+        roster.toggle_mark(&tank);
+        assert!(roster.marked.is_empty());
+
+        roster.filter = RosterFilter::Species("Guppy".to_string());
+        roster.mark_all(&tank);
+        assert_eq!(roster.targets(&tank), [tank.fish[0].id, tank.fish[2].id]);
+        // Marks on fish that have left the tank are ignored
+        let angel = tank.fish[0].id;
+        tank.remove_fish(angel);
+        assert_eq!(roster.targets(&tank), [tank.fish[1].id]);
+        roster.mark_all(&tank);
+        assert!(roster.marked.is_empty());
+    }
+}
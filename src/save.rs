@@ -1,20 +1,70 @@
+//! JSON save files. A save holds a whole [`Aquarium`]; files from before
+//! there were several tanks hold a single [`Tank`] and load as a one-tank
+//! aquarium.
+
+use crate::aquarium::Aquarium;
 use crate::tank::Tank;
 use std::fs;
 use std::io;
+use std::path::Path;
 
 const SAVE_FILE: &str = "tank.json";
 
+/// Write the tank to `tank.json` in the working directory.
 pub fn save_tank(tank: &Tank) -> io::Result<()> {
+    save_tank_to(tank, Path::new(SAVE_FILE))
+}
+
+/// Write the tank as pretty-printed JSON to `path`.
+pub fn save_tank_to(tank: &Tank, path: &Path) -> io::Result<()> {
     let json = serde_json::to_string_pretty(tank)?;
-    fs::write(SAVE_FILE, json)?;
+    fs::write(path, json)?;
     Ok(())
 }
 
+/// Load `tank.json` from the working directory, if present and valid.
 pub fn load_tank() -> Option<Tank> {
-    if !std::path::Path::new(SAVE_FILE).exists() {
+    if !Path::new(SAVE_FILE).exists() {
         return None;
     }
 
-    let json = fs::read_to_string(SAVE_FILE).ok()?;
-    serde_json::from_str(&json).ok()
+    load_tank_from(Path::new(SAVE_FILE)).ok()
+}
+
+/// Load a tank from any JSON save file; for a whole aquarium, the tank that
+/// was on screen.
+pub fn load_tank_from(path: &Path) -> io::Result<Tank> {
+    let mut aquarium = load_aquarium_from(path)?;
+    let active = aquarium.active;
+    Ok(aquarium.tanks.swap_remove(active))
+}
+
+/// Write every tank as pretty-printed JSON to `path`.
+pub fn save_aquarium_to(aquarium: &Aquarium, path: &Path) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(aquarium)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Load an aquarium, or a single-tank save as a one-tank aquarium.
+pub fn load_aquarium_from(path: &Path) -> io::Result<Aquarium> {
+    let json = fs::read_to_string(path)?;
+    let value: serde_json::Value = serde_json::from_str(&json)?;
+    let mut aquarium = if value.get("tanks").is_some() {
+        serde_json::from_value::<Aquarium>(value)?
+    } else {
+        Aquarium::single(serde_json::from_value(value)?)
+    };
+    if aquarium.tanks.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: save has no tanks", path.display()),
+        ));
+    }
+    aquarium.active = aquarium.active.min(aquarium.tanks.len() - 1);
+    for tank in &mut aquarium.tanks {
+        tank.assign_sexes();
+        tank.sync_lineage();
+    }
+    Ok(aquarium)
 }
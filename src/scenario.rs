@@ -0,0 +1,428 @@
+//! Data-defined challenges with win and lose conditions.
+//!
+//! Scenarios are TOML `[[scenario]]` tables: the built-in ones in
+//! `assets/scenarios.toml`, plus any in `<config dir>/scenarios/*.toml`.
+//!
+//! ```toml
+//! [[scenario]]
+//! id = "tetra-school"
+//! name = "Tetra School"
+//! description = "Keep a school of 20 tetras alive for 4 days."
+//! stock = { Tetra = 20 }
+//! coins = 150
+//! win = [{ type = "elapsed", days = 4 }]
+//! lose = [{ type = "population", species = "Tetra", below = 20 }]
+//! ```
+//!
+//! The game is won once every `win` condition holds at the same time, and
+//! lost as soon as any `lose` condition does or `time_limit_days` runs out.
+
+use crate::aquarium::Aquarium;
+use crate::config;
+use crate::economy::Wallet;
+use crate::fish::{Fish, Sex};
+use crate::species;
+use crate::tank::Tank;
+use crate::water::Water;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Ticks in one in-game day.
+pub const TICKS_PER_DAY: u64 = 1000;
+
+const BUILTIN: &str = include_str!("../assets/scenarios.toml");
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Fish the tank starts with, by species.
+    #[serde(default)]
+    pub stock: BTreeMap<String, u32>,
+    /// Starting coins; without them there is no wallet and food is free.
+    #[serde(default)]
+    pub coins: Option<i64>,
+    /// Whether the tank starts with a heater.
+    #[serde(default = "default_heater")]
+    pub heater: bool,
+    /// Allow free fish, removing and dragging, as in the sandbox.
+    #[serde(default)]
+    pub god_actions: bool,
+    /// Water the tank starts with.
+    #[serde(default)]
+    pub water: StartingWater,
+    #[serde(default)]
+    pub time_limit_days: Option<f32>,
+    pub win: Vec<Condition>,
+    #[serde(default)]
+    pub lose: Vec<Condition>,
+}
+
+fn default_heater() -> bool {
+    true
+}
+
+/// Starting water, `water = { bacteria = 0.0, ammonia = 0.5 }`. Anything
+/// left out is as in a new sandbox tank: clean, with a cycled filter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StartingWater {
+    /// Nitrifying bacteria, 0.0 for a filter that still has to cycle.
+    pub bacteria: f32,
+    /// ppm.
+    pub ammonia: f32,
+    pub nitrate: f32,
+}
+
+impl Default for StartingWater {
+    fn default() -> Self {
+        let water = Water::default();
+        StartingWater {
+            bacteria: water.bacteria,
+            ammonia: water.ammonia,
+            nitrate: water.nitrate,
+        }
+    }
+}
+
+/// Something true or false about the aquarium at a given moment.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Condition {
+    /// At least this many days since the scenario started.
+    Elapsed { days: f32 },
+    /// Fish of one species (or all fish) within bounds.
+    Population {
+        #[serde(default)]
+        species: Option<String>,
+        #[serde(default)]
+        at_least: Option<usize>,
+        #[serde(default)]
+        below: Option<usize>,
+    },
+    Deaths { at_least: u64 },
+    Births { at_least: u64 },
+    /// A living fish of at least this generation.
+    Generation { at_least: u32 },
+    Coins { at_least: i64 },
+    /// Water in the first tank, in ppm.
+    Ammonia { at_most: f32 },
+    Nitrate { at_least: f32 },
+}
+
+impl Condition {
+    /// Whether the condition holds `elapsed` ticks into the scenario.
+    pub fn holds(&self, aquarium: &Aquarium, elapsed: u64) -> bool {
+        let water = &aquarium.tanks[0].water;
+        let totals = || aquarium.tanks.iter().map(|tank| &tank.stats.totals);
+        let fish = || aquarium.tanks.iter().flat_map(|tank| &tank.fish);
+        match self {
+            Condition::Elapsed { days } => elapsed as f32 >= days * TICKS_PER_DAY as f32,
+            Condition::Population { species, at_least, below } => {
+                let count = fish()
+                    .filter(|f| species.as_ref().is_none_or(|s| f.species == *s))
+                    .count();
+                at_least.is_none_or(|n| count >= n) && below.is_none_or(|n| count < n)
+            }
+            Condition::Deaths { at_least } => totals().map(|t| t.deaths()).sum::<u64>() >= *at_least,
+            Condition::Births { at_least } => totals().map(|t| t.births).sum::<u64>() >= *at_least,
+            Condition::Generation { at_least } => fish().any(|f| f.generation >= *at_least),
+            Condition::Coins { at_least } => aquarium.wallet.as_ref().is_some_and(|w| w.coins >= *at_least),
+            Condition::Ammonia { at_most } => water.ammonia <= *at_most,
+            Condition::Nitrate { at_least } => water.nitrate >= *at_least,
+        }
+    }
+
+    /// "at least 20 Tetra", for objectives and results.
+    pub fn describe(&self) -> String {
+        match self {
+            Condition::Elapsed { days } => format!("{} days pass", days),
+            Condition::Population { species, at_least, below } => {
+                let species = species.as_deref().unwrap_or("fish");
+                match (at_least, below) {
+                    (Some(low), Some(high)) => format!("{} to {} {}", low, high - 1, species),
+                    (Some(low), None) => format!("at least {} {}", low, species),
+                    (None, Some(high)) => format!("fewer than {} {}", high, species),
+                    (None, None) => format!("any number of {}", species),
+                }
+            }
+            Condition::Deaths { at_least: 1 } => "a fish dies".to_string(),
+            Condition::Deaths { at_least } => format!("{} fish die", at_least),
+            Condition::Births { at_least } => format!("{} fish are born", at_least),
+            Condition::Generation { at_least } => format!("a generation {} fish lives", at_least),
+            Condition::Coins { at_least } => format!("{} coins saved", at_least),
+            Condition::Ammonia { at_most } => format!("ammonia at most {} ppm", at_most),
+            Condition::Nitrate { at_least } => format!("nitrate at least {} ppm", at_least),
+        }
+    }
+}
+
+/// How a scenario ended.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Outcome {
+    pub won: bool,
+    /// Ticks from the start to the end.
+    pub elapsed: u64,
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct ScenarioFile {
+    scenario: Vec<Scenario>,
+}
+
+impl Scenario {
+    /// The scenarios in `assets/scenarios.toml`.
+    pub fn builtin() -> Vec<Scenario> {
+        parse(BUILTIN).expect("built-in scenarios are valid")
+    }
+
+    /// Built-in scenarios followed by the user's, sorted by file name.
+    pub fn load_all() -> io::Result<Vec<Scenario>> {
+        let mut scenarios = Scenario::builtin();
+        let Some(dir) = scenario_dir().filter(|dir| dir.is_dir()) else {
+            return Ok(scenarios);
+        };
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            scenarios.extend(from_file(&path)?);
+        }
+        Ok(scenarios)
+    }
+
+    /// A fresh single-tank aquarium set up for this scenario. Stocked fish
+    /// alternate female and male so every species can breed.
+    pub fn build(&self, width: u16, height: u16, seed: u64) -> Aquarium {
+        let mut tank = Tank::with_seed(width, height, seed);
+        if !self.heater {
+            tank.equipment.heater = None;
+        }
+        tank.water.bacteria = self.water.bacteria.clamp(0.0, 1.0);
+        tank.water.ammonia = self.water.ammonia.max(0.0);
+        tank.water.nitrate = self.water.nitrate.max(0.0);
+        for (species, count) in &self.stock {
+            for n in 0..*count {
+                let mut fish = Fish::new(species.clone(), tank.spawn_position());
+                fish.sex = Some(if n % 2 == 0 { Sex::Female } else { Sex::Male });
+                tank.add_fish(fish);
+            }
+        }
+        let mut aquarium = Aquarium::single(tank);
+        aquarium.wallet = self.coins.map(|coins| Wallet {
+            coins,
+            ..Wallet::default()
+        });
+        aquarium
+    }
+
+    /// The outcome at this point, if the game is decided.
+    pub fn evaluate(&self, aquarium: &Aquarium, elapsed: u64) -> Option<Outcome> {
+        let outcome = |won, reason| Some(Outcome { won, elapsed, reason });
+        if let Some(condition) = self.lose.iter().find(|c| c.holds(aquarium, elapsed)) {
+            return outcome(false, format!("Lost: {}", condition.describe()));
+        }
+        if !self.win.is_empty() && self.win.iter().all(|c| c.holds(aquarium, elapsed)) {
+            let goals: Vec<String> = self.win.iter().map(Condition::describe).collect();
+            return outcome(true, format!("Won: {}", goals.join(", ")));
+        }
+        if let Some(days) = self.time_limit_days
+            && elapsed as f32 >= days * TICKS_PER_DAY as f32
+        {
+            return outcome(false, format!("Lost: ran out of time after {} days", days));
+        }
+        None
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(unknown) = self.stock.keys().find(|name| species::get_species(name).is_none()) {
+            return Err(format!("scenario '{}' stocks unknown species {}", self.id, unknown));
+        }
+        if self.win.is_empty() {
+            return Err(format!("scenario '{}' has no win conditions", self.id));
+        }
+        Ok(())
+    }
+}
+
+/// `<config dir>/scenarios`.
+pub fn scenario_dir() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("scenarios"))
+}
+
+fn from_file(path: &Path) -> io::Result<Vec<Scenario>> {
+    let text = fs::read_to_string(path)?;
+    parse(&text).map_err(|message| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message))
+    })
+}
+
+fn parse(text: &str) -> Result<Vec<Scenario>, String> {
+    let file: ScenarioFile = toml::from_str(text).map_err(|e| e.to_string())?;
+    for scenario in &file.scenario {
+        scenario.validate()?;
+    }
+    Ok(file.scenario)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::{self, ShopItem};
+    use crate::maintenance::{FreshWater, Task};
+    use crate::mode::{GameMode, ScenarioRun};
+    use crate::tank::DEFAULT_DIMENSIONS;
+    use crate::water::{AMMONIA_WARNING, NITRATE_WARNING};
+
+    /// Ticks between feedings and between water checks in a headless game.
+    const FEED_EVERY: u64 = 200;
+    const CARE_EVERY: u64 = 50;
+    /// Bred fish kept beyond what the scenario needs; the rest are sold.
+    const SPARE: usize = 4;
+    /// Coins kept back for food rather than spent on filters.
+    const FOOD_RESERVE: u32 = 40;
+
+    /// Play a scenario headless until it is decided, as a careful player
+    /// would: buying the best filter it can afford, feeding regularly,
+    /// selling surplus fry, and changing conditioned water at the tank's
+    /// temperature when waste builds up.
+    fn play(scenario: &Scenario, seed: u64) -> Outcome {
+        let mode = GameMode::Scenario(Box::new(ScenarioRun {
+            scenario: scenario.clone(),
+            started: 0,
+            outcome: None,
+        }));
+        let display = Tank::with_seed(DEFAULT_DIMENSIONS.0, DEFAULT_DIMENSIONS.1, seed);
+        let mut aquarium = mode.new_game(display);
+        let goal = scenario.win.iter().filter_map(|condition| match condition {
+            Condition::Population { at_least, .. } => *at_least,
+            _ => None,
+        });
+        let keep = goal.chain([scenario.stock.values().sum::<u32>() as usize]).max().unwrap_or(0) + SPARE;
+        loop {
+            let tick = aquarium.tanks[0].tick_count;
+            for tank in 0..aquarium.tanks.len() {
+                if tick.is_multiple_of(FEED_EVERY) {
+                    shop(&mut aquarium, tank, keep);
+                    feed(&mut aquarium, tank);
+                }
+                if tick.is_multiple_of(CARE_EVERY) {
+                    care(&mut aquarium.tanks[tank]);
+                }
+            }
+            aquarium.tick();
+            if let Some(outcome) = aquarium.update_scenario() {
+                return outcome;
+            }
+        }
+    }
+
+    fn shop(aquarium: &mut Aquarium, index: usize, keep: usize) {
+        let Some(wallet) = &mut aquarium.wallet else {
+            return;
+        };
+        let tank = &mut aquarium.tanks[index];
+        let excess = tank.fish.len().saturating_sub(keep);
+        let surplus: Vec<Fish> = tank.fish.iter().rev().filter(|f| f.generation > 0).take(excess).cloned().collect();
+        for fish in &surplus {
+            let price = species::get_species(&fish.species).map_or(1, |s| economy::sale_price(fish, &s));
+            if tank.sell_fish(fish.id, price).is_some() {
+                wallet.receive(price);
+            }
+        }
+        let filter = ShopItem::Filter;
+        while filter.check(tank).is_ok() && wallet.coins >= (filter.price() + FOOD_RESERVE) as i64 {
+            wallet.pay(filter.price());
+            filter.install(tank, wallet);
+        }
+    }
+
+    fn feed(aquarium: &mut Aquarium, tank: usize) {
+        let count = aquarium.tanks[tank].fish.len() as u32;
+        if let Some(wallet) = &mut aquarium.wallet {
+            if wallet.food < count && wallet.pay(ShopItem::Food.price()) {
+                ShopItem::Food.install(&mut aquarium.tanks[tank], wallet);
+            }
+            if !wallet.use_food(count) {
+                return;
+            }
+        }
+        aquarium.tanks[tank].feed();
+    }
+
+    fn care(tank: &mut Tank) {
+        let water = &tank.water;
+        if water.ammonia < AMMONIA_WARNING / 2.0 && water.nitrate < NITRATE_WARNING / 2.0 {
+            return;
+        }
+        let fresh = FreshWater {
+            temperature: water.temperature,
+            ..FreshWater::default()
+        };
+        Task::Conditioner.perform(tank, &fresh);
+        Task::WaterChange.perform(tank, &fresh);
+        if tank.water.debris > 5.0 {
+            Task::GravelVacuum.perform(tank, &fresh);
+            Task::TopOff.perform(tank, &fresh);
+        }
+    }
+
+    #[test]
+    fn builtin_scenarios_can_be_won() {
+        for scenario in Scenario::builtin() {
+            for seed in 1..=3 {
+                let outcome = play(&scenario, seed);
+                assert!(outcome.won, "{} (seed {}): {}", scenario.id, seed, outcome.reason);
+            }
+        }
+    }
+
+    #[test]
+    fn scenarios_set_the_starting_water() {
+        let scenarios = Scenario::builtin();
+        let cycle = scenarios.iter().find(|s| s.id == "cycle").unwrap();
+        let aquarium = cycle.build(60, 20, 1);
+        assert_eq!(aquarium.tanks[0].water.bacteria, 0.0);
+        assert_eq!(aquarium.tanks[0].equipment.heater, None);
+
+        let school = scenarios.iter().find(|s| s.id == "tetra-school").unwrap();
+        assert_eq!(school.build(60, 20, 1).tanks[0].water, Water::default());
+    }
+
+    #[test]
+    fn losing_is_checked_before_winning() {
+        let scenario = parse(
+            r#"
+            [[scenario]]
+            id = "test"
+            name = "Test"
+            description = ""
+            stock = { Guppy = 2 }
+            win = [{ type = "population", at_least = 1 }]
+            lose = [{ type = "population", below = 3 }]
+            "#,
+        )
+        .unwrap()
+        .remove(0);
+        let aquarium = scenario.build(60, 20, 1);
+        let outcome = scenario.evaluate(&aquarium, 0).unwrap();
+        assert!(!outcome.won);
+        assert_eq!(outcome.reason, "Lost: fewer than 3 fish");
+    }
+
+    #[test]
+    fn rejects_bad_scenarios() {
+        let stock = "[[scenario]]\nid = \"x\"\nname = \"X\"\ndescription = \"\"\nstock = { Shark = 1 }\nwin = [{ type = \"elapsed\", days = 1 }]\n";
+        assert!(parse(stock).unwrap_err().contains("unknown species Shark"));
+        let water = "[[scenario]]\nid = \"x\"\nname = \"X\"\ndescription = \"\"\nwater = { salt = 1.0 }\nwin = [{ type = \"elapsed\", days = 1 }]\n";
+        assert!(parse(water).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! Headless batch runs of the simulation, used by `aquarium-sim simulate`.
+
+use crate::save;
+use crate::species;
+use crate::tank::{DeathCause, Tank, DEFAULT_DIMENSIONS};
+use serde::Serialize;
+use std::io;
+use std::path::PathBuf;
+
+/// Options for a headless run.
+pub struct SimulationConfig {
+    pub ticks: u64,
+    pub seed: u64,
+    pub tank_path: Option<PathBuf>,
+    pub feed_every: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DeathCounts {
+    pub starvation: u64,
+    pub old_age: u64,
+    pub poor_water: u64,
+    pub disease: u64,
+}
+
+impl DeathCounts {
+    fn record(&mut self, cause: DeathCause) {
+        match cause {
+            DeathCause::Starvation => self.starvation += 1,
+            DeathCause::OldAge => self.old_age += 1,
+            DeathCause::PoorWater => self.poor_water += 1,
+            DeathCause::Disease => self.disease += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.starvation + self.old_age + self.poor_water + self.disease
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SpeciesReport {
+    pub species: String,
+    pub initial: u64,
+    pub born: u64,
+    pub deaths: DeathCounts,
+    pub final_count: u64,
+    /// Share of every fish that existed during the run still alive at the end.
+    pub survival_rate: f32,
+    pub extinct_at_tick: Option<u64>,
+}
+
+/// Aggregate results of a headless run.
+#[derive(Serialize, Clone, Debug)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub ticks: u64,
+    pub feed_every: Option<u64>,
+    pub initial_population: u64,
+    pub final_population: u64,
+    pub peak_population: u64,
+    pub births: u64,
+    pub deaths: DeathCounts,
+    pub species: Vec<SpeciesReport>,
+}
+
+/// Run `Tank::tick` without a terminal and collect what happened.
+pub fn run(config: &SimulationConfig) -> io::Result<SimulationReport> {
+    let mut tank = match &config.tank_path {
+        Some(path) => {
+            let mut tank = save::load_tank_from(path)?;
+            tank.rng = crate::rng::SimRng::new(config.seed);
+            tank
+        }
+        None => Tank::with_seed(DEFAULT_DIMENSIONS.0, DEFAULT_DIMENSIONS.1, config.seed).stocked(),
+    };
+
+    let mut species: Vec<SpeciesReport> = species::get_all_species()
+        .into_iter()
+        .map(|s| SpeciesReport {
+            initial: count_species(&tank, &s.name),
+            species: s.name,
+            born: 0,
+            deaths: DeathCounts::default(),
+            final_count: 0,
+            survival_rate: 0.0,
+            extinct_at_tick: None,
+        })
+        .collect();
+
+    let initial_population = tank.fish.len() as u64;
+    let mut peak_population = initial_population;
+    let mut births = 0;
+    let mut deaths = DeathCounts::default();
+
+    for tick in 1..=config.ticks {
+        if let Some(every) = config.feed_every
+            && every > 0
+            && tick.is_multiple_of(every)
+        {
+            tank.feed();
+        }
+
+        let summary = tank.tick();
+
+        for name in summary.births() {
+            births += 1;
+            if let Some(report) = species.iter_mut().find(|r| r.species == name) {
+                report.born += 1;
+            }
+        }
+        for death in summary.deaths() {
+            deaths.record(death.cause);
+            if let Some(report) = species.iter_mut().find(|r| r.species == death.species) {
+                report.deaths.record(death.cause);
+                if report.extinct_at_tick.is_none() && count_species(&tank, &report.species) == 0 {
+                    report.extinct_at_tick = Some(tank.tick_count);
+                }
+            }
+        }
+
+        peak_population = peak_population.max(tank.fish.len() as u64);
+    }
+
+    for report in &mut species {
+        report.final_count = count_species(&tank, &report.species);
+        let ever_lived = report.initial + report.born;
+        if ever_lived > 0 {
+            report.survival_rate = report.final_count as f32 / ever_lived as f32;
+        }
+    }
+
+    Ok(SimulationReport {
+        seed: config.seed,
+        ticks: config.ticks,
+        feed_every: config.feed_every,
+        initial_population,
+        final_population: tank.fish.len() as u64,
+        peak_population,
+        births,
+        deaths,
+        species,
+    })
+}
+
+fn count_species(tank: &Tank, name: &str) -> u64 {
+    tank.fish.iter().filter(|f| f.species == name).count() as u64
+}
+
+impl SimulationReport {
+    pub fn to_table(&self) -> String {
+        let feeding = match self.feed_every {
+            Some(every) => format!("every {} ticks", every),
+            None => "never".to_string(),
+        };
+        let mut out = String::new();
+        out.push_str(&format!(
+            "Ticks: {}    Seed: {}    Feeding: {}\n",
+            self.ticks, self.seed, feeding
+        ));
+        out.push_str(&format!(
+            "Population: {} -> {} (peak {})    Births: {}    Deaths: {} (starvation {}, old age {}, poor water {}, disease {})\n\n",
+            self.initial_population,
+            self.final_population,
+            self.peak_population,
+            self.births,
+            self.deaths.total(),
+            self.deaths.starvation,
+            self.deaths.old_age,
+            self.deaths.poor_water,
+            self.deaths.disease
+        ));
+        out.push_str(&format!(
+            "{:<10} {:>7} {:>7} {:>8} {:>8} {:>8} {:>8} {:>7} {:>9} {:>10}\n",
+            "Species", "Initial", "Born", "Starved", "Old age", "Water", "Disease", "Final", "Survival", "Extinct at"
+        ));
+        for report in &self.species {
+            let extinct = report
+                .extinct_at_tick
+                .map_or("-".to_string(), |t| t.to_string());
+            out.push_str(&format!(
+                "{:<10} {:>7} {:>7} {:>8} {:>8} {:>8} {:>8} {:>7} {:>8.1}% {:>10}\n",
+                report.species,
+                report.initial,
+                report.born,
+                report.deaths.starvation,
+                report.deaths.old_age,
+                report.deaths.poor_water,
+                report.deaths.disease,
+                report.final_count,
+                report.survival_rate * 100.0,
+                extinct
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64, feed_every: Option<u64>) -> SimulationConfig {
+        SimulationConfig {
+            ticks: 3000,
+            seed,
+            tank_path: None,
+            feed_every,
+        }
+    }
+
+    #[test]
+    fn a_seed_gives_the_same_report() {
+        let report = |seed| serde_json::to_string(&run(&config(seed, Some(150))).unwrap()).unwrap();
+        assert_eq!(report(5), report(5));
+        assert_eq!(report(11), report(11));
+    }
+
+    #[test]
+    fn feeding_keeps_fish_from_starving() {
+        let starved = run(&config(5, None)).unwrap();
+        assert_eq!(starved.deaths.starvation, starved.initial_population);
+        assert_eq!(starved.final_population, 0);
+        assert!(starved.species.iter().filter(|s| s.initial > 0).all(|s| s.extinct_at_tick.is_some()));
+
+        let fed = run(&config(5, Some(100))).unwrap();
+        assert_eq!(fed.feed_every, Some(100));
+        assert_eq!(fed.deaths.starvation, 0);
+        assert!(fed.final_population > 0);
+        assert!(fed.to_table().contains("Feeding: every 100 ticks"));
+    }
+}
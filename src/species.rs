@@ -1,21 +1,33 @@
 use serde::{Deserialize, Serialize};
 
+/// Static stats shared by every fish of a species. How it looks is in
+/// [`sprite`](crate::sprite).
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Species {
     pub name: String,
-    pub sprite_left: String,
-    pub sprite_right: String,
+    /// Hunger gained every ten ticks.
     pub hunger_rate: f32,
     pub base_health: u8,
+    /// Age in ticks at which a fish dies of old age.
     pub lifespan: u64,
+    /// Ticks a fish waits after breeding before it can breed again.
     pub breeding_cooldown: u32,
+    /// Comfortable water temperature, in °C.
+    pub temperature: (f32, f32),
+    /// Comfortable pH.
+    pub ph: (f32, f32),
+    /// Shop price in coins, and the base sale price of a healthy fish.
+    #[serde(default = "default_price")]
+    pub price: u32,
+}
+
+fn default_price() -> u32 {
+    10
 }
 
 impl Species {
     pub fn new(
         name: String,
-        sprite_left: String,
-        sprite_right: String,
         hunger_rate: f32,
         base_health: u8,
         lifespan: u64,
@@ -23,66 +35,81 @@ impl Species {
     ) -> Self {
         Species {
             name,
-            sprite_left,
-            sprite_right,
             hunger_rate,
             base_health,
             lifespan,
             breeding_cooldown,
+            temperature: (20.0, 28.0),
+            ph: (6.5, 8.0),
+            price: default_price(),
         }
     }
+
+    /// Set the water the species is comfortable in.
+    pub fn tolerates(mut self, temperature: (f32, f32), ph: (f32, f32)) -> Self {
+        self.temperature = temperature;
+        self.ph = ph;
+        self
+    }
+
+    pub fn priced(mut self, price: u32) -> Self {
+        self.price = price;
+        self
+    }
 }
 
+/// The built-in species roster, in picker order.
 pub fn get_all_species() -> Vec<Species> {
     vec![
         Species::new(
             "Goldfish".to_string(),
-            "<><".to_string(),
-            "><>".to_string(),
             0.5,
             100,
             10000,
             500,
-        ),
+        )
+        .tolerates((18.0, 24.0), (7.0, 8.0))
+        .priced(5),
         Species::new(
             "Guppy".to_string(),
-            "<o".to_string(),
-            "o>".to_string(),
             2.0,
             60,
             3000,
             200,
-        ),
+        )
+        .tolerates((22.0, 28.0), (7.0, 8.0))
+        .priced(3),
         Species::new(
             "Betta".to_string(),
-            "<)))<".to_string(),
-            ">((()>".to_string(),
             1.0,
             80,
             6000,
             400,
-        ),
+        )
+        .tolerates((24.0, 30.0), (6.0, 7.5))
+        .priced(12),
         Species::new(
             "Tetra".to_string(),
-            "<*".to_string(),
-            "*>".to_string(),
             1.5,
             50,
             5000,
             300,
-        ),
+        )
+        .tolerates((22.0, 27.0), (6.0, 7.5))
+        .priced(4),
         Species::new(
             "Angelfish".to_string(),
-            "<^>".to_string(),
-            "<^>".to_string(),
             0.8,
             90,
             8000,
             450,
-        ),
+        )
+        .tolerates((24.0, 29.0), (6.5, 7.5))
+        .priced(20),
     ]
 }
 
+/// Look up a species by its exact name.
 pub fn get_species(name: &str) -> Option<Species> {
     get_all_species().into_iter().find(|s| s.name == name)
 }
@@ -0,0 +1,288 @@
+//! Animated fish sprites.
+//!
+//! Every species has a [`SpriteSet`]: lists of frames for each
+//! [`SpriteState`], drawn facing right and mirrored for fish swimming left.
+//! A frame is one or more lines of ASCII art plus an optional color mask of
+//! the same shape, in the JSON format of `assets/sprites.json`:
+//!
+//! ```json
+//! {
+//!   "Guppy": {
+//!     "frame_ms": 250,
+//!     "idle": [
+//!       { "art": ["~o>"], "mask": ["Mw "] },
+//!       { "art": ["-o>"], "mask": ["Mw "] }
+//!     ],
+//!     "dead": [{ "art": ["_x>"], "mask": ["KKK"] }]
+//!   }
+//! }
+//! ```
+//!
+//! Spaces in the art are transparent. Mask letters pick a fixed color
+//! (`r g y b m c w k`, uppercase for the bright variant); a space or a
+//! missing mask cell draws in the fish's own species or hunger color.
+//! `eating`, `sick` and `dead` are optional and fall back to `idle`.
+//!
+//! Users can replace or add species with a JSON file of the same shape,
+//! `<config dir>/sprites.json` or the `sprites` config path.
+
+use crate::config;
+use crate::fish::Fish;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Ticks after a meal that a fish shows its eating frames.
+pub const EATING_TICKS: u64 = 10;
+
+const BUILTIN: &str = include_str!("../assets/sprites.json");
+
+/// Which animation a fish is showing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpriteState {
+    Idle,
+    Eating,
+    Sick,
+    Dead,
+}
+
+impl SpriteState {
+    /// The animation for a living fish at `tick`.
+    pub fn of(fish: &Fish, tick: u64) -> SpriteState {
+        if fish.last_meal.is_some_and(|meal| tick.saturating_sub(meal) < EATING_TICKS) {
+            SpriteState::Eating
+        } else if fish.is_sick() {
+            SpriteState::Sick
+        } else {
+            SpriteState::Idle
+        }
+    }
+}
+
+/// One frame of a sprite, facing right.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SpriteFrame {
+    pub art: Vec<String>,
+    #[serde(default)]
+    pub mask: Vec<String>,
+}
+
+/// A visible character of a frame, relative to its top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpriteCell {
+    pub x: u16,
+    pub y: u16,
+    pub symbol: char,
+    /// Mask letter, or `None` to use the fish's own color.
+    pub mask: Option<char>,
+}
+
+impl SpriteFrame {
+    pub fn width(&self) -> u16 {
+        self.art.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16
+    }
+
+    pub fn height(&self) -> u16 {
+        self.art.len() as u16
+    }
+
+    /// Visible characters, mirrored when `flip` is set so the frame faces
+    /// left.
+    pub fn cells(&self, flip: bool) -> impl Iterator<Item = SpriteCell> + '_ {
+        let width = self.width();
+        self.art.iter().enumerate().flat_map(move |(y, line)| {
+            let mask: Vec<char> = self.mask.get(y).map_or_else(Vec::new, |m| m.chars().collect());
+            line.chars().enumerate().filter(|(_, c)| *c != ' ').map(move |(x, symbol)| SpriteCell {
+                x: if flip { width - 1 - x as u16 } else { x as u16 },
+                y: y as u16,
+                symbol: if flip { mirror(symbol) } else { symbol },
+                mask: mask.get(x).copied().filter(|m| *m != ' '),
+            })
+        })
+    }
+}
+
+/// The character that looks like `c` seen in a mirror.
+fn mirror(c: char) -> char {
+    match c {
+        '<' => '>',
+        '>' => '<',
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '/' => '\\',
+        '\\' => '/',
+        'd' => 'b',
+        'b' => 'd',
+        'p' => 'q',
+        'q' => 'p',
+        other => other,
+    }
+}
+
+fn default_frame_ms() -> u64 {
+    500
+}
+
+/// All animations for one species.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SpriteSet {
+    /// How long each frame is shown.
+    #[serde(default = "default_frame_ms")]
+    pub frame_ms: u64,
+    pub idle: Vec<SpriteFrame>,
+    #[serde(default)]
+    pub eating: Vec<SpriteFrame>,
+    #[serde(default)]
+    pub sick: Vec<SpriteFrame>,
+    #[serde(default)]
+    pub dead: Vec<SpriteFrame>,
+}
+
+impl SpriteSet {
+    /// Frames for a state, falling back to `idle`.
+    pub fn frames(&self, state: SpriteState) -> &[SpriteFrame] {
+        let frames = match state {
+            SpriteState::Idle => &self.idle,
+            SpriteState::Eating => &self.eating,
+            SpriteState::Sick => &self.sick,
+            SpriteState::Dead => &self.dead,
+        };
+        if frames.is_empty() { &self.idle } else { frames }
+    }
+
+    /// The frame to show `elapsed_ms` into the animation. `phase` offsets
+    /// the cycle so fish of one species don't all move in step.
+    pub fn frame(&self, state: SpriteState, elapsed_ms: u64, phase: u64) -> &SpriteFrame {
+        let frames = self.frames(state);
+        let step = elapsed_ms / self.frame_ms.max(1) + phase;
+        &frames[step as usize % frames.len()]
+    }
+
+    /// Width and height of the largest idle frame, for hit-testing.
+    pub fn size(&self) -> (u16, u16) {
+        self.idle
+            .iter()
+            .fold((1, 1), |(w, h), frame| (w.max(frame.width()), h.max(frame.height())))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.idle.is_empty() {
+            return Err("needs at least one idle frame".to_string());
+        }
+        let all = [&self.idle, &self.eating, &self.sick, &self.dead];
+        if all.iter().flat_map(|frames| frames.iter()).any(|frame| frame.width() == 0) {
+            return Err("has a frame with no art".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Sprite sets by species name.
+#[derive(Clone, Debug)]
+pub struct Sprites {
+    sets: BTreeMap<String, SpriteSet>,
+}
+
+impl Default for Sprites {
+    fn default() -> Self {
+        Sprites::builtin()
+    }
+}
+
+impl Sprites {
+    /// The sprites shipped in `assets/sprites.json`.
+    pub fn builtin() -> Sprites {
+        let sets = parse(BUILTIN).expect("built-in sprites are valid");
+        Sprites { sets }
+    }
+
+    /// Built-in sprites with species from a user file laid over them. With
+    /// no `path`, `<config dir>/sprites.json` is used if it exists.
+    pub fn load(path: Option<&Path>) -> io::Result<Sprites> {
+        let mut sprites = Sprites::builtin();
+        let path = match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => sprite_file().filter(|path| path.exists()),
+        };
+        if let Some(path) = path {
+            let text = fs::read_to_string(&path)?;
+            let sets = parse(&text).map_err(|message| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message))
+            })?;
+            sprites.sets.extend(sets);
+        }
+        Ok(sprites)
+    }
+
+    pub fn get(&self, species: &str) -> Option<&SpriteSet> {
+        self.sets.get(species)
+    }
+}
+
+/// `<config dir>/sprites.json`.
+pub fn sprite_file() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("sprites.json"))
+}
+
+fn parse(text: &str) -> Result<BTreeMap<String, SpriteSet>, String> {
+    let sets: BTreeMap<String, SpriteSet> = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    for (species, set) in &sets {
+        set.validate().map_err(|message| format!("sprite for {} {}", species, message))?;
+    }
+    Ok(sets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_of_different_sizes_are_valid() {
+        let sets = parse(
+            r#"{"Eel": {
+                "idle": [{"art": ["~~~~>"]}, {"art": [" ~~>", "~~"], "mask": ["Gg"]}],
+                "dead": [{"art": ["x"]}]
+            }}"#,
+        )
+        .unwrap();
+        let eel = &sets["Eel"];
+        assert_eq!(eel.size(), (5, 2));
+        assert_eq!(eel.frames(SpriteState::Dead)[0].width(), 1);
+        assert_eq!(eel.frames(SpriteState::Sick).len(), 2);
+    }
+
+    #[test]
+    fn rejects_sets_without_usable_frames() {
+        let error = |json: &str| parse(json).unwrap_err();
+        assert_eq!(error(r#"{"Eel": {"idle": []}}"#), "sprite for Eel needs at least one idle frame");
+        assert_eq!(
+            error(r#"{"Eel": {"idle": [{"art": ["~>"]}, {"art": []}]}}"#),
+            "sprite for Eel has a frame with no art"
+        );
+        assert_eq!(
+            error(r#"{"Eel": {"idle": [{"art": ["~>"]}], "eating": [{"art": ["", ""]}]}}"#),
+            "sprite for Eel has a frame with no art"
+        );
+        assert!(error(r#"{"Eel": {"idle": [{"art": ["~>"], "colour": ["r"]}]}}"#).contains("unknown field"));
+    }
+
+    #[test]
+    fn cells_skip_spaces_and_mirror_when_flipped() {
+        let frame = SpriteFrame { art: vec!["<o )".to_string()], mask: vec!["r".to_string()] };
+        let cells: Vec<SpriteCell> = frame.cells(false).collect();
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0], SpriteCell { x: 0, y: 0, symbol: '<', mask: Some('r') });
+        assert_eq!(cells[2], SpriteCell { x: 3, y: 0, symbol: ')', mask: None });
+        let flipped: Vec<SpriteCell> = frame.cells(true).collect();
+        assert_eq!(flipped[0], SpriteCell { x: 3, y: 0, symbol: '>', mask: Some('r') });
+        assert_eq!(flipped[2], SpriteCell { x: 0, y: 0, symbol: '(', mask: None });
+    }
+}
@@ -0,0 +1,227 @@
+//! Periodic population statistics, kept with the tank in a bounded history.
+
+use crate::fish::Fish;
+use crate::tank::{DeathCause, TickSummary};
+use crate::water::Water;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Ticks between samples.
+pub const SAMPLE_INTERVAL: u64 = 50;
+/// Samples kept before the oldest are dropped.
+pub const HISTORY_CAPACITY: usize = 500;
+
+/// Births and deaths counted over some span of ticks.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct EventCounts {
+    pub births: u64,
+    pub starvation_deaths: u64,
+    pub old_age_deaths: u64,
+    #[serde(default)]
+    pub poor_water_deaths: u64,
+    #[serde(default)]
+    pub disease_deaths: u64,
+}
+
+impl EventCounts {
+    pub fn record(&mut self, summary: &TickSummary) {
+        self.births += summary.births().count() as u64;
+        for death in summary.deaths() {
+            match death.cause {
+                DeathCause::Starvation => self.starvation_deaths += 1,
+                DeathCause::OldAge => self.old_age_deaths += 1,
+                DeathCause::PoorWater => self.poor_water_deaths += 1,
+                DeathCause::Disease => self.disease_deaths += 1,
+            }
+        }
+    }
+
+    pub fn deaths(&self) -> u64 {
+        self.starvation_deaths + self.old_age_deaths + self.poor_water_deaths + self.disease_deaths
+    }
+
+    fn add(&mut self, other: &EventCounts) {
+        self.births += other.births;
+        self.starvation_deaths += other.starvation_deaths;
+        self.old_age_deaths += other.old_age_deaths;
+        self.poor_water_deaths += other.poor_water_deaths;
+        self.disease_deaths += other.disease_deaths;
+    }
+}
+
+/// The state of the tank at one point in time.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatsSample {
+    pub tick: u64,
+    pub population: BTreeMap<String, u32>,
+    /// Births and deaths since the previous sample.
+    pub events: EventCounts,
+    pub average_hunger: f32,
+    pub average_health: f32,
+    /// `None` in samples from saves made before tanks had water.
+    #[serde(default)]
+    pub water: Option<WaterSample>,
+}
+
+/// Water parameters when a sample was taken.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct WaterSample {
+    pub temperature: f32,
+    pub ph: f32,
+    pub ammonia: f32,
+    pub nitrate: f32,
+}
+
+impl From<&Water> for WaterSample {
+    fn from(water: &Water) -> Self {
+        WaterSample {
+            temperature: water.temperature,
+            ph: water.ph,
+            ammonia: water.ammonia,
+            nitrate: water.nitrate,
+        }
+    }
+}
+
+impl StatsSample {
+    pub fn total_population(&self) -> u32 {
+        self.population.values().sum()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StatsHistory {
+    pub samples: VecDeque<StatsSample>,
+    /// Counts accumulated since the last sample.
+    pending: EventCounts,
+    /// Counts over the whole life of the tank.
+    pub totals: EventCounts,
+}
+
+impl StatsHistory {
+    /// Count a tick's births and deaths and take a sample when one is due.
+    pub fn record(&mut self, tick: u64, fish: &[Fish], water: &Water, summary: &TickSummary) {
+        self.pending.record(summary);
+        self.totals.record(summary);
+        if tick.is_multiple_of(SAMPLE_INTERVAL) {
+            self.sample(tick, fish, water);
+        }
+    }
+
+    fn sample(&mut self, tick: u64, fish: &[Fish], water: &Water) {
+        let mut population = BTreeMap::new();
+        for f in fish {
+            *population.entry(f.species.clone()).or_insert(0) += 1;
+        }
+        let count = fish.len().max(1) as f32;
+        let sample = StatsSample {
+            tick,
+            population,
+            events: std::mem::take(&mut self.pending),
+            average_hunger: fish.iter().map(|f| f.hunger as f32).sum::<f32>() / count,
+            average_health: fish.iter().map(|f| f.health as f32).sum::<f32>() / count,
+            water: Some(water.into()),
+        };
+        if self.samples.len() == HISTORY_CAPACITY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// The most recent `count` samples, oldest first (all when `None`).
+    pub fn window(&self, count: Option<usize>) -> impl Iterator<Item = &StatsSample> {
+        let skip = count.map_or(0, |c| self.samples.len().saturating_sub(c));
+        self.samples.iter().skip(skip)
+    }
+
+    /// Births and deaths summed over a window.
+    pub fn events_in(&self, count: Option<usize>) -> EventCounts {
+        let mut total = EventCounts::default();
+        for sample in self.window(count) {
+            total.add(&sample.events);
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::TankEvent;
+    use crate::tank::Death;
+    use uuid::Uuid;
+
+    fn born(species: &str) -> TankEvent {
+        TankEvent::Born { id: Uuid::new_v4(), name: "Fry".to_string(), species: species.to_string() }
+    }
+
+    fn died(cause: DeathCause) -> TankEvent {
+        TankEvent::Died(Death {
+            id: Uuid::new_v4(),
+            name: "Old".to_string(),
+            favorite: false,
+            species: "Guppy".to_string(),
+            cause,
+        })
+    }
+
+    fn summary(events: Vec<TankEvent>) -> TickSummary {
+        TickSummary { events }
+    }
+
+    #[test]
+    fn samples_every_interval() {
+        let mut history = StatsHistory::default();
+        let fish = vec![Fish::new("Guppy".to_string(), (1, 1)), Fish::new("Neon Tetra".to_string(), (2, 2))];
+        let water = Water::default();
+        for tick in 1..=SAMPLE_INTERVAL * 3 {
+            history.record(tick, &fish, &water, &TickSummary::default());
+        }
+        let ticks: Vec<u64> = history.samples.iter().map(|s| s.tick).collect();
+        assert_eq!(ticks, vec![SAMPLE_INTERVAL, SAMPLE_INTERVAL * 2, SAMPLE_INTERVAL * 3]);
+        let sample = &history.samples[0];
+        assert_eq!(sample.total_population(), 2);
+        assert_eq!(sample.population["Guppy"], 1);
+        assert!(sample.water.is_some());
+    }
+
+    #[test]
+    fn drops_the_oldest_sample_at_capacity() {
+        let mut history = StatsHistory::default();
+        let water = Water::default();
+        let samples = HISTORY_CAPACITY as u64 + 3;
+        for n in 1..=samples {
+            history.record(n * SAMPLE_INTERVAL, &[], &water, &TickSummary::default());
+        }
+        assert_eq!(history.samples.len(), HISTORY_CAPACITY);
+        assert_eq!(history.samples.front().unwrap().tick, 4 * SAMPLE_INTERVAL);
+        assert_eq!(history.samples.back().unwrap().tick, samples * SAMPLE_INTERVAL);
+        let window: Vec<u64> = history.window(Some(2)).map(|s| s.tick).collect();
+        assert_eq!(window, vec![(samples - 1) * SAMPLE_INTERVAL, samples * SAMPLE_INTERVAL]);
+    }
+
+    #[test]
+    fn counts_events_per_sample_and_in_total() {
+        let mut history = StatsHistory::default();
+        let water = Water::default();
+        history.record(1, &[], &water, &summary(vec![born("Guppy"), born("Guppy"), died(DeathCause::Starvation)]));
+        history.record(SAMPLE_INTERVAL, &[], &water, &summary(vec![died(DeathCause::OldAge)]));
+        history.record(SAMPLE_INTERVAL + 1, &[], &water, &summary(vec![died(DeathCause::PoorWater)]));
+        history.record(SAMPLE_INTERVAL * 2, &[], &water, &summary(vec![born("Guppy"), died(DeathCause::Disease)]));
+
+        let first = &history.samples[0].events;
+        assert_eq!((first.births, first.deaths()), (2, 2));
+        let second = &history.samples[1].events;
+        assert_eq!((second.births, second.poor_water_deaths, second.disease_deaths), (1, 1, 1));
+
+        let totals = &history.totals;
+        assert_eq!(totals.births, 3);
+        assert_eq!(totals.starvation_deaths, 1);
+        assert_eq!(totals.old_age_deaths, 1);
+        assert_eq!(totals.poor_water_deaths, 1);
+        assert_eq!(totals.disease_deaths, 1);
+        assert_eq!(totals.deaths(), 4);
+        assert_eq!(history.events_in(None).deaths(), 4);
+        assert_eq!(history.events_in(Some(1)).births, 1);
+    }
+}
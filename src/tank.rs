@@ -1,67 +1,469 @@
-use crate::fish::{Direction, Fish};
+use crate::events::{TankEvent, TimedEvent};
+use crate::fish::{Direction, Fish, Morph, Sex};
+use crate::incidents::Incidents;
+use crate::lineage::{Fate, Lineage};
+use crate::maintenance::MaintenanceLog;
+use crate::names;
+use crate::rng::SimRng;
 use crate::species;
+use crate::stats::StatsHistory;
+use crate::water::{Equipment, Water};
 use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver, Sender};
 use uuid::Uuid;
 
+/// A tank of fish and the simulation that runs it.
+///
+/// Everything is advanced by [`Tank::tick`]; the UI and the headless
+/// simulator only differ in how often they call it.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Tank {
+    /// Shown on the tank's tab, e.g. "Display" or "Quarantine".
+    #[serde(default = "default_name")]
+    pub name: String,
     pub fish: Vec<Fish>,
+    /// Display speed multiplier, one of the presets cycled by
+    /// [`Tank::increase_speed`] and [`Tank::decrease_speed`].
     pub simulation_speed: f32,
     pub tick_count: u64,
+    /// Width and height of the water, in cells. Fixed for the life of the
+    /// tank; the TUI shows as much of it as fits and pans over the rest.
     pub dimensions: (u16, u16),
+    /// Randomness used by the simulation; seed it for reproducible runs.
+    #[serde(default)]
+    pub rng: SimRng,
+    /// Food dropped at a spot, sinking until a fish eats it or it dissolves.
+    #[serde(default)]
+    pub food: Vec<FoodPellet>,
+    /// Fish that just died, floating up before they are cleared away.
+    #[serde(default)]
+    pub remains: Vec<Remains>,
+    #[serde(default)]
+    pub water: Water,
+    #[serde(default)]
+    pub equipment: Equipment,
+    /// Ornaments on the tank floor.
+    #[serde(default)]
+    pub decorations: Vec<Decoration>,
+    /// Water changes and other care, for the maintenance screen.
+    #[serde(default)]
+    pub maintenance: MaintenanceLog,
+    /// Random incident settings and whatever is under way.
+    #[serde(default)]
+    pub incidents: Incidents,
+    /// Sampled population history for the stats screen.
+    #[serde(default)]
+    pub stats: StatsHistory,
+    /// Every fish that has lived here, for family trees.
+    #[serde(default)]
+    pub lineage: Lineage,
+    /// Tick the tank last went from empty to holding fish; `None` while
+    /// it is empty.
+    #[serde(default)]
+    pub stocked_since: Option<u64>,
+    /// Channels registered with [`Tank::subscribe`].
+    #[serde(skip)]
+    subscribers: Subscribers,
+}
+
+/// Event channels of one tank. A cloned tank starts without any, so
+/// subscribers only hear from the tank they subscribed to.
+#[derive(Debug, Default)]
+struct Subscribers(Vec<Sender<TimedEvent>>);
+
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Subscribers::default()
+    }
+}
+
+fn default_name() -> String {
+    "Display".to_string()
+}
+
+/// Speed multipliers the player can step through.
+pub const SPEED_PRESETS: [f32; 5] = [0.5, 1.0, 2.0, 5.0, 10.0];
+
+/// Hunger removed by eating one pellet.
+const PELLET_NUTRITION: u8 = 40;
+/// Ticks a pellet lasts on the bottom before it dissolves.
+const PELLET_LIFETIME: u32 = 300;
+/// Fish at or above this hunger swim towards dropped food.
+const FORAGE_HUNGER: u8 = 20;
+/// Ticks a dead fish stays in view.
+const REMAINS_LIFETIME: u32 = 60;
+/// Room kept free at the right and bottom edges, since sprites are drawn
+/// from their top-left corner.
+const SPRITE_MARGIN: (u16, u16) = (8, 3);
+/// Ticks between checks of how the water is treating each fish.
+const WATER_CHECK_INTERVAL: u64 = 10;
+/// Ticks for a fish to lose one point of stress.
+const STRESS_RECOVERY_TICKS: u64 = 5;
+/// Stress at which a fish starts losing health.
+pub const STRESS_HARMFUL: u8 = 50;
+/// World size for new tanks.
+pub const DEFAULT_DIMENSIONS: (u16, u16) = (120, 36);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FoodPellet {
+    pub position: (u16, u16),
+    pub age: u32,
+}
+
+/// Why a fish was removed by [`Tank::tick`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeathCause {
+    Starvation,
+    OldAge,
+    /// Worn down by bad water or the stress of a move.
+    PoorWater,
+    /// Worn down by the stress of a disease outbreak.
+    Disease,
+}
+
+impl DeathCause {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeathCause::Starvation => "starvation",
+            DeathCause::OldAge => "old age",
+            DeathCause::PoorWater => "poor water",
+            DeathCause::Disease => "disease",
+        }
+    }
+}
+
+/// A dead fish, drawn with its species' dead sprite.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Remains {
+    pub species: String,
+    pub position: (u16, u16),
+    pub direction: Direction,
+    pub age: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecorationKind {
+    Plant,
+    Rock,
+    Castle,
+}
+
+impl DecorationKind {
+    pub const ALL: [DecorationKind; 3] = [DecorationKind::Plant, DecorationKind::Rock, DecorationKind::Castle];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DecorationKind::Plant => "Plant",
+            DecorationKind::Rock => "Rock",
+            DecorationKind::Castle => "Castle",
+        }
+    }
+
+    /// Lines of art, top first, standing on the tank floor.
+    pub fn art(&self) -> &'static [&'static str] {
+        match self {
+            DecorationKind::Plant => &[" ) ", "( (", " ) ", "(|("],
+            DecorationKind::Rock => &[" __ ", "(__)"],
+            DecorationKind::Castle => &["|_|_|", "| ∩ |", "|_|_|"],
+        }
+    }
+}
+
+/// An ornament standing on the floor with its left edge at column `x`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Decoration {
+    pub kind: DecorationKind,
+    pub x: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Death {
+    pub id: Uuid,
+    pub name: String,
+    pub favorite: bool,
+    pub species: String,
+    pub cause: DeathCause,
+}
+
+/// What happened during a single `Tank::tick`, in order.
+#[derive(Clone, Debug, Default)]
+pub struct TickSummary {
+    pub events: Vec<TankEvent>,
+}
+
+impl TickSummary {
+    /// Species of each fish born this tick.
+    pub fn births(&self) -> impl Iterator<Item = &str> {
+        self.events.iter().filter_map(|e| match e {
+            TankEvent::Born { species, .. } => Some(species.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn deaths(&self) -> impl Iterator<Item = &Death> {
+        self.events.iter().filter_map(|e| match e {
+            TankEvent::Died(death) => Some(death),
+            _ => None,
+        })
+    }
 }
 
 impl Tank {
+    /// Empty tank seeded from the system clock.
     pub fn new(width: u16, height: u16) -> Self {
         Tank {
+            name: default_name(),
             fish: Vec::new(),
             simulation_speed: 1.0,
             tick_count: 0,
             dimensions: (width, height),
+            rng: SimRng::from_time(),
+            food: Vec::new(),
+            remains: Vec::new(),
+            water: Water::default(),
+            equipment: Equipment::default(),
+            decorations: Vec::new(),
+            maintenance: MaintenanceLog::default(),
+            incidents: Incidents::default(),
+            stats: StatsHistory::default(),
+            lineage: Lineage::default(),
+            stocked_since: None,
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    /// Empty tank with a fixed RNG seed.
+    pub fn with_seed(width: u16, height: u16, seed: u64) -> Self {
+        Tank {
+            rng: SimRng::new(seed),
+            ..Tank::new(width, height)
+        }
+    }
+
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_equipment(mut self, equipment: Equipment) -> Self {
+        self.equipment = equipment;
+        self
+    }
+
+    /// Fresh tank with the three starter fish a new game begins with.
+    pub fn stocked(mut self) -> Self {
+        for (species, position) in [("Goldfish", (10, 5)), ("Guppy", (30, 10)), ("Betta", (50, 8))] {
+            let mut fish = Fish::new(species.to_string(), position);
+            fish.sex = Some(Sex::from_rng(&mut self.rng));
+            self.add_fish(fish);
         }
+        self
+    }
+
+    /// Receive every event [`Tank::tick`] emits from now on. The channel is
+    /// dropped from the tank once the receiver is.
+    pub fn subscribe(&mut self) -> Receiver<TimedEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.0.push(sender);
+        receiver
     }
 
-    pub fn add_fish(&mut self, fish: Fish) {
+    /// Put a fish in the tank, giving it a sex and a name if it doesn't have
+    /// them.
+    pub fn add_fish(&mut self, mut fish: Fish) {
+        if fish.sex.is_none() {
+            fish.sex = Some(Sex::from_rng(&mut self.rng));
+        }
+        if fish.name.is_none() {
+            fish.name = Some(self.generate_name(&fish.species));
+        }
+        // Fish bred here have their parents in the tank; anything else came
+        // in from outside
+        if !fish.parents.iter().any(|id| self.get_fish(*id).is_some()) {
+            self.incidents.arrived(fish.id, self.tick_count);
+        }
+        self.lineage.record(&fish, self.tick_count);
         self.fish.push(fish);
     }
 
+    /// Give fish from saves made before fish had a sex one from the tank RNG.
+    pub fn assign_sexes(&mut self) {
+        for fish in &mut self.fish {
+            if fish.sex.is_none() {
+                fish.sex = Some(Sex::from_rng(&mut self.rng));
+            }
+        }
+    }
+
+    /// Record fish from saves made before the lineage archive existed.
+    pub fn sync_lineage(&mut self) {
+        for fish in &self.fish {
+            if self.lineage.get(fish.id).is_none() {
+                self.lineage.record(fish, self.tick_count);
+            }
+        }
+    }
+
+    /// An unused name from the species name list.
+    pub fn generate_name(&mut self, species: &str) -> String {
+        let taken: Vec<&str> = self.fish.iter().filter_map(|f| f.name.as_deref()).collect();
+        names::generate_name(species, &taken, &mut self.rng)
+    }
+
+    /// Rename a fish; an empty name clears it back to the default label.
+    pub fn rename_fish(&mut self, id: Uuid, name: &str) {
+        if let Some(fish) = self.fish.iter_mut().find(|f| f.id == id) {
+            let name = name.trim();
+            fish.name = (!name.is_empty()).then(|| name.to_string());
+            self.lineage.record(fish, self.tick_count);
+        }
+    }
+
+    pub fn toggle_favorite(&mut self, id: Uuid) {
+        if let Some(fish) = self.fish.iter_mut().find(|f| f.id == id) {
+            fish.favorite = !fish.favorite;
+        }
+    }
+
+    /// Where the next fish added by the player appears, staggered so new
+    /// arrivals don't stack on top of each other.
+    pub fn spawn_position(&self) -> (u16, u16) {
+        let count = self.fish.len() as u16;
+        (10 + (count * 3) % 60, 5 + count % 10)
+    }
+
     pub fn remove_fish(&mut self, id: Uuid) {
-        self.fish.retain(|f| f.id != id);
+        if self.get_fish(id).is_some() {
+            self.fish.retain(|f| f.id != id);
+            self.lineage.set_fate(id, Fate::Removed { tick: self.tick_count });
+        }
+    }
+
+    /// Take a fish out for sale, recording the price in its family tree.
+    pub fn sell_fish(&mut self, id: Uuid, price: u32) -> Option<Fish> {
+        let index = self.fish.iter().position(|f| f.id == id)?;
+        self.lineage.set_fate(id, Fate::Sold { tick: self.tick_count, price });
+        Some(self.fish.remove(index))
+    }
+
+    /// Stand an ornament on the floor at a random column.
+    pub fn decorate(&mut self, kind: DecorationKind) {
+        let width = kind.art().iter().map(|line| line.chars().count()).max().unwrap_or(1) as u16;
+        let x = self.rng.below(self.dimensions.0.saturating_sub(width).max(1) as u64) as u16;
+        self.decorations.push(Decoration { kind, x });
     }
 
     pub fn get_fish(&self, id: Uuid) -> Option<&Fish> {
         self.fish.iter().find(|f| f.id == id)
     }
 
+    /// Reset hunger for every fish in the tank.
     pub fn feed(&mut self) {
         for fish in &mut self.fish {
             fish.hunger = 0;
+            fish.last_meal = Some(self.tick_count);
+        }
+    }
+
+    /// Reset hunger for a single fish.
+    pub fn feed_fish(&mut self, id: Uuid) {
+        if let Some(fish) = self.fish.iter_mut().find(|f| f.id == id) {
+            fish.hunger = 0;
+            fish.last_meal = Some(self.tick_count);
+        }
+    }
+
+    /// Fish in the tank that have `id` as a parent.
+    pub fn offspring_of(&self, id: Uuid) -> impl Iterator<Item = &Fish> {
+        self.fish.iter().filter(move |f| f.parents.contains(&id))
+    }
+
+    /// Drop a food pellet at a spot; it sinks and the first fish to reach
+    /// it eats it.
+    pub fn drop_food(&mut self, position: (u16, u16)) {
+        self.food.push(FoodPellet { position, age: 0 });
+    }
+
+    /// Move a fish to a spot, kept inside the swimmable area.
+    pub fn move_fish(&mut self, id: Uuid, position: (u16, u16)) {
+        let position = self.clamp_position(position);
+        if let Some(fish) = self.fish.iter_mut().find(|f| f.id == id) {
+            fish.position = position;
         }
     }
 
+    /// The nearest spot to `position` where a fish fits in the water.
+    pub fn clamp_position(&self, position: (u16, u16)) -> (u16, u16) {
+        (position.0.min(self.max_x()), position.1.min(self.floor()))
+    }
+
+    /// Rightmost column a fish's left edge can reach.
+    fn max_x(&self) -> u16 {
+        self.dimensions.0.saturating_sub(SPRITE_MARGIN.0)
+    }
+
+    /// Lowest row a fish or pellet can reach.
+    fn floor(&self) -> u16 {
+        self.dimensions.1.saturating_sub(SPRITE_MARGIN.1)
+    }
+
     pub fn increase_speed(&mut self) {
-        const SPEEDS: [f32; 5] = [0.5, 1.0, 2.0, 5.0, 10.0];
-        if let Some(idx) = SPEEDS.iter().position(|&s| (s - self.simulation_speed).abs() < 0.01) {
-            if idx < SPEEDS.len() - 1 {
-                self.simulation_speed = SPEEDS[idx + 1];
-            }
+        if let Some(idx) = SPEED_PRESETS.iter().position(|&s| (s - self.simulation_speed).abs() < 0.01)
+            && idx < SPEED_PRESETS.len() - 1
+        {
+            self.simulation_speed = SPEED_PRESETS[idx + 1];
         }
     }
 
     pub fn decrease_speed(&mut self) {
-        const SPEEDS: [f32; 5] = [0.5, 1.0, 2.0, 5.0, 10.0];
-        if let Some(idx) = SPEEDS.iter().position(|&s| (s - self.simulation_speed).abs() < 0.01) {
-            if idx > 0 {
-                self.simulation_speed = SPEEDS[idx - 1];
-            }
+        if let Some(idx) = SPEED_PRESETS.iter().position(|&s| (s - self.simulation_speed).abs() < 0.01)
+            && idx > 0
+        {
+            self.simulation_speed = SPEED_PRESETS[idx - 1];
         }
     }
 
-    pub fn tick(&mut self) {
+    /// Set the speed to the preset closest to `speed`.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.simulation_speed = SPEED_PRESETS
+            .iter()
+            .copied()
+            .min_by(|a, b| (a - speed).abs().total_cmp(&(b - speed).abs()))
+            .unwrap_or(1.0);
+    }
+
+    /// Advance the simulation one step: move fish, apply hunger, starvation
+    /// and ageing, breed eligible pairs and remove the dead. Returns what
+    /// happened, which is also sent to subscribers.
+    pub fn tick(&mut self) -> TickSummary {
         self.tick_count += 1;
+        let mut summary = TickSummary::default();
+        let (max_x, floor) = (self.max_x(), self.floor());
 
         // Update each fish
         for fish in &mut self.fish {
+            // Hungry fish turn towards the nearest pellet
+            let target = if fish.hunger >= FORAGE_HUNGER {
+                self.food
+                    .iter()
+                    .min_by_key(|p| p.position.0.abs_diff(fish.position.0) + p.position.1.abs_diff(fish.position.1))
+                    .map(|p| p.position)
+            } else {
+                None
+            };
+            if let Some((food_x, food_y)) = target {
+                if food_x < fish.position.0 {
+                    fish.direction = Direction::Left;
+                } else if food_x > fish.position.0 {
+                    fish.direction = Direction::Right;
+                }
+                if food_y < fish.position.1 {
+                    fish.position.1 -= 1;
+                } else if food_y > fish.position.1 && fish.position.1 < floor {
+                    fish.position.1 += 1;
+                }
+            }
+
             // Horizontal movement
             match fish.direction {
                 Direction::Left => {
@@ -72,8 +474,7 @@ impl Tank {
                     }
                 }
                 Direction::Right => {
-                    // Leave some margin for fish sprite width (approximate 5 chars)
-                    if fish.position.0 < self.dimensions.0.saturating_sub(8) {
+                    if fish.position.0 < max_x {
                         fish.position.0 = fish.position.0.saturating_add(1);
                     } else {
                         fish.direction = Direction::Left;
@@ -81,20 +482,30 @@ impl Tank {
                 }
             }
 
-            // Vertical drift (30% chance to drift up or down)
-            if self.tick_count % 3 == 0 {
-                let drift = (self.tick_count % 7) as i32 - 3;
+            // Vertical drift every third tick, rolled per fish
+            if target.is_none() && self.tick_count.is_multiple_of(3) {
+                let drift = self.rng.below(3) as i32 - 1;
                 if drift < 0 && fish.position.1 > 0 {
                     fish.position.1 = fish.position.1.saturating_sub(1);
-                } else if drift > 0 && fish.position.1 < self.dimensions.1.saturating_sub(5) {
+                } else if drift > 0 && fish.position.1 < floor {
                     fish.position.1 = fish.position.1.saturating_add(1);
                 }
             }
 
-            // Hunger increase
+            // Hunger rises by `hunger_rate / 10` per tick; whole points are
+            // added as the fish's age crosses them so the fraction isn't lost
             if let Some(species_data) = species::get_species(&fish.species) {
-                let hunger_increase = species_data.hunger_rate * 0.1;
-                fish.hunger = ((fish.hunger as f32 + hunger_increase).min(100.0)) as u8;
+                let rate = species_data.hunger_rate as f64 * 0.1;
+                let gained = ((fish.age + 1) as f64 * rate) as u64 - (fish.age as f64 * rate) as u64;
+                let was_starving = fish.hunger >= 100;
+                fish.hunger = (fish.hunger as u64 + gained).min(100) as u8;
+                if fish.hunger >= 100 && !was_starving {
+                    summary.events.push(TankEvent::Starving {
+                        id: fish.id,
+                        name: fish.display_name(),
+                        favorite: fish.favorite,
+                    });
+                }
             }
 
             // Health drain if starving
@@ -111,6 +522,13 @@ impl Tank {
             }
         }
 
+        summary
+            .events
+            .extend(self.incidents.update(self.tick_count, &mut self.fish, &mut self.rng));
+        let dissolved = self.update_food();
+        self.update_water(dissolved, &mut summary);
+        self.update_remains();
+
         // Check for breeding pairs
         let mut new_fish = Vec::new();
         let mut bred_indices = std::collections::HashSet::new();
@@ -130,19 +548,25 @@ impl Tank {
 
                 // Check breeding conditions
                 if fish_a.species == fish_b.species
-                    && fish_a.health > 50
-                    && fish_b.health > 50
-                    && fish_a.hunger < 50
-                    && fish_b.hunger < 50
-                    && fish_a.breeding_cooldown == 0
-                    && fish_b.breeding_cooldown == 0
+                    && fish_a.sex != fish_b.sex
+                    && fish_a.ready_to_breed()
+                    && fish_b.ready_to_breed()
                 {
                     // Spawn offspring near first parent
                     let position = (
-                        fish_a.position.0.saturating_add(5).min(self.dimensions.0.saturating_sub(10)),
-                        fish_a.position.1.saturating_add(2).min(self.dimensions.1.saturating_sub(5)),
+                        fish_a.position.0.saturating_add(5).min(max_x),
+                        fish_a.position.1.saturating_add(2).min(floor),
                     );
-                    let baby = Fish::new(fish_a.species.clone(), position);
+                    let mut baby = Fish::new(fish_a.species.clone(), position);
+                    baby.sex = Some(Sex::from_rng(&mut self.rng));
+                    baby.parents = vec![fish_a.id, fish_b.id];
+                    baby.generation = fish_a.generation.max(fish_b.generation) + 1;
+                    baby.morph = Morph::inherit(fish_a.morph, fish_b.morph, &mut self.rng);
+                    summary.events.push(TankEvent::Bred {
+                        parents: [fish_a.id, fish_b.id],
+                        names: [fish_a.display_name(), fish_b.display_name()],
+                        species: fish_a.species.clone(),
+                    });
                     new_fish.push(baby);
 
                     // Mark for cooldown application
@@ -155,23 +579,238 @@ impl Tank {
 
         // Apply cooldowns to bred fish
         for &idx in &bred_indices {
-            if let Some(fish) = self.fish.get_mut(idx) {
-                if let Some(species_data) = species::get_species(&fish.species) {
-                    fish.breeding_cooldown = species_data.breeding_cooldown;
-                }
+            if let Some(fish) = self.fish.get_mut(idx)
+                && let Some(species_data) = species::get_species(&fish.species)
+            {
+                fish.breeding_cooldown = species_data.breeding_cooldown;
             }
         }
 
         // Add new fish to tank
-        self.fish.extend(new_fish);
+        for baby in new_fish {
+            let (id, species) = (baby.id, baby.species.clone());
+            self.add_fish(baby);
+            let name = self.get_fish(id).map(Fish::display_name).unwrap_or_default();
+            summary.events.push(TankEvent::Born { id, name, species });
+        }
 
         // Remove dead fish (health = 0 or age >= lifespan)
         let all_species = species::get_all_species();
         self.fish.retain(|fish| {
             let species_data = all_species.iter().find(|s| s.name == fish.species);
             let alive_by_health = fish.health > 0;
-            let alive_by_age = species_data.map_or(true, |s| fish.age < s.lifespan);
-            alive_by_health && alive_by_age
+            let alive_by_age = species_data.is_none_or(|s| fish.age < s.lifespan);
+            let cause = if !alive_by_health && fish.hunger >= 100 {
+                Some(DeathCause::Starvation)
+            } else if !alive_by_health && self.incidents.is_infected(fish.id) {
+                Some(DeathCause::Disease)
+            } else if !alive_by_health {
+                Some(DeathCause::PoorWater)
+            } else if !alive_by_age {
+                Some(DeathCause::OldAge)
+            } else {
+                None
+            };
+            if let Some(cause) = cause {
+                self.lineage.set_fate(fish.id, Fate::Died { tick: self.tick_count, cause });
+                self.remains.push(Remains {
+                    species: fish.species.clone(),
+                    position: fish.position,
+                    direction: fish.direction.clone(),
+                    age: 0,
+                });
+                summary.events.push(TankEvent::Died(Death {
+                    id: fish.id,
+                    name: fish.display_name(),
+                    favorite: fish.favorite,
+                    species: fish.species.clone(),
+                    cause,
+                }));
+            }
+            cause.is_none()
         });
+        if self.fish.is_empty() {
+            self.stocked_since = None;
+        } else if self.stocked_since.is_none() {
+            self.stocked_since = Some(self.tick_count);
+        }
+
+        self.stats.record(self.tick_count, &self.fish, &self.water, &summary);
+        self.publish(&summary);
+
+        summary
+    }
+
+    /// Send a tick's events to every live subscriber.
+    fn publish(&mut self, summary: &TickSummary) {
+        if self.subscribers.0.is_empty() {
+            return;
+        }
+        let tick = self.tick_count;
+        let tank = &self.name;
+        self.subscribers.0.retain(|sender| {
+            summary.events.iter().all(|event| {
+                sender
+                    .send(TimedEvent {
+                        tick,
+                        tank: tank.clone(),
+                        event: event.clone(),
+                    })
+                    .is_ok()
+            })
+        });
+    }
+
+    /// Sink pellets, let fish that touch one eat it, and dissolve old ones.
+    /// Returns how many dissolved.
+    fn update_food(&mut self) -> usize {
+        let floor = self.floor();
+        let mut dissolved = 0;
+        let sinking = self.tick_count.is_multiple_of(2);
+        let tick = self.tick_count;
+        let fish = &mut self.fish;
+        self.food.retain_mut(|pellet| {
+            if pellet.position.1 < floor {
+                if sinking {
+                    pellet.position.1 += 1;
+                }
+            } else {
+                pellet.age += 1;
+            }
+
+            // A fish eats when its sprite covers the pellet
+            let eater = fish.iter_mut().find(|f| {
+                f.hunger > 0
+                    && f.position.1.abs_diff(pellet.position.1) <= 1
+                    && pellet.position.0 + 1 >= f.position.0
+                    && pellet.position.0 <= f.position.0 + 5
+            });
+            if let Some(eater) = eater {
+                eater.hunger = eater.hunger.saturating_sub(PELLET_NUTRITION);
+                eater.last_meal = Some(tick);
+                return false;
+            }
+            if pellet.age >= PELLET_LIFETIME {
+                dissolved += 1;
+                return false;
+            }
+            true
+        });
+        dissolved
+    }
+
+    /// Age the water, report new problems, and let bad water and stress
+    /// wear fish down.
+    fn update_water(&mut self, dissolved: usize, summary: &mut TickSummary) {
+        let equipment = self.incidents.equipment(&self.equipment);
+        let room = self.incidents.room_temperature();
+        let load = self.fish.len() + self.incidents.waste();
+        for issue in self.water.update(&equipment, room, load, dissolved) {
+            summary.events.push(TankEvent::WaterAlert {
+                message: self.water.describe(issue),
+            });
+        }
+        let recovering = self.tick_count.is_multiple_of(STRESS_RECOVERY_TICKS);
+        let checking = self.tick_count.is_multiple_of(WATER_CHECK_INTERVAL);
+        for fish in &mut self.fish {
+            if recovering {
+                fish.stress = fish.stress.saturating_sub(1);
+            }
+            if checking && let Some(species_data) = species::get_species(&fish.species) {
+                let mut harm = self.water.harm(&species_data);
+                if fish.stress >= STRESS_HARMFUL {
+                    harm += 1;
+                }
+                fish.health = fish.health.saturating_sub(harm);
+            }
+        }
+    }
+
+    /// Float dead fish to the surface and clear them after a while.
+    fn update_remains(&mut self) {
+        let rising = self.tick_count.is_multiple_of(3);
+        self.remains.retain_mut(|remains| {
+            if rising {
+                remains.position.1 = remains.position.1.saturating_sub(1);
+            }
+            remains.age += 1;
+            remains.age < REMAINS_LIFETIME
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::incidents::{Incident, IncidentKind};
+
+    /// A tank whose only fish is about to die, sick or not.
+    fn dying_fish(sick: bool) -> (Tank, Uuid) {
+        let mut tank = Tank::with_seed(60, 20, 1);
+        let mut fish = Fish::new("Guppy".to_string(), (5, 5));
+        fish.health = 0;
+        let id = fish.id;
+        tank.add_fish(fish);
+        if sick {
+            tank.incidents.active.push(Incident {
+                kind: IncidentKind::Disease,
+                started: 0,
+                ends: 1000,
+                snails: 0,
+                infected: vec![id],
+            });
+        }
+        (tank, id)
+    }
+
+    #[test]
+    fn sick_fish_die_of_disease() {
+        let (mut tank, id) = dying_fish(true);
+        let summary = tank.tick();
+        let death = summary.deaths().next().unwrap();
+        assert_eq!((death.id, death.cause), (id, DeathCause::Disease));
+        assert_eq!(tank.stats.totals.disease_deaths, 1);
+        assert_eq!(tank.stats.totals.poor_water_deaths, 0);
+
+        let (mut tank, _) = dying_fish(false);
+        assert_eq!(tank.tick().deaths().next().unwrap().cause, DeathCause::PoorWater);
+    }
+
+    #[test]
+    fn clones_drop_subscribers() {
+        let mut tank = Tank::with_seed(60, 20, 1);
+        let _receiver = tank.subscribe();
+        let copy = tank.clone();
+        assert_eq!(tank.subscribers.0.len(), 1);
+        assert!(copy.subscribers.0.is_empty());
+    }
+
+    #[test]
+    fn added_fish_get_a_seeded_sex() {
+        let sexes = |seed| {
+            let mut tank = Tank::with_seed(60, 20, seed);
+            for _ in 0..16 {
+                tank.add_fish(Fish::new("Guppy".to_string(), (5, 5)));
+            }
+            tank.fish.iter().map(|f| f.sex.unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(sexes(3), sexes(3));
+        assert!(sexes(3).contains(&Sex::Female) && sexes(3).contains(&Sex::Male));
+
+        // A sex that's already set is kept, as when netting between tanks
+        let mut tank = Tank::with_seed(60, 20, 3);
+        let mut fish = Fish::new("Guppy".to_string(), (5, 5));
+        fish.sex = Some(Sex::Male);
+        tank.add_fish(fish);
+        assert_eq!(tank.fish[0].sex, Some(Sex::Male));
+    }
+
+    #[test]
+    fn old_saves_get_sexes_on_load() {
+        let mut tank = Tank::with_seed(60, 20, 3);
+        tank.add_fish(Fish::new("Guppy".to_string(), (5, 5)));
+        tank.fish[0].sex = None;
+        tank.assign_sexes();
+        assert!(tank.fish[0].sex.is_some());
     }
 }
@@ -0,0 +1,602 @@
+//! Color themes and terminal color-depth fallback.
+//!
+//! A [`Theme`] names every color the UI draws with. Built-in presets are
+//! listed in [`PRESETS`]; users can add their own as TOML files in
+//! `<config dir>/themes/<name>.toml`, starting from a preset and overriding
+//! only what they want:
+//!
+//! ```toml
+//! base = "ocean"
+//! water = "#001a33"
+//! warning = "light-yellow"
+//!
+//! [species]
+//! Guppy = "#ff8800"
+//! ```
+//!
+//! Colors are ratatui color strings: names, `#rrggbb`, or a 0-255 palette
+//! index. Before drawing, [`Theme::for_depth`] maps them down to what the
+//! terminal can show.
+
+use crate::config;
+use crate::events::Severity;
+use crate::fish::HungerLevel;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Built-in theme names.
+pub const PRESETS: [&str; 4] = ["default", "ocean", "high-contrast", "colorblind"];
+
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub name: String,
+    /// Tank background; `Reset` leaves the terminal's own background.
+    pub water: Color,
+    pub border: Color,
+    pub header: Color,
+    /// Table headers and other emphasis.
+    pub accent: Color,
+    pub text: Color,
+    /// Secondary text such as timestamps and dead relatives.
+    pub muted: Color,
+    /// Background of popups.
+    pub modal: Color,
+    pub highlight_fg: Color,
+    pub highlight_bg: Color,
+    pub favorite: Color,
+    pub pellet: Color,
+    /// Well-fed fish of each species.
+    pub species: BTreeMap<String, Color>,
+    /// Species missing from `species`.
+    pub fish: Color,
+    pub info: Color,
+    pub good: Color,
+    pub warning: Color,
+    pub bad: Color,
+    /// What the colors were fitted to by [`Theme::for_depth`]; sprite mask
+    /// colors are fitted the same way when drawn.
+    pub depth: ColorDepth,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            name: "default".to_string(),
+            water: Color::Reset,
+            border: Color::Reset,
+            header: Color::Reset,
+            accent: Color::Yellow,
+            text: Color::Reset,
+            muted: Color::DarkGray,
+            modal: Color::Black,
+            highlight_fg: Color::Black,
+            highlight_bg: Color::Cyan,
+            favorite: Color::Magenta,
+            pellet: Color::Yellow,
+            species: species_colors([
+                Color::LightYellow,
+                Color::Cyan,
+                Color::LightMagenta,
+                Color::LightGreen,
+                Color::LightBlue,
+            ]),
+            fish: Color::Cyan,
+            info: Color::Gray,
+            good: Color::Green,
+            warning: Color::Yellow,
+            bad: Color::Red,
+            depth: ColorDepth::TrueColor,
+        }
+    }
+}
+
+/// Pair colors with the built-in species, in picker order.
+fn species_colors(colors: [Color; 5]) -> BTreeMap<String, Color> {
+    ["Goldfish", "Guppy", "Betta", "Tetra", "Angelfish"]
+        .into_iter()
+        .map(String::from)
+        .zip(colors)
+        .collect()
+}
+
+impl Theme {
+    /// A built-in theme by name.
+    pub fn preset(name: &str) -> Option<Theme> {
+        let theme = match name {
+            "default" => Theme::default(),
+            "ocean" => Theme {
+                name: name.to_string(),
+                water: Color::Rgb(0, 30, 60),
+                border: Color::Rgb(80, 160, 200),
+                header: Color::Rgb(170, 220, 255),
+                accent: Color::Rgb(250, 210, 80),
+                text: Color::Rgb(220, 235, 245),
+                muted: Color::Rgb(110, 130, 150),
+                modal: Color::Rgb(10, 20, 35),
+                highlight_fg: Color::Rgb(0, 30, 60),
+                highlight_bg: Color::Rgb(120, 200, 230),
+                favorite: Color::Rgb(255, 120, 200),
+                pellet: Color::Rgb(210, 170, 90),
+                species: species_colors([
+                    Color::Rgb(255, 160, 40),
+                    Color::Rgb(120, 230, 200),
+                    Color::Rgb(200, 100, 255),
+                    Color::Rgb(90, 200, 255),
+                    Color::Rgb(240, 240, 190),
+                ]),
+                fish: Color::Rgb(120, 230, 200),
+                info: Color::Rgb(150, 170, 190),
+                good: Color::Rgb(110, 220, 120),
+                warning: Color::Rgb(250, 210, 80),
+                bad: Color::Rgb(255, 90, 80),
+                depth: ColorDepth::TrueColor,
+            },
+            // Bright on black, no mid-tones
+            "high-contrast" => Theme {
+                name: name.to_string(),
+                water: Color::Black,
+                border: Color::White,
+                header: Color::White,
+                accent: Color::LightYellow,
+                text: Color::White,
+                muted: Color::Gray,
+                modal: Color::Black,
+                highlight_fg: Color::Black,
+                highlight_bg: Color::White,
+                favorite: Color::LightMagenta,
+                pellet: Color::White,
+                species: species_colors([Color::White; 5]),
+                fish: Color::White,
+                info: Color::White,
+                good: Color::LightGreen,
+                warning: Color::LightYellow,
+                bad: Color::LightRed,
+                depth: ColorDepth::TrueColor,
+            },
+            // Okabe-Ito palette, distinguishable with common color vision
+            // deficiencies; status colors avoid red/green pairs
+            "colorblind" => Theme {
+                name: name.to_string(),
+                accent: Color::Rgb(240, 228, 66),
+                muted: Color::Rgb(140, 140, 140),
+                highlight_fg: Color::Black,
+                highlight_bg: Color::Rgb(86, 180, 233),
+                favorite: Color::Rgb(204, 121, 167),
+                pellet: Color::Rgb(240, 228, 66),
+                species: species_colors([
+                    Color::Rgb(230, 159, 0),
+                    Color::Rgb(86, 180, 233),
+                    Color::Rgb(204, 121, 167),
+                    Color::Rgb(0, 158, 115),
+                    Color::Rgb(255, 255, 255),
+                ]),
+                fish: Color::Rgb(86, 180, 233),
+                info: Color::Rgb(200, 200, 200),
+                good: Color::Rgb(0, 114, 178),
+                warning: Color::Rgb(240, 228, 66),
+                bad: Color::Rgb(213, 94, 0),
+                ..Theme::default()
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Load a theme by preset name, by name from the user theme directory,
+    /// or from a `.toml` path.
+    pub fn load(name: &str) -> io::Result<Theme> {
+        if let Some(theme) = Theme::preset(name) {
+            return Ok(theme);
+        }
+        let path = if name.ends_with(".toml") {
+            PathBuf::from(name)
+        } else {
+            theme_dir()
+                .map(|dir| dir.join(format!("{}.toml", name)))
+                .filter(|path| path.exists())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("unknown theme '{}' (built-in: {})", name, PRESETS.join(", ")),
+                    )
+                })?
+        };
+        Theme::from_file(&path)
+    }
+
+    fn from_file(path: &Path) -> io::Result<Theme> {
+        let invalid = |message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message))
+        };
+        let text = fs::read_to_string(path)?;
+        let file: ThemeFile = toml::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+        let base = file.base.as_deref().unwrap_or("default");
+        let mut theme = Theme::preset(base).ok_or_else(|| invalid(format!("unknown base theme '{}'", base)))?;
+        theme.name = path
+            .file_stem()
+            .map_or_else(|| "custom".to_string(), |s| s.to_string_lossy().into_owned());
+        file.apply(&mut theme).map_err(invalid)?;
+        Ok(theme)
+    }
+
+    /// Color for a well-fed fish of a species.
+    pub fn species_color(&self, species: &str) -> Color {
+        self.species.get(species).copied().unwrap_or(self.fish)
+    }
+
+    /// Status color for a hunger level.
+    pub fn hunger_color(&self, hunger: u8) -> Color {
+        match HungerLevel::of(hunger) {
+            HungerLevel::Fed => self.good,
+            HungerLevel::Hungry => self.warning,
+            HungerLevel::Critical => self.bad,
+        }
+    }
+
+    /// Color a fish is drawn in: its species color while fed, then the
+    /// hunger status colors.
+    pub fn fish_color(&self, species: &str, hunger: u8) -> Color {
+        match HungerLevel::of(hunger) {
+            HungerLevel::Fed => self.species_color(species),
+            _ => self.hunger_color(hunger),
+        }
+    }
+
+    /// Color for a sprite mask letter: `r g y b m c w k`, uppercase for the
+    /// bright variant. `None` for anything else.
+    pub fn mask_color(&self, mask: char) -> Option<Color> {
+        let color = match mask {
+            'k' => Color::Black,
+            'K' => Color::DarkGray,
+            'r' => Color::Red,
+            'R' => Color::LightRed,
+            'g' => Color::Green,
+            'G' => Color::LightGreen,
+            'y' => Color::Yellow,
+            'Y' => Color::LightYellow,
+            'b' => Color::Blue,
+            'B' => Color::LightBlue,
+            'm' => Color::Magenta,
+            'M' => Color::LightMagenta,
+            'c' => Color::Cyan,
+            'C' => Color::LightCyan,
+            'w' => Color::Gray,
+            'W' => Color::White,
+            _ => return None,
+        };
+        Some(self.depth.fit(color))
+    }
+
+    pub fn severity_color(&self, severity: Severity) -> Color {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Good => self.good,
+            Severity::Warning => self.warning,
+            Severity::Bad => self.bad,
+        }
+    }
+
+    /// The same theme with every color mapped to the nearest one the
+    /// terminal can show.
+    pub fn for_depth(mut self, depth: ColorDepth) -> Theme {
+        let fit = |color: &mut Color| *color = depth.fit(*color);
+        for color in [
+            &mut self.water,
+            &mut self.border,
+            &mut self.header,
+            &mut self.accent,
+            &mut self.text,
+            &mut self.muted,
+            &mut self.modal,
+            &mut self.highlight_fg,
+            &mut self.highlight_bg,
+            &mut self.favorite,
+            &mut self.pellet,
+            &mut self.fish,
+            &mut self.info,
+            &mut self.good,
+            &mut self.warning,
+            &mut self.bad,
+        ] {
+            fit(color);
+        }
+        self.species.values_mut().for_each(fit);
+        self.depth = depth;
+        self
+    }
+}
+
+/// `<config dir>/themes`.
+pub fn theme_dir() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("themes"))
+}
+
+/// A theme file: a base preset plus optional overrides.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    base: Option<String>,
+    water: Option<String>,
+    border: Option<String>,
+    header: Option<String>,
+    accent: Option<String>,
+    text: Option<String>,
+    muted: Option<String>,
+    modal: Option<String>,
+    highlight_fg: Option<String>,
+    highlight_bg: Option<String>,
+    favorite: Option<String>,
+    pellet: Option<String>,
+    fish: Option<String>,
+    info: Option<String>,
+    good: Option<String>,
+    warning: Option<String>,
+    bad: Option<String>,
+    species: BTreeMap<String, String>,
+}
+
+impl ThemeFile {
+    fn apply(&self, theme: &mut Theme) -> Result<(), String> {
+        for (value, slot) in [
+            (&self.water, &mut theme.water),
+            (&self.border, &mut theme.border),
+            (&self.header, &mut theme.header),
+            (&self.accent, &mut theme.accent),
+            (&self.text, &mut theme.text),
+            (&self.muted, &mut theme.muted),
+            (&self.modal, &mut theme.modal),
+            (&self.highlight_fg, &mut theme.highlight_fg),
+            (&self.highlight_bg, &mut theme.highlight_bg),
+            (&self.favorite, &mut theme.favorite),
+            (&self.pellet, &mut theme.pellet),
+            (&self.fish, &mut theme.fish),
+            (&self.info, &mut theme.info),
+            (&self.good, &mut theme.good),
+            (&self.warning, &mut theme.warning),
+            (&self.bad, &mut theme.bad),
+        ] {
+            if let Some(value) = value {
+                *slot = parse_color(value)?;
+            }
+        }
+        for (species, value) in &self.species {
+            theme.species.insert(species.clone(), parse_color(value)?);
+        }
+        Ok(())
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    Color::from_str(value).map_err(|_| format!("invalid color '{}'", value))
+}
+
+/// How many colors the terminal can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit RGB.
+    TrueColor,
+    /// The xterm 256-color palette.
+    Ansi256,
+    /// The 16 basic ANSI colors.
+    Ansi16,
+    /// No color at all, e.g. with `NO_COLOR` set.
+    Monochrome,
+}
+
+/// The 16 ANSI colors with typical xterm RGB values, for nearest matching.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel levels of the 6x6x6 color cube in the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Guess from `NO_COLOR`, `COLORTERM` and `TERM`.
+    pub fn detect() -> ColorDepth {
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return ColorDepth::Monochrome;
+        }
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default().to_lowercase();
+        let term = std::env::var("TERM").unwrap_or_default().to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" || term.contains("direct") {
+            ColorDepth::TrueColor
+        } else if term.contains("256") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// Parse a `color_depth` config value; `auto` detects.
+    pub fn from_config(value: &str) -> Option<ColorDepth> {
+        match value {
+            "auto" => Some(ColorDepth::detect()),
+            "truecolor" | "24bit" => Some(ColorDepth::TrueColor),
+            "256" => Some(ColorDepth::Ansi256),
+            "16" => Some(ColorDepth::Ansi16),
+            "none" => Some(ColorDepth::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// The closest color available at this depth.
+    pub fn fit(&self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::TrueColor, _) => color,
+            (ColorDepth::Monochrome, _) => Color::Reset,
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256((r, g, b))),
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => nearest_16((r, g, b)),
+            (ColorDepth::Ansi16, Color::Indexed(index)) => nearest_16(indexed_rgb(index)),
+            _ => color,
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+/// Nearest entry in the color cube (16-231) or the gray ramp (232-255).
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(c))
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = 232 + ((average.saturating_sub(8) / 10).min(23)) as u8;
+    if distance(rgb, indexed_rgb(gray)) < distance(rgb, indexed_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Approximate RGB of a 256-color palette entry.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `text` to `<fresh temp dir>/<name>.toml` and return its path.
+    fn theme_file(name: &str, text: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aquarium-sim-test-{}", uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.toml", name));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn colors(theme: &Theme) -> Vec<Color> {
+        let mut colors = vec![theme.water, theme.border, theme.accent, theme.bad, theme.fish];
+        colors.extend(theme.species.values());
+        colors
+    }
+
+    #[test]
+    fn fits_colors_to_each_depth() {
+        let white = Color::Rgb(255, 255, 255);
+        assert_eq!(ColorDepth::TrueColor.fit(white), white);
+        assert_eq!(ColorDepth::Ansi256.fit(white), Color::Indexed(231));
+        assert_eq!(ColorDepth::Ansi256.fit(Color::Rgb(0, 0, 0)), Color::Indexed(16));
+        assert_eq!(ColorDepth::Ansi256.fit(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(ColorDepth::Ansi16.fit(white), Color::White);
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Rgb(255, 90, 80)), Color::LightRed);
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Indexed(21)), Color::Blue);
+        assert_eq!(ColorDepth::Monochrome.fit(white), Color::Reset);
+        // Named colors are already available everywhere but monochrome
+        assert_eq!(ColorDepth::Ansi16.fit(Color::Yellow), Color::Yellow);
+        assert_eq!(ColorDepth::Ansi256.fit(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn for_depth_falls_back_from_truecolor_to_256_to_16() {
+        let ocean = Theme::preset("ocean").unwrap();
+        let truecolor = ocean.clone().for_depth(ColorDepth::TrueColor);
+        assert_eq!(colors(&truecolor), colors(&ocean));
+
+        let palette = ocean.clone().for_depth(ColorDepth::Ansi256);
+        assert_eq!(palette.depth, ColorDepth::Ansi256);
+        assert!(colors(&palette).iter().all(|c| matches!(c, Color::Indexed(_))));
+
+        let basic = palette.for_depth(ColorDepth::Ansi16);
+        assert_eq!(basic.depth, ColorDepth::Ansi16);
+        assert!(colors(&basic).iter().all(|c| ANSI16.iter().any(|(ansi, _)| ansi == c)));
+        assert_eq!(basic.bad, Color::LightRed);
+
+        let mono = ocean.for_depth(ColorDepth::Monochrome);
+        assert!(colors(&mono).iter().all(|c| *c == Color::Reset));
+        assert_eq!(mono.mask_color('R'), Some(Color::Reset));
+    }
+
+    #[test]
+    fn loads_a_user_theme_over_its_base() {
+        let path = theme_file(
+            "reef",
+            "base = \"ocean\"\nwater = \"#001a33\"\nwarning = \"light-yellow\"\n\n[species]\nGuppy = \"#ff8800\"\nZebra = \"208\"\n",
+        );
+        let theme = Theme::load(path.to_str().unwrap()).unwrap();
+        let ocean = Theme::preset("ocean").unwrap();
+        assert_eq!(theme.name, "reef");
+        assert_eq!(theme.water, Color::Rgb(0, 0x1a, 0x33));
+        assert_eq!(theme.warning, Color::LightYellow);
+        assert_eq!(theme.species_color("Guppy"), Color::Rgb(0xff, 0x88, 0));
+        assert_eq!(theme.species_color("Zebra"), Color::Indexed(208));
+        assert_eq!(theme.border, ocean.border);
+        assert_eq!(theme.species_color("Betta"), ocean.species_color("Betta"));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        // Without a base, overrides start from the default theme
+        let path = theme_file("plain", "bad = \"magenta\"\n");
+        let theme = Theme::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(theme.bad, Color::Magenta);
+        assert_eq!(theme.accent, Theme::default().accent);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rejects_bad_theme_files() {
+        for (text, message) in [
+            ("base = \"sunset\"\n", "unknown base theme 'sunset'"),
+            ("water = \"sea green\"\n", "invalid color 'sea green'"),
+            ("sand = \"yellow\"\n", "unknown field"),
+        ] {
+            let path = theme_file("broken", text);
+            let error = Theme::load(path.to_str().unwrap()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            assert!(error.to_string().contains(message), "{}", error);
+            fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        }
+        let error = Theme::load("no-such-theme-anywhere").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(Theme::load("high-contrast").is_ok());
+    }
+}
@@ -1,120 +1,1039 @@
+use crate::achievements::Achievement;
+use crate::app::{App, Screen, STATS_WINDOWS};
 use crate::fish::{Direction, Fish};
+use crate::economy;
+use crate::keymap::{
+    Action, Context, LINEAGE_ACTIONS, LOG_ACTIONS, MAINTENANCE_ACTIONS, MODE_ACTIONS, ROSTER_ACTIONS, SHOP_ACTIONS,
+    STATS_ACTIONS,
+};
+use crate::maintenance::Task;
+use crate::mode::GameMode;
+use crate::scenario::TICKS_PER_DAY;
+use crate::lineage::{Branch, Fate};
+use crate::roster::SortColumn;
 use crate::species;
-use crate::tank::Tank;
+use crate::sprite::{SpriteFrame, SpriteState};
+use crate::tank::Remains;
+use crate::theme::Theme;
+use crate::water;
 use ratatui::{
     layout::{Alignment, Constraint, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType,
+        Paragraph, Row, Sparkline, Table, TableState,
+    },
     Frame,
 };
 
-pub fn render(f: &mut Frame, tank: &Tank, selected_fish_id: Option<uuid::Uuid>, paused: bool, species_picker_open: bool, help_open: bool) {
-    let chunks = Layout::vertical([
-        Constraint::Length(3), // Header
-        Constraint::Min(0),    // Tank area
-        Constraint::Length(3), // Footer
+/// Header, body and footer. The footer gets a second line in accessible
+/// mode for the selected fish's description.
+fn main_layout(area: Rect, accessible: bool) -> std::rc::Rc<[Rect]> {
+    let footer = if accessible { 4 } else { 3 };
+    Layout::vertical([
+        Constraint::Length(3),      // Header
+        Constraint::Min(0),         // Tank area
+        Constraint::Length(footer), // Footer
     ])
-    .split(f.area());
+    .split(area)
+}
+
+/// Width of the inspector side panel.
+const INSPECTOR_WIDTH: u16 = 36;
+
+/// Split the body into the tank and, if open, the inspector panel.
+fn body_layout(area: Rect, inspector_open: bool) -> (Rect, Option<Rect>) {
+    if !inspector_open {
+        return (area, None);
+    }
+    let columns = Layout::horizontal([Constraint::Min(0), Constraint::Length(INSPECTOR_WIDTH)]).split(area);
+    (columns[0], Some(columns[1]))
+}
+
+/// The water inside the tank border, where fish are drawn.
+pub fn tank_inner_area(area: Rect, inspector_open: bool, accessible: bool, world: (u16, u16)) -> Rect {
+    let (tank_area, _) = body_layout(main_layout(area, accessible)[1], inspector_open);
+    tank_block().inner(fit_tank(tank_area, world))
+}
 
-    render_header(f, chunks[0], tank, paused);
-    render_tank(f, chunks[1], tank);
-    render_footer(f, chunks[2], tank, selected_fish_id);
+/// The tank border around a tank of `world` size, shrunk to it when the
+/// space is larger.
+fn fit_tank(area: Rect, world: (u16, u16)) -> Rect {
+    Rect {
+        width: area.width.min(world.0.saturating_add(2)),
+        height: area.height.min(world.1.saturating_add(2)),
+        ..area
+    }
+}
+
+fn tank_block() -> Block<'static> {
+    Block::default().borders(Borders::ALL)
+}
+
+pub fn render(f: &mut Frame, app: &App) {
+    let chunks = main_layout(f.area(), app.accessible);
+    f.render_widget(Block::default().style(Style::default().fg(app.theme.text)), f.area());
 
-    // Render modals on top
-    if species_picker_open {
-        render_species_picker(f);
+    let (tank_area, inspector_area) = body_layout(chunks[1], app.inspector_open);
+
+    render_header(f, chunks[0], app);
+    if app.text_view {
+        render_text_view(f, tank_area, app);
+    } else {
+        let view = fit_tank(tank_area, app.tank().dimensions);
+        render_tank(f, view, app);
+        render_toasts(f, view, app);
     }
+    if let Some(area) = inspector_area {
+        render_inspector(f, area, app);
+    }
+    render_footer(f, chunks[2], app);
 
-    if help_open {
-        render_help(f);
+    // Render modals on top, in stack order
+    for screen in app.screens() {
+        match screen {
+            Screen::Tank => {}
+            Screen::SpeciesPicker => render_species_picker(f, app),
+            Screen::Help => render_help(f, app),
+            Screen::Roster => render_roster(f, app),
+            Screen::Rename => render_rename_prompt(f, app),
+            Screen::Stats => render_stats(f, app),
+            Screen::EventLog => render_event_log(f, app),
+            Screen::Lineage => render_lineage(f, app),
+            Screen::NetPicker => render_net_picker(f, app),
+            Screen::Shop => render_shop(f, app),
+            Screen::ModeSelect => render_mode_select(f, app),
+            Screen::Results => render_results(f, app),
+            Screen::Achievements => render_achievements(f, app),
+            Screen::Maintenance => render_maintenance(f, app),
+        }
     }
 }
 
-fn render_header(f: &mut Frame, area: Rect, tank: &Tank, paused: bool) {
-    let pause_indicator = if paused { " [PAUSED]" } else { "" };
+fn render_header(f: &mut Frame, area: Rect, app: &App) {
+    let tank = app.tank();
+    let pause_indicator = if app.paused { " [PAUSED]" } else { "" };
+    let feed_indicator = if app.feed_mode { " [FEED MODE]" } else { "" };
+    let follow_indicator = if app.follow { " [FOLLOW]" } else { "" };
+    let wallet = app
+        .aquarium
+        .wallet
+        .as_ref()
+        .map_or(String::new(), |wallet| format!("    {}", wallet.summary()));
     let header_text = format!(
-        "Aquarium Simulator{}    Speed: {:.1}x    Fish: {}",
+        "Aquarium Simulator{}{}{}    Speed: {:.1}x    Fish: {}{}",
         pause_indicator,
+        feed_indicator,
+        follow_indicator,
         tank.simulation_speed,
-        tank.fish.len()
+        tank.fish.len(),
+        wallet
     );
 
     let header = Paragraph::new(header_text)
-        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(app.theme.header))
+        .block(
+            panel_block(&app.theme)
+                .title(tank_tabs(app))
+                .title(Line::from(mode_label(app)).right_aligned()),
+        )
         .alignment(Alignment::Center);
 
     f.render_widget(header, area);
 }
 
-fn render_tank(f: &mut Frame, area: Rect, tank: &Tank) {
-    let block = Block::default().borders(Borders::ALL).title("Tank");
+/// The mode being played, with days elapsed in a scenario.
+fn mode_label(app: &App) -> String {
+    let mode = &app.aquarium.mode;
+    match app.aquarium.scenario_elapsed() {
+        Some(elapsed) => format!(" {}: day {:.1} ", mode.label(), elapsed as f32 / TICKS_PER_DAY as f32),
+        None => format!(" {} ", mode.label()),
+    }
+}
+
+/// Win and lose conditions of the scenario being played, one per line.
+fn scenario_goals(app: &App) -> Vec<String> {
+    let GameMode::Scenario(run) = &app.aquarium.mode else {
+        return Vec::new();
+    };
+    let elapsed = app.aquarium.scenario_elapsed().unwrap_or(0);
+    let mut lines = Vec::new();
+    for condition in &run.scenario.win {
+        let mark = if condition.holds(&app.aquarium, elapsed) { "[x]" } else { "[ ]" };
+        lines.push(format!("  {} Win: {}", mark, condition.describe()));
+    }
+    for condition in &run.scenario.lose {
+        lines.push(format!("  (!) Lose if {}", condition.describe()));
+    }
+    if let Some(days) = run.scenario.time_limit_days {
+        lines.push(format!("  (!) Lose after {} days", days));
+    }
+    lines
+}
+
+/// One tab per tank with its fish count, the active one highlighted.
+fn tank_tabs(app: &App) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (index, tank) in app.aquarium.tanks.iter().enumerate() {
+        if index > 0 {
+            spans.push(Span::raw(" │ "));
+        }
+        let style = if index == app.aquarium.active {
+            highlight_style(&app.theme)
+        } else {
+            Style::default().fg(app.theme.muted)
+        };
+        spans.push(Span::styled(format!("{} ({})", tank.name, tank.fish.len()), style));
+    }
+    Line::from(spans)
+}
+
+fn render_tank(f: &mut Frame, area: Rect, app: &App) {
+    let (tank, theme) = (app.tank(), &app.theme);
+    let mut block = tank_block()
+        .title(tank.name.clone())
+        .title_bottom(format!(" {} ", tank.water.summary()))
+        .border_style(Style::default().fg(theme.border))
+        .style(Style::default().bg(theme.water));
+    let incidents = tank.incidents.summary();
+    if !incidents.is_empty() {
+        block = block.title(Line::styled(format!(" {} ", incidents), Style::default().fg(theme.warning)).right_aligned());
+    }
     let inner = block.inner(area);
     f.render_widget(block, area);
 
+    for decoration in &tank.decorations {
+        let art = decoration.kind.art();
+        let top = tank.dimensions.1 as i32 - art.len() as i32;
+        for (row, line) in art.iter().enumerate() {
+            let (x, y) = view_point(app, (decoration.x, 0));
+            draw_text(f, inner, (x, y + top + row as i32), line, Style::default().fg(theme.muted));
+        }
+    }
+
+    for pellet in &tank.food {
+        draw_text(f, inner, view_point(app, pellet.position), ".", Style::default().fg(theme.pellet));
+    }
+
+    for remains in &tank.remains {
+        render_remains(f, inner, app, remains);
+    }
+
     // Render each fish
+    let selected = app.selected_fish_id();
     for fish in &tank.fish {
-        render_fish(f, inner, fish);
+        let marked = app.accessible && selected == Some(fish.id);
+        render_fish(f, inner, app, fish, app.accessible, marked);
+    }
+
+    if app.minimap && !app.tank_fits() {
+        render_minimap(f, inner, app);
     }
 }
 
-fn render_fish(f: &mut Frame, area: Rect, fish: &Fish) {
-    // Get species to determine sprite
-    let species_data = species::get_species(&fish.species);
-    if species_data.is_none() {
+/// Outer width of the minimap, border included.
+const MINIMAP_WIDTH: u16 = 24;
+
+/// The whole tank scaled down into a bottom corner of the view: fish as
+/// dots, with the part on screen shaded. It moves to the left corner when
+/// the selected fish would be under it.
+fn render_minimap(f: &mut Frame, view: Rect, app: &App) {
+    let theme = &app.theme;
+    let (world_width, world_height) = (app.tank().dimensions.0.max(1) as u32, app.tank().dimensions.1.max(1) as u32);
+    let map_width = (MINIMAP_WIDTH - 2) as u32;
+    // Cells are the unit on both axes, so one scale keeps the shape
+    let map_height = (world_height * map_width).div_ceil(world_width).max(1);
+    let (outer_width, outer_height) = (MINIMAP_WIDTH, map_height as u16 + 2);
+    if view.width < outer_width * 2 || view.height < outer_height + 2 {
         return;
     }
-    let species_data = species_data.unwrap();
+    let selected = app.selected_fish_id();
+    let under_right = selected.and_then(|id| app.tank().get_fish(id)).is_some_and(|fish| {
+        let (x, y) = view_point(app, fish.position);
+        x >= (view.width - outer_width) as i32 - 8 && y >= (view.height - outer_height) as i32 - 3
+    });
+    let x = if under_right { view.x } else { view.right() - outer_width };
+    let area = Rect::new(x, view.bottom() - outer_height, outer_width, outer_height);
+    let block = modal_block(theme).title("Map");
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let (view_width, view_height) = app.view_size();
+    let shown_x = app.camera.0 as u32..(app.camera.0 + view_width) as u32;
+    let shown_y = app.camera.1 as u32..(app.camera.1 + view_height) as u32;
+    let buf = f.buffer_mut();
+    for row in 0..map_height {
+        for column in 0..map_width {
+            // Shade cells whose patch of the tank overlaps the view
+            let x = column * world_width / map_width;
+            let y = row * world_height / map_height;
+            let x_end = ((column + 1) * world_width / map_width).max(x + 1);
+            let y_end = ((row + 1) * world_height / map_height).max(y + 1);
+            if shown_x.start < x_end && x < shown_x.end && shown_y.start < y_end && y < shown_y.end {
+                buf[(inner.x + column as u16, inner.y + row as u16)].set_bg(theme.muted);
+            }
+        }
+    }
+
+    for fish in &app.tank().fish {
+        let column = (fish.position.0 as u32 * map_width / world_width).min(map_width - 1);
+        let row = (fish.position.1 as u32 * map_height / world_height).min(map_height - 1);
+        let style = if selected == Some(fish.id) {
+            highlight_style(theme)
+        } else {
+            Style::default().fg(theme.fish_color(&fish.species, fish.hunger))
+        };
+        buf[(inner.x + column as u16, inner.y + row as u16)].set_char('•').set_style(style);
+    }
+}
+
+/// Where a tank point lands in the view, relative to its top-left corner.
+fn view_point(app: &App, (x, y): (u16, u16)) -> (i32, i32) {
+    (x as i32 - app.camera.0 as i32, y as i32 - app.camera.1 as i32)
+}
+
+/// Events listed at the bottom of the text view.
+const TEXT_VIEW_EVENTS: usize = 6;
+
+/// The tank as plain sentences, one per line, for screen readers: any
+/// notices the picture view would toast, a status line, every fish, then
+/// recent events.
+fn render_text_view(f: &mut Frame, area: Rect, app: &App) {
+    let tank = app.tank();
+    let notices = if app.toasts.is_empty() { 0 } else { app.toasts.len() as u16 + 2 };
+    let rows = Layout::vertical([
+        Constraint::Length(notices),
+        Constraint::Min(3),
+        Constraint::Length(TEXT_VIEW_EVENTS as u16 + 2),
+    ])
+    .split(area);
+    if notices > 0 {
+        let lines: Vec<&str> = app.toasts.iter().map(|toast| toast.text.as_str()).collect();
+        f.render_widget(
+            Paragraph::new(lines.join("\n"))
+                .wrap(ratatui::widgets::Wrap { trim: false })
+                .block(panel_block(&app.theme).title("Notices")),
+            rows[0],
+        );
+    }
+
+    let paused = if app.paused { " Paused." } else { "" };
+    let mut lines = vec![
+        format!(
+            "{} tank, {} of {}. Tick {}. Speed {:.1}x. {} fish, {} food pellets.{}",
+            tank.name,
+            app.aquarium.active + 1,
+            app.aquarium.tanks.len(),
+            tank.tick_count,
+            tank.simulation_speed,
+            tank.fish.len(),
+            tank.food.len(),
+            paused
+        ),
+        format!("Water: {}. Equipment: {}.", tank.water.summary(), tank.equipment.summary()),
+    ];
+    let incidents = tank.incidents.summary();
+    if !incidents.is_empty() {
+        lines.push(format!("Incidents: {}.", incidents));
+    }
+    lines.push(String::new());
+    let selected = app.selected_fish_id();
+    let mut selected_line = 0;
+    for (n, fish) in tank.fish.iter().enumerate() {
+        let marker = if selected == Some(fish.id) {
+            selected_line = lines.len();
+            "> "
+        } else {
+            "  "
+        };
+        let heading = match fish.direction {
+            Direction::Left => "left",
+            Direction::Right => "right",
+        };
+        lines.push(format!(
+            "{}{}. {}. Column {}, row {}, swimming {}.",
+            marker,
+            n + 1,
+            fish.describe(),
+            fish.position.0,
+            fish.position.1,
+            heading
+        ));
+    }
+    if tank.fish.is_empty() {
+        lines.push("The tank is empty.".to_string());
+    }
+
+    // Keep the selected fish on screen
+    let height = rows[1].height.saturating_sub(2) as usize;
+    let scroll = selected_line.saturating_sub(height.saturating_sub(1));
+    f.render_widget(
+        Paragraph::new(lines.join("\n"))
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .scroll((scroll as u16, 0))
+            .block(panel_block(&app.theme).title("Tank (text view)")),
+        rows[1],
+    );
 
-    // Choose sprite based on direction
-    let sprite = match fish.direction {
-        Direction::Left => &species_data.sprite_left,
-        Direction::Right => &species_data.sprite_right,
+    let skip = app.event_log.len().saturating_sub(TEXT_VIEW_EVENTS);
+    let events: Vec<String> = app
+        .event_log
+        .iter()
+        .skip(skip)
+        .map(|entry| format!("Tick {}, {}: {}.", entry.tick, entry.tank, entry.event.describe()))
+        .collect();
+    let events = if events.is_empty() {
+        "No events yet.".to_string()
+    } else {
+        events.join("\n")
+    };
+    f.render_widget(
+        Paragraph::new(events)
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .block(panel_block(&app.theme).title("Recent events")),
+        rows[2],
+    );
+}
+
+/// Draw a fish's current animation frame. With `glyphs` on, its hunger and
+/// favorite status are also spelled out after the sprite, and `marked` puts
+/// `>` before it.
+fn render_fish(f: &mut Frame, area: Rect, app: &App, fish: &Fish, glyphs: bool, marked: bool) {
+    let Some(set) = app.sprites.get(&fish.species) else {
+        return;
+    };
+    let theme = &app.theme;
+    let state = SpriteState::of(fish, app.tank().tick_count);
+    let elapsed = app.started.elapsed().as_millis() as u64;
+    let frame = set.frame(state, elapsed, fish.id.as_bytes()[0] as u64);
+
+    let mut style = Style::default().fg(theme.fish_color(&fish.species, fish.hunger));
+    if fish.favorite {
+        style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    }
+    let (x, y) = view_point(app, fish.position);
+    let flip = fish.direction == Direction::Left;
+    draw_sprite(f, area, frame, flip, (x, y), style, theme);
+
+    let mut suffix = String::new();
+    if glyphs {
+        suffix.push_str(fish.hunger_level().glyph());
+        if fish.favorite {
+            suffix.push('*');
+        }
+    }
+    draw_text(f, area, (x + frame.width() as i32, y), &suffix, style);
+    if marked {
+        draw_text(f, area, (x - 1, y), ">", style);
+    }
+}
+
+/// Draw a dead fish floating up.
+fn render_remains(f: &mut Frame, area: Rect, app: &App, remains: &Remains) {
+    let Some(set) = app.sprites.get(&remains.species) else {
+        return;
+    };
+    let frame = set.frame(SpriteState::Dead, 0, 0);
+    let style = Style::default().fg(app.theme.muted);
+    let (x, y) = view_point(app, remains.position);
+    draw_sprite(f, area, frame, remains.direction == Direction::Left, (x, y), style, &app.theme);
+}
+
+/// Draw a sprite frame with its top-left corner at `origin`, relative to
+/// `area`. Cells outside `area` are clipped; mask letters override the
+/// foreground of `style`.
+fn draw_sprite(
+    f: &mut Frame,
+    area: Rect,
+    frame: &SpriteFrame,
+    flip: bool,
+    origin: (i32, i32),
+    style: Style,
+    theme: &Theme,
+) {
+    let buf = f.buffer_mut();
+    for cell in frame.cells(flip) {
+        let Some(position) = clip(area, (origin.0 + cell.x as i32, origin.1 + cell.y as i32)) else {
+            continue;
+        };
+        let mut cell_style = style;
+        if let Some(color) = cell.mask.and_then(|mask| theme.mask_color(mask)) {
+            cell_style = cell_style.fg(color);
+        }
+        buf[position].set_char(cell.symbol).set_style(cell_style);
+    }
+}
+
+/// Draw a line of text at `origin`, relative to `area`, clipped to it.
+fn draw_text(f: &mut Frame, area: Rect, origin: (i32, i32), text: &str, style: Style) {
+    let buf = f.buffer_mut();
+    for (dx, symbol) in text.chars().enumerate() {
+        if let Some(position) = clip(area, (origin.0 + dx as i32, origin.1)) {
+            buf[position].set_char(symbol).set_style(style);
+        }
+    }
+}
+
+/// The terminal cell for a point relative to `area`, if it lies inside.
+fn clip(area: Rect, (x, y): (i32, i32)) -> Option<(u16, u16)> {
+    let inside = x >= 0 && y >= 0 && x < area.width as i32 && y < area.height as i32;
+    inside.then(|| (area.x + x as u16, area.y + y as u16))
+}
+
+fn render_inspector(f: &mut Frame, area: Rect, app: &App) {
+    let theme = &app.theme;
+    let block = panel_block(theme).title("Inspector");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let selected = app.selected_fish_id().and_then(|id| app.tank().get_fish(id));
+    let Some(fish) = selected else {
+        let hint = Paragraph::new("No fish selected.\nPress Tab or click a fish.");
+        f.render_widget(hint, inner);
+        return;
+    };
+    let Some(species_data) = species::get_species(&fish.species) else {
+        return;
     };
 
-    // Determine color based on hunger level
-    let color = if fish.hunger < 50 {
-        Color::Cyan
-    } else if fish.hunger < 80 {
-        Color::Yellow
+    let rows = Layout::vertical([
+        Constraint::Length(8), // Details
+        Constraint::Length(1), // Hunger gauge
+        Constraint::Length(1), // Health gauge
+        Constraint::Length(4), // Family
+        Constraint::Min(3),    // Health history
+        Constraint::Length(3), // Actions
+    ])
+    .split(inner);
+
+    let breeding = if fish.breeding_cooldown == 0 {
+        "ready".to_string()
     } else {
-        Color::Red
+        format!("in {} ticks", fish.breeding_cooldown)
     };
+    let favorite = if fish.favorite { " *" } else { "" };
+    let details = [
+        format!("Name:    {}{}", fish.display_name(), favorite),
+        format!("Species: {} ({})", fish.species, fish.morph.label()),
+        format!("Sex:     {}", fish.sex.map_or("Unknown", |sex| sex.label())),
+        format!("Gen:     {}", fish.generation),
+        format!("Stage:   {}", fish.life_stage(&species_data).label()),
+        format!("Age:     {:.0}% of lifespan", fish.age_fraction(&species_data) * 100.0),
+        format!("Breeds:  {}", breeding),
+        format!("Stress:  {}", fish.stress),
+    ];
+    f.render_widget(Paragraph::new(details.join("\n")), rows[0]);
 
-    // Calculate absolute position
-    let (x, y) = fish.position;
+    let hunger = Gauge::default()
+        .gauge_style(Style::default().fg(theme.hunger_color(fish.hunger)))
+        .percent(fish.hunger.min(100) as u16)
+        .label(format!("Hunger {}", fish.hunger));
+    f.render_widget(hunger, rows[1]);
+    let health = Gauge::default()
+        .gauge_style(Style::default().fg(theme.good))
+        .percent(fish.health.min(100) as u16)
+        .label(format!("Health {}", fish.health));
+    f.render_widget(health, rows[2]);
 
-    // Check if fish is within visible area
-    if x < area.width && y < area.height {
-        let fish_para = Paragraph::new(sprite.as_str())
-            .style(Style::default().fg(color));
+    let parents = if fish.parents.is_empty() {
+        "store-bought".to_string()
+    } else {
+        fish.parents
+            .iter()
+            .map(|id| match app.tank().lineage.get(*id) {
+                Some(parent) if parent.fate == Fate::Alive => parent.name.clone(),
+                Some(parent) => format!("{} (gone)", parent.name),
+                None => format!("#{} (gone)", &id.simple().to_string()[..4]),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let offspring: Vec<String> = app.tank().offspring_of(fish.id).map(|f| f.display_name()).collect();
+    let offspring = match offspring.len() {
+        0 => "none".to_string(),
+        n if n <= 2 => offspring.join(", "),
+        n => format!("{}, {} +{} more", offspring[0], offspring[1], n - 2),
+    };
+    let family = Paragraph::new(format!("Parents: {}\nOffspring: {}", parents, offspring))
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    f.render_widget(family, rows[3]);
+
+    let history: Vec<u64> = app
+        .health_history
+        .get(&fish.id)
+        .map(|h| h.iter().copied().collect())
+        .unwrap_or_default();
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::TOP).title("Health history"))
+        .data(&history)
+        .max(100)
+        .style(Style::default().fg(theme.good));
+    f.render_widget(sparkline, rows[4]);
+
+    let mut actions = vec![Action::FeedSelected, Action::Rename, Action::ToggleFavorite, Action::RemoveFish];
+    if !app.net_targets().is_empty() {
+        actions.push(Action::NetFish);
+    }
+    let actions = app.keymap.hints(Context::Normal, &actions);
+    f.render_widget(Paragraph::new(actions).wrap(ratatui::widgets::Wrap { trim: true }), rows[5]);
+}
+
+
+/// Price list for economy mode, plus what the selected fish would sell for.
+fn render_shop(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 70, f.area());
+    let Some(wallet) = &app.aquarium.wallet else {
+        return;
+    };
 
-        let fish_area = Rect {
-            x: area.x + x,
-            y: area.y + y,
-            width: sprite.len() as u16,
-            height: 1,
+    let rows = app.shop_items.iter().map(|item| {
+        let affordable = wallet.coins >= item.price() as i64;
+        let style = if affordable {
+            Style::default()
+        } else {
+            Style::default().fg(theme.muted)
         };
+        Row::new([
+            Cell::from(item.label()),
+            Cell::from(item.price().to_string()),
+            Cell::from(item.description()),
+        ])
+        .style(style)
+    });
+    let header = Row::new(["Item", "Price", ""]).style(Style::default().fg(theme.accent));
+    let table = Table::new(
+        rows,
+        [Constraint::Length(20), Constraint::Length(6), Constraint::Min(10)],
+    )
+    .header(header)
+    .row_highlight_style(highlight_style(theme));
 
-        f.render_widget(fish_para, fish_area);
+    let sale = app
+        .selected_fish_id()
+        .and_then(|id| app.tank().get_fish(id))
+        .and_then(|fish| {
+            let species = species::get_species(&fish.species)?;
+            Some(format!(
+                "{} ({}) would sell for {}",
+                fish.display_name(),
+                fish.morph.label(),
+                economy::sale_price(fish, &species)
+            ))
+        })
+        .unwrap_or_else(|| "Select a fish in the tank to sell it".to_string());
+
+    let block = modal_block(theme).title(format!("Shop - {}  ({})", app.tank().name, wallet.summary()));
+    let inner = block.inner(area);
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)]).split(inner);
+    let mut state = TableState::default().with_selected(Some(app.shop_cursor));
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_stateful_widget(table, chunks[0], &mut state);
+    f.render_widget(Paragraph::new(sale), chunks[1]);
+    f.render_widget(Paragraph::new(app.keymap.hints(Context::Shop, &SHOP_ACTIONS)), chunks[2]);
+}
+
+fn render_roster(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = f.area();
+    let roster = &app.roster;
+    let rows = roster.rows(app.tank());
+
+    let direction = if roster.descending { "desc" } else { "asc" };
+    let search = if roster.searching {
+        format!("/{}_", roster.query)
+    } else if roster.query.is_empty() {
+        "none".to_string()
+    } else {
+        format!("/{}", roster.query)
+    };
+    let title = format!(
+        "Roster ({} of {})  Sort: {} {}  Filter: {}  Search: {}  Marked: {}",
+        rows.len(),
+        app.tank().fish.len(),
+        roster.sort.label(),
+        direction,
+        roster.filter.label(),
+        search,
+        roster.marked.len()
+    );
+
+    let header = Row::new(
+        std::iter::once(Cell::from(" ")).chain(SortColumn::ALL.iter().map(|column| {
+            let label = if *column == roster.sort {
+                format!("{}{}", column.label(), if roster.descending { " v" } else { " ^" })
+            } else {
+                column.label().to_string()
+            };
+            Cell::from(label)
+        })),
+    )
+    .style(Style::default().fg(theme.accent));
+
+    let table_rows = rows.iter().map(|fish| {
+        let mark = if roster.marked.contains(&fish.id) { "*" } else { " " };
+        let age = species::get_species(&fish.species)
+            .map_or(fish.age.to_string(), |s| format!("{:.0}%", fish.age_fraction(&s) * 100.0));
+        Row::new([
+            Cell::from(mark),
+            if fish.favorite {
+                Cell::from(format!("{} *", fish.display_name())).style(favorite_style(theme))
+            } else {
+                Cell::from(fish.display_name())
+            },
+            Cell::from(fish.species.clone()),
+            Cell::from(age),
+            Cell::from(fish.hunger.to_string()).style(Style::default().fg(theme.hunger_color(fish.hunger))),
+            Cell::from(fish.health.to_string()),
+        ])
+    });
+
+    let table = Table::new(
+        table_rows,
+        [
+            Constraint::Length(1),
+            Constraint::Min(16),
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .block(panel_block(theme).title(title))
+    .row_highlight_style(highlight_style(theme));
+
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(area);
+    let mut state = TableState::default().with_selected(roster.cursor_index(&rows));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(table, chunks[0], &mut state);
+    f.render_widget(
+        Paragraph::new(app.keymap.hints(Context::Roster, &ROSTER_ACTIONS)),
+        chunks[1],
+    );
+}
+
+/// Top of the nitrate sparkline, past the level that harms fish.
+const NITRATE_CHART_MAX: u64 = (water::NITRATE_WARNING * 1.5) as u64;
+
+fn render_stats(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = f.area();
+    let history = &app.tank().stats;
+    let (window, window_label) = STATS_WINDOWS[app.stats_window];
+    let samples: Vec<_> = history.window(window).collect();
+    let events = history.events_in(window);
+
+    let chunks = Layout::vertical([
+        Constraint::Percentage(55),
+        Constraint::Min(8),
+        Constraint::Length(1),
+    ])
+    .split(area);
+    let bottom = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).split(chunks[1]);
+    let sparklines = Layout::vertical([Constraint::Ratio(1, 3); 3]).split(bottom[1]);
+
+    f.render_widget(Clear, area);
+
+    // Population per species over time
+    let first_tick = samples.first().map_or(0, |s| s.tick);
+    let last_tick = samples.last().map_or(0, |s| s.tick).max(first_tick + 1);
+    let peak = samples.iter().map(|s| s.population.values().copied().max().unwrap_or(0)).max().unwrap_or(0).max(1);
+    let series: Vec<(String, Vec<(f64, f64)>)> = species::get_all_species()
+        .into_iter()
+        .filter(|s| samples.iter().any(|sample| sample.population.contains_key(&s.name)))
+        .map(|s| {
+            let points = samples
+                .iter()
+                .map(|sample| {
+                    let count = sample.population.get(&s.name).copied().unwrap_or(0);
+                    (sample.tick as f64, count as f64)
+                })
+                .collect();
+            (s.name, points)
+        })
+        .collect();
+    let datasets = series
+        .iter()
+        .map(|(name, points)| {
+            Dataset::default()
+                .name(name.as_str())
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(theme.species_color(name)))
+                .data(points)
+        })
+        .collect();
+    let title = format!(
+        "{} population ({}, {} samples)  Tick {}  All-time births: {} deaths: {}",
+        app.tank().name,
+        window_label,
+        samples.len(),
+        app.tank().tick_count,
+        history.totals.births,
+        history.totals.deaths()
+    );
+    let chart = Chart::new(datasets)
+        .block(panel_block(theme).title(title))
+        .x_axis(
+            Axis::default()
+                .title("tick")
+                .bounds([first_tick as f64, last_tick as f64])
+                .labels([Span::raw(first_tick.to_string()), Span::raw(last_tick.to_string())]),
+        )
+        .y_axis(
+            Axis::default()
+                .title("fish")
+                .bounds([0.0, peak as f64])
+                .labels([Span::raw("0"), Span::raw(peak.to_string())]),
+        );
+    f.render_widget(chart, chunks[0]);
+
+    // Births and deaths by cause over the window
+    let bars = [
+        ("Births", events.births, theme.good),
+        ("Starved", events.starvation_deaths, theme.bad),
+        ("Old age", events.old_age_deaths, theme.muted),
+        ("Water", events.poor_water_deaths, theme.warning),
+        ("Disease", events.disease_deaths, theme.bad),
+    ]
+    .map(|(label, value, color)| {
+        Bar::default()
+            .label(label.into())
+            .value(value)
+            .style(Style::default().fg(color))
+    });
+    let bar_chart = BarChart::default()
+        .block(panel_block(theme).title("Births & deaths"))
+        .bar_width(8)
+        .bar_gap(2)
+        .data(BarGroup::default().bars(&bars));
+    f.render_widget(bar_chart, bottom[0]);
+
+    let hunger: Vec<u64> = samples.iter().map(|s| s.average_hunger.round() as u64).collect();
+    let health: Vec<u64> = samples.iter().map(|s| s.average_health.round() as u64).collect();
+    let nitrate: Vec<u64> = samples
+        .iter()
+        .filter_map(|s| s.water.as_ref())
+        .map(|w| w.nitrate.round() as u64)
+        .collect();
+    for (area, label, data, color, max) in [
+        (sparklines[0], "Average hunger", &hunger, theme.warning, 100),
+        (sparklines[1], "Average health", &health, theme.good, 100),
+        (sparklines[2], "Nitrate ppm", &nitrate, theme.accent, NITRATE_CHART_MAX),
+    ] {
+        let current = data.last().map_or("-".to_string(), |v| v.to_string());
+        // Sparkline scales to the newest values on the right, so show the tail
+        let width = area.width.saturating_sub(2) as usize;
+        let tail = &data[data.len().saturating_sub(width)..];
+        let sparkline = Sparkline::default()
+            .block(panel_block(theme).title(format!("{}: {}", label, current)))
+            .data(tail)
+            .max(max)
+            .style(Style::default().fg(color));
+        f.render_widget(sparkline, area);
+    }
+
+    f.render_widget(
+        Paragraph::new(app.keymap.hints(Context::Stats, &STATS_ACTIONS)),
+        chunks[2],
+    );
+}
+
+/// Stack active toasts in the top-right corner of the tank, newest last.
+fn render_toasts(f: &mut Frame, area: Rect, app: &App) {
+    if app.toasts.is_empty() {
+        return;
     }
+    let inner = tank_block().inner(area);
+    let longest = app.toasts.iter().map(|t| t.text.chars().count()).max().unwrap_or(0) as u16;
+    let width = (longest + 2).min(inner.width);
+    let height = (app.toasts.len() as u16 + 2).min(inner.height);
+    let toast_area = Rect::new(inner.right().saturating_sub(width), inner.y, width, height);
+
+    let lines: Vec<Line> = app
+        .toasts
+        .iter()
+        .map(|toast| Line::styled(toast.text.clone(), Style::default().fg(app.theme.severity_color(toast.severity))))
+        .collect();
+    f.render_widget(Clear, toast_area);
+    f.render_widget(
+        Paragraph::new(lines).block(modal_block(&app.theme)),
+        toast_area,
+    );
 }
 
-fn render_footer(f: &mut Frame, area: Rect, tank: &Tank, selected_fish_id: Option<uuid::Uuid>) {
-    let mut footer_text = String::from("[F]eed [A]dd [R]emove [Tab]Select [Space]Pause [+/-]Speed [?]Help [Q]uit");
+fn render_event_log(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = f.area();
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(area);
 
-    // If a fish is selected, show its stats
-    if let Some(fish_id) = selected_fish_id {
-        if let Some(fish) = tank.get_fish(fish_id) {
-            footer_text = format!(
-                "Selected: {} | Hunger: {} | Health: {} | Age: {} | {}",
-                fish.species, fish.hunger, fish.health, fish.age, footer_text
+    let lines: Vec<Line> = app
+        .event_log
+        .iter()
+        .rev()
+        .skip(app.log_scroll)
+        .map(|entry| {
+            Line::from(vec![
+                Span::styled(format!("{:>8}  ", entry.tick), Style::default().fg(theme.muted)),
+                Span::styled(format!("{:<12}", entry.tank), Style::default().fg(theme.muted)),
+                Span::styled(entry.event.describe(), Style::default().fg(theme.severity_color(entry.event.severity()))),
+            ])
+        })
+        .collect();
+    let title = format!("Event log ({} events, newest first, tick {})", app.event_log.len(), app.tank().tick_count);
+    let body = if lines.is_empty() {
+        Paragraph::new("Nothing has happened yet.")
+    } else {
+        Paragraph::new(lines)
+    };
+
+    f.render_widget(Clear, area);
+    f.render_widget(body.block(panel_block(theme).title(title)), chunks[0]);
+    f.render_widget(
+        Paragraph::new(app.keymap.hints(Context::EventLog, &LOG_ACTIONS)),
+        chunks[1],
+    );
+}
+
+fn render_lineage(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = f.area();
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(area);
+    let lineage = &app.tank().lineage;
+    let rows = app.lineage_rows();
+
+    let lines: Vec<Line> = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(i, row)| {
+            let record = lineage.get(row.id)?;
+            let indent = "  ".repeat(row.depth);
+            let marker = match row.branch {
+                Branch::Ancestor => "^ ",
+                Branch::Root => "* ",
+                Branch::Descendant => "v ",
+            };
+            let text = format!(
+                "{}{}{}  ({}, {} {}, gen {})",
+                indent,
+                marker,
+                record.summary(),
+                row.relation(),
+                record.species,
+                record.sex.map_or("Unknown", |sex| sex.label()),
+                record.generation
             );
-        }
+            let mut style = match record.fate {
+                Fate::Alive => Style::default(),
+                _ => Style::default().fg(theme.muted),
+            };
+            if row.branch == Branch::Root {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if i == app.lineage_cursor {
+                style = style.patch(highlight_style(theme));
+            }
+            Some(Line::styled(text, style))
+        })
+        .collect();
+
+    let name = app
+        .lineage_focus
+        .and_then(|id| lineage.get(id))
+        .map_or(String::new(), |r| r.name.clone());
+    let title = format!("Family of {}  ({} fish on record)", name, lineage.len());
+    // Keep the cursor row on screen
+    let height = chunks[0].height.saturating_sub(2) as usize;
+    let scroll = app.lineage_cursor.saturating_sub(height.saturating_sub(1));
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .block(panel_block(theme).title(title)),
+        chunks[0],
+    );
+    f.render_widget(
+        Paragraph::new(app.keymap.hints(Context::Lineage, &LINEAGE_ACTIONS)),
+        chunks[1],
+    );
+}
+
+fn render_rename_prompt(f: &mut Frame, app: &App) {
+    let area = centered_rect(50, 20, f.area());
+    let current = app
+        .selected_fish_id()
+        .and_then(|id| app.tank().get_fish(id))
+        .map_or(String::new(), |fish| fish.display_name());
+    let confirm = app
+        .keymap
+        .keys_for(Context::Prompt, Action::Confirm)
+        .first()
+        .map_or(String::new(), |k| k.to_string());
+    let text = format!(
+        "Rename {}:\n\n> {}_\n\n{} to save, {} to cancel (empty resets)",
+        current,
+        app.input,
+        confirm,
+        app.keymap.close_hint(Context::Prompt)
+    );
+    let block = modal_block(&app.theme).title("Rename");
+
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// Highlight for favorite fish.
+fn favorite_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.favorite).add_modifier(Modifier::BOLD)
+}
+
+/// Cursor row in lists and tables. Falls back to reverse video when the
+/// theme has no highlight color (e.g. on monochrome terminals).
+fn highlight_style(theme: &Theme) -> Style {
+    let style = Style::default().fg(theme.highlight_fg).bg(theme.highlight_bg);
+    if theme.highlight_bg == Color::Reset {
+        style.add_modifier(Modifier::REVERSED)
+    } else {
+        style
+    }
+}
+
+/// Bordered block for full-screen views.
+fn panel_block(theme: &Theme) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border))
+}
+
+/// Bordered block for popups drawn over the tank.
+fn modal_block(theme: &Theme) -> Block<'static> {
+    panel_block(theme).style(Style::default().bg(theme.modal))
+}
+
+fn render_footer(f: &mut Frame, area: Rect, app: &App) {
+    let mut footer_text = app.keymap.footer_hints();
+    let selected = app.selected_fish_id().and_then(|id| app.tank().get_fish(id));
+
+    if app.accessible {
+        let description = selected.map_or("No fish selected".to_string(), |fish| fish.describe());
+        footer_text = format!("{}\n{}", description, footer_text);
+    } else if let Some(fish) = selected {
+        // If a fish is selected, show its stats
+        footer_text = format!(
+            "Selected: {} | Hunger: {} | Health: {} | Age: {} | {}",
+            fish.species, fish.hunger, fish.health, fish.age, footer_text
+        );
     }
 
     let footer = Paragraph::new(footer_text)
-        .block(Block::default().borders(Borders::ALL))
+        .block(panel_block(&app.theme))
         .alignment(Alignment::Left);
 
     f.render_widget(footer, area);
@@ -136,26 +1055,31 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     .split(popup_layout[1])[1]
 }
 
-fn render_species_picker(f: &mut Frame) {
+fn render_species_picker(f: &mut Frame, app: &App) {
+    let keymap = &app.keymap;
     let area = centered_rect(50, 40, f.area());
 
-    let species_text = vec![
-        "Select a species:",
-        "",
-        "1 - Goldfish (Slow hunger, high health, long life)",
-        "2 - Guppy (Fast hunger, low health, short life)",
-        "3 - Betta (Medium hunger, medium health, medium life)",
-        "4 - Tetra (Fast hunger, low health, medium life)",
-        "5 - Angelfish (Slow hunger, high health, long life)",
-        "",
-        "Press Esc to cancel",
+    const DESCRIPTIONS: [&str; 5] = [
+        "Slow hunger, high health, long life",
+        "Fast hunger, low health, short life",
+        "Medium hunger, medium health, medium life",
+        "Fast hunger, low health, medium life",
+        "Slow hunger, high health, long life",
     ];
 
+    let mut species_text = vec!["Select a species:".to_string(), String::new()];
+    for (n, species) in app.species.iter().enumerate() {
+        let Some(key) = keymap.keys_for(Context::Picker, Action::PickSpecies(n)).into_iter().next() else {
+            continue;
+        };
+        let description = DESCRIPTIONS.get(n).copied().unwrap_or("");
+        species_text.push(format!("{} - {} ({})", key, species.name, description));
+    }
+    species_text.push(String::new());
+    species_text.push(format!("Press {} to cancel", keymap.close_hint(Context::Picker)));
+
     let text = species_text.join("\n");
-    let block = Block::default()
-        .title("Add Fish")
-        .borders(Borders::ALL)
-        .style(Style::default().bg(Color::Black));
+    let block = modal_block(&app.theme).title("Add Fish");
 
     let paragraph = Paragraph::new(text)
         .block(block)
@@ -165,42 +1089,406 @@ fn render_species_picker(f: &mut Frame) {
     f.render_widget(paragraph, area);
 }
 
-fn render_help(f: &mut Frame) {
+/// The other tanks, with how much stress the selected fish would pick up
+/// moving into each.
+fn render_net_picker(f: &mut Frame, app: &App) {
+    let keymap = &app.keymap;
+    let area = centered_rect(60, 40, f.area());
+    let tank = app.tank();
+    let fish = app.selected_fish_id().and_then(|id| tank.get_fish(id));
+
+    let mut lines = vec![
+        format!(
+            "Net {} out of {} ({}) into:",
+            fish.map_or("the fish".to_string(), |f| f.display_name()),
+            tank.name,
+            tank.water.summary()
+        ),
+        String::new(),
+    ];
+    for (n, index) in app.net_targets().into_iter().enumerate() {
+        let Some(key) = keymap.keys_for(Context::Picker, Action::PickSpecies(n)).into_iter().next() else {
+            continue;
+        };
+        let other = &app.aquarium.tanks[index];
+        lines.push(format!(
+            "{} - {} ({} fish; {}), stress {}",
+            key,
+            other.name,
+            other.fish.len(),
+            other.water.summary(),
+            tank.water.acclimation_stress(&other.water)
+        ));
+    }
+    lines.push(String::new());
+    lines.push(format!("Press {} to cancel", keymap.close_hint(Context::Picker)));
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines.join("\n"))
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .block(modal_block(&app.theme).title("Net Fish")),
+        area,
+    );
+}
+
+/// Pick sandbox, economy, survival or a scenario for a new game.
+fn render_mode_select(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 60, f.area());
+    let block = modal_block(theme).title("New Game");
+    let inner = block.inner(area);
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(3), Constraint::Length(1)]).split(inner);
+
+    let rows = app
+        .mode_choices
+        .iter()
+        .map(|mode| Row::new([Cell::from(mode.label().to_string()), Cell::from(mode.description().to_string())]));
+    let table = Table::new(rows, [Constraint::Length(20), Constraint::Min(10)])
+        .row_highlight_style(highlight_style(theme));
+    let mut state = TableState::default().with_selected(Some(app.mode_cursor));
+
+    let details = match app.mode_choices.get(app.mode_cursor) {
+        Some(GameMode::Scenario(run)) => {
+            let win: Vec<String> = run.scenario.win.iter().map(|c| c.describe()).collect();
+            let lose: Vec<String> = run.scenario.lose.iter().map(|c| c.describe()).collect();
+            format!(
+                "Win when: {}\nLose if: {}",
+                win.join(", "),
+                if lose.is_empty() { "-".to_string() } else { lose.join(", ") }
+            )
+        }
+        Some(mode) => mode.description().to_string(),
+        None => String::new(),
+    };
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_stateful_widget(table, chunks[0], &mut state);
+    f.render_widget(
+        Paragraph::new(details)
+            .wrap(ratatui::widgets::Wrap { trim: true })
+            .style(Style::default().fg(theme.muted)),
+        chunks[1],
+    );
+    f.render_widget(Paragraph::new(app.keymap.hints(Context::ModeSelect, &MODE_ACTIONS)), chunks[2]);
+}
+
+/// How the scenario ended, with the state of its goals.
+fn render_results(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(60, 50, f.area());
+    let GameMode::Scenario(run) = &app.aquarium.mode else {
+        return;
+    };
+    let Some(outcome) = &run.outcome else {
+        return;
+    };
+
+    let (title, color) = if outcome.won {
+        ("Scenario complete", theme.good)
+    } else {
+        ("Scenario failed", theme.bad)
+    };
+    let totals = app.aquarium.tanks.iter().map(|tank| &tank.stats.totals);
+    let births: u64 = totals.clone().map(|t| t.births).sum();
+    let deaths: u64 = totals.map(|t| t.deaths()).sum();
+    let mut lines = vec![
+        Line::styled(run.scenario.name.clone(), Style::default().add_modifier(Modifier::BOLD)),
+        Line::styled(outcome.reason.clone(), Style::default().fg(color)),
+        Line::raw(""),
+        Line::raw(format!(
+            "Time: {:.1} days ({} ticks)",
+            outcome.elapsed as f32 / TICKS_PER_DAY as f32,
+            outcome.elapsed
+        )),
+        Line::raw(format!("Fish: {}   Births: {}   Deaths: {}", app.aquarium.fish_count(), births, deaths)),
+    ];
+    if let Some(wallet) = &app.aquarium.wallet {
+        lines.push(Line::raw(wallet.summary()));
+    }
+    lines.push(Line::raw(""));
+    lines.extend(scenario_goals(app).into_iter().map(Line::raw));
+    lines.push(Line::raw(""));
+    lines.push(Line::raw(format!(
+        "Press {} to keep watching the tank",
+        app.keymap.close_hint(Context::Help)
+    )));
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .block(modal_block(theme).title(title)),
+        area,
+    );
+}
+
+/// Care tasks with when each was last done, the readings they affect, and
+/// the tank's maintenance history.
+fn render_maintenance(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 80, f.area());
+    let tank = app.tank();
+    let water = &tank.water;
+    let fresh = &app.fresh_water;
+
+    let readings = vec![
+        Line::raw(format!("Water: {}", water.summary())),
+        Line::raw(format!(
+            "Level {:.0}%  Debris {:.1}  Bacteria {:.0}%  Filter clogged {:.0}%  Chlorine {:.2} ppm{}",
+            water.level * 100.0,
+            water.debris,
+            water.bacteria * 100.0,
+            water.clog * 100.0,
+            water.chlorine,
+            if water.conditioner > 0 { "  (conditioned)" } else { "" }
+        )),
+    ];
+
+    let mut rows: Vec<Row> = Task::ALL
+        .iter()
+        .map(|task| {
+            let setting = match task {
+                Task::WaterChange => format!("< {}% >", fresh.percent),
+                _ => String::new(),
+            };
+            let last = match tank.maintenance.last(*task) {
+                Some(record) => format!("t{} ({} ago)", record.tick, tank.tick_count - record.tick),
+                None => "never".to_string(),
+            };
+            Row::new([
+                Cell::from(task.label()),
+                Cell::from(setting),
+                Cell::from(last),
+                Cell::from(task.description()),
+            ])
+        })
+        .collect();
+    rows.push(Row::new([
+        Cell::from("Tap water"),
+        Cell::from(format!("< {:.0} °C >", fresh.temperature)),
+        Cell::from(format!("tank is {:.1} °C", water.temperature)),
+        Cell::from("for water changes and topping off"),
+    ]));
+    let header = Row::new(["Task", "", "Last done", ""]).style(Style::default().fg(theme.accent));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Length(10),
+            Constraint::Length(18),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(highlight_style(theme));
+
+    let history: Vec<Line> = tank
+        .maintenance
+        .recent()
+        .map(|record| Line::raw(record.summary()))
+        .collect();
+    let history = if history.is_empty() {
+        vec![Line::styled("Nothing done yet", Style::default().fg(theme.muted))]
+    } else {
+        history
+    };
+
+    let block = modal_block(theme).title(format!("Maintenance - {}", tank.name));
+    let inner = block.inner(area);
+    let chunks = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(Task::ALL.len() as u16 + 2),
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+    let mut state = TableState::default().with_selected(Some(app.maintenance_cursor));
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(Paragraph::new(readings), chunks[0]);
+    f.render_stateful_widget(table, chunks[1], &mut state);
+    f.render_widget(Paragraph::new("History").style(Style::default().fg(theme.accent)), chunks[2]);
+    f.render_widget(Paragraph::new(history), chunks[3]);
+    f.render_widget(
+        Paragraph::new(app.keymap.hints(Context::Maintenance, &MAINTENANCE_ACTIONS)),
+        chunks[4],
+    );
+}
+
+/// Every achievement: when it was unlocked, or the best progress so far.
+fn render_achievements(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 60, f.area());
+    let profile = &app.profile;
+
+    let rows = Achievement::ALL.iter().map(|achievement| {
+        let (mark, status, style) = match profile.unlocked.get(achievement) {
+            Some(unlock) => ("[x]", unlock.describe(), Style::default().fg(theme.good)),
+            None => (
+                "[ ]",
+                format!("{}/{}", profile.progress(*achievement), achievement.target()),
+                Style::default().fg(theme.muted),
+            ),
+        };
+        Row::new([
+            Cell::from(mark),
+            Cell::from(achievement.title()),
+            Cell::from(achievement.description()),
+            Cell::from(status),
+        ])
+        .style(style)
+    });
+    let header = Row::new(["", "Achievement", "", "Unlocked"]).style(Style::default().fg(theme.accent));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(14),
+            Constraint::Min(20),
+            Constraint::Length(30),
+        ],
+    )
+    .header(header);
+
+    let title = format!("Achievements ({} of {})", profile.unlocked.len(), Achievement::ALL.len());
+    let block = modal_block(theme).title(title);
+    let inner = block.inner(area);
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(inner);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(table, chunks[0]);
+    f.render_widget(Paragraph::new(app.keymap.hints(Context::Help, &[Action::Close])), chunks[1]);
+}
+
+fn render_help(f: &mut Frame, app: &App) {
+    let keymap = &app.keymap;
     let area = centered_rect(60, 50, f.area());
 
-    let help_text = vec![
-        "Aquarium Simulator - Help",
-        "",
-        "Controls:",
-        "  F - Feed all fish",
-        "  A - Add fish (opens species picker)",
-        "  R - Remove selected fish",
-        "  Tab - Select next fish",
-        "  Space - Pause/Resume simulation",
-        "  + / = - Increase simulation speed",
-        "  - - Decrease simulation speed",
-        "  ? - Toggle this help",
-        "  Q - Quit (auto-saves)",
-        "",
-        "Fish Care:",
-        "  - Feed fish regularly to keep hunger low",
-        "  - Fish turn yellow when hungry, red when critical",
-        "  - Unfed fish will starve and die",
-        "  - Healthy, fed fish breed automatically",
-        "",
-        "Press ? or Esc to close",
+    let mut help_text = vec![
+        "Aquarium Simulator - Help".to_string(),
+        String::new(),
+        "Controls:".to_string(),
     ];
+    let goals = scenario_goals(app);
+    if !goals.is_empty() {
+        help_text.push(format!("{}:", app.aquarium.mode.label()));
+        help_text.extend(goals);
+        help_text.push(String::new());
+    }
+    help_text.extend(keymap.help_lines().into_iter().map(|line| format!("  {}", line)));
+    help_text.extend(
+        [
+            "",
+            "Fish Care:",
+            "  - Feed fish regularly to keep hunger low",
+            "  - Fish change color when hungry and again when critical",
+            "  - Unfed fish will starve and die",
+            "  - Healthy, fed male/female pairs breed automatically",
+            "",
+        ]
+        .iter()
+        .map(|line| line.to_string()),
+    );
+    help_text.push(format!("Press {} to close", keymap.close_hint(Context::Help)));
 
     let text = help_text.join("\n");
-    let block = Block::default()
-        .title("Help")
-        .borders(Borders::ALL)
-        .style(Style::default().bg(Color::Black));
+    let block = modal_block(&app.theme).title("Help (scroll for more)");
 
     let paragraph = Paragraph::new(text)
         .block(block)
-        .alignment(Alignment::Left);
+        .alignment(Alignment::Left)
+        .scroll((app.help_scroll, 0));
 
     f.render_widget(Clear, area);
     f.render_widget(paragraph, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aquarium::Aquarium;
+    use crate::events::Severity;
+    use crate::keymap::Keymap;
+    use crate::sprite::Sprites;
+    use crate::tank::Tank;
+    use ratatui::Terminal;
+    use ratatui::layout::Position;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn inspector_offers_netting() {
+        let mut app = App::new(Aquarium::starter(Tank::with_seed(60, 20, 7)), Keymap::default_layout());
+        let fish = Fish::new("Guppy".to_string(), (10, 10));
+        app.selected_fish = Some(fish.id);
+        app.tank_mut().add_fish(fish);
+        app.inspector_open = true;
+
+        let mut terminal = Terminal::new(TestBackend::new(140, 50)).unwrap();
+        terminal.draw(|f| render(f, &app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("[B]Net"));
+    }
+
+    #[test]
+    fn text_view_shows_notices() {
+        let mut app = App::new(Aquarium::starter(Tank::with_seed(60, 20, 7)), Keymap::default_layout());
+        app.text_view = true;
+        app.notify("HTTP API on http://127.0.0.1:7878".to_string(), Severity::Info);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|f| render(f, &app)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Notices"));
+        assert!(screen.contains("HTTP API on http://127.0.0.1:7878"));
+        assert!(screen.contains("Tank (text view)"));
+    }
+
+    /// Cells showing `symbol` after drawing `app` on a terminal of `size`.
+    fn cells_with(app: &App, size: (u16, u16), symbol: &str) -> Vec<Position> {
+        let mut terminal = Terminal::new(TestBackend::new(size.0, size.1)).unwrap();
+        terminal.draw(|f| render(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.area.positions().filter(|&position| buffer[position].symbol() == symbol).collect()
+    }
+
+    #[test]
+    fn wide_sprites_are_clipped_to_the_tank() {
+        let dir = std::env::temp_dir().join(format!("aquarium-sim-test-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sprites.json");
+        let row = "@".repeat(12);
+        let json = format!(r#"{{"Guppy": {{"idle": [{{"art": ["{row}", "{row}", "{row}"]}}]}}}}"#);
+        std::fs::write(&path, json).unwrap();
+        let sprites = Sprites::load(Some(&path)).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        // Large enough for the whole tank, then smaller than it
+        for size in [(120, 40), (50, 16)] {
+            let mut app = App::new(Aquarium::single(Tank::with_seed(60, 20, 7)), Keymap::default_layout());
+            app.sprites = sprites.clone();
+            app.resize(size.0, size.1);
+            assert!(cells_with(&app, size, "@").is_empty());
+
+            let inner = tank_inner_area(Rect::new(0, 0, size.0, size.1), false, false, (60, 20));
+            let (right, bottom) = (inner.width - 1, inner.height - 1);
+            for (n, position) in [(right, 0), (0, bottom), (right, bottom), (right - 3, bottom - 1), (59, 19)]
+                .into_iter()
+                .enumerate()
+            {
+                let mut fish = Fish::new("Guppy".to_string(), position);
+                if n % 2 == 1 {
+                    fish.direction = Direction::Left;
+                }
+                app.tank_mut().add_fish(fish);
+            }
+            let drawn = cells_with(&app, size, "@");
+            assert!(!drawn.is_empty());
+            for position in drawn {
+                assert!(inner.contains(position), "drew at {:?} outside {:?}", position, inner);
+            }
+        }
+    }
+}
@@ -0,0 +1,512 @@
+//! Water chemistry and the equipment that keeps it in check.
+//!
+//! Every tank has its own [`Water`]. Fish add ammonia, and waste settles in
+//! the gravel as debris that rots into more. Bacteria in the filter turn
+//! ammonia into nitrate, as well as the filter's clogging allows, and the
+//! heater (if any) pulls the temperature towards its target while an
+//! unheated tank drifts to room temperature. pH sinks as nitrate builds up,
+//! and water slowly evaporates. Fresh tap water brings chlorine, which
+//! kills bacteria and burns fish until it gases off or is conditioned away.
+//! [`Water::harm`] says how much a species suffers from the current values.
+
+use crate::species::Species;
+use serde::{Deserialize, Serialize};
+
+/// Temperature an unheated tank settles at, in °C.
+pub const ROOM_TEMPERATURE: f32 = 21.0;
+/// Degrees the temperature moves towards its target per tick.
+const TEMPERATURE_DRIFT: f32 = 0.02;
+/// Ammonia each fish adds per tick, in ppm.
+const AMMONIA_PER_FISH: f32 = 0.0004;
+/// Ammonia released by a pellet that dissolves uneaten.
+const AMMONIA_PER_PELLET: f32 = 0.02;
+/// Nitrate produced per ppm of ammonia the filter converts.
+const NITRATE_PER_AMMONIA: f32 = 10.0;
+/// Share of nitrate taken up by plants and bacteria per tick.
+const NITRATE_UPTAKE: f32 = 0.0005;
+/// pH of fresh water with no nitrate.
+const BASE_PH: f32 = 7.6;
+/// pH lost per ppm of nitrate.
+const PH_PER_NITRATE: f32 = 0.01;
+/// pH moved towards its target per tick.
+const PH_DRIFT: f32 = 0.001;
+/// Debris each fish leaves per tick, and each dissolved pellet.
+const DEBRIS_PER_FISH: f32 = 0.001;
+const DEBRIS_PER_PELLET: f32 = 0.5;
+/// Share of the debris that rots per tick, and the ammonia it releases.
+const DEBRIS_ROT: f32 = 0.0005;
+const AMMONIA_PER_DEBRIS: f32 = 0.2;
+/// Filter clogging added per tick, and the most it can clog.
+const CLOG_RATE: f32 = 0.00005;
+const MAX_CLOG: f32 = 0.9;
+/// Share of the missing bacteria that regrows per tick.
+const BACTERIA_GROWTH: f32 = 0.001;
+/// Bacteria killed per tick by each ppm of chlorine.
+const CHLORINE_KILL: f32 = 0.002;
+/// Share of the chlorine that gases off per tick.
+const CHLORINE_DECAY: f32 = 0.002;
+/// Water lost to evaporation per tick at 25 °C, as a share of a full tank.
+const EVAPORATION: f32 = 0.00001;
+/// pH and chlorine of tap water, in ppm.
+pub const TAP_PH: f32 = BASE_PH;
+const TAP_CHLORINE: f32 = 1.0;
+/// Ticks a dose of conditioner keeps neutralising chlorine.
+const CONDITIONER_TICKS: u32 = 300;
+
+/// Levels at which the tank raises a water alert.
+pub const AMMONIA_WARNING: f32 = 0.5;
+pub const AMMONIA_TOXIC: f32 = 1.0;
+pub const NITRATE_WARNING: f32 = 80.0;
+pub const CHLORINE_TOXIC: f32 = 0.1;
+/// Share of a full tank below which the level is too low.
+pub const LOW_LEVEL: f32 = 0.85;
+pub const TEMPERATURE_RANGE: (f32, f32) = (20.0, 30.0);
+pub const PH_RANGE: (f32, f32) = (6.0, 8.5);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Water {
+    /// °C.
+    pub temperature: f32,
+    pub ph: f32,
+    /// ppm; toxic to every species above [`AMMONIA_TOXIC`].
+    pub ammonia: f32,
+    /// ppm.
+    pub nitrate: f32,
+    /// ppm, from untreated tap water.
+    #[serde(default)]
+    pub chlorine: f32,
+    /// Waste in the gravel, rotting into ammonia.
+    #[serde(default)]
+    pub debris: f32,
+    /// Nitrifying bacteria in the filter, from 0.0 to a fully cycled 1.0.
+    #[serde(default = "full")]
+    pub bacteria: f32,
+    /// How clogged the filter is, from 0.0 to [`MAX_CLOG`].
+    #[serde(default)]
+    pub clog: f32,
+    /// Share of a full tank.
+    #[serde(default = "full")]
+    pub level: f32,
+    /// Ticks left of the last conditioner dose.
+    #[serde(default)]
+    pub conditioner: u32,
+    /// Alerts currently raised, so each is only reported once.
+    #[serde(default)]
+    alerts: Vec<WaterIssue>,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Water {
+            temperature: 24.0,
+            ph: 7.4,
+            ammonia: 0.0,
+            nitrate: 0.0,
+            chlorine: 0.0,
+            debris: 0.0,
+            bacteria: 1.0,
+            clog: 0.0,
+            level: 1.0,
+            conditioner: 0,
+            alerts: Vec::new(),
+        }
+    }
+}
+
+fn full() -> f32 {
+    1.0
+}
+
+/// Something wrong with the water worth telling the player about.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaterIssue {
+    Ammonia,
+    Nitrate,
+    Cold,
+    Hot,
+    Acidic,
+    Alkaline,
+    Chlorine,
+    LowLevel,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Equipment {
+    /// Target temperature in °C, or `None` without a heater.
+    pub heater: Option<f32>,
+    /// Share of the ammonia the filter converts to nitrate each tick; 0
+    /// without a filter.
+    pub filter: f32,
+}
+
+impl Default for Equipment {
+    fn default() -> Self {
+        Equipment {
+            heater: Some(25.0),
+            filter: 0.01,
+        }
+    }
+}
+
+impl Equipment {
+    /// "heater 25 °C, filter 1.0%" or "no heater, no filter".
+    pub fn summary(&self) -> String {
+        let heater = match self.heater {
+            Some(target) => format!("heater {:.0} °C", target),
+            None => "no heater".to_string(),
+        };
+        let filter = if self.filter > 0.0 {
+            format!("filter {:.1}%", self.filter * 100.0)
+        } else {
+            "no filter".to_string()
+        };
+        format!("{}, {}", heater, filter)
+    }
+}
+
+impl Water {
+    /// Advance one tick. `room` is the temperature around the tank, which
+    /// a heater can warm the water above but not cool it below. `fish` is
+    /// the number of fish adding waste and `dissolved` the pellets that
+    /// rotted this tick. Returns issues that have just appeared.
+    pub fn update(&mut self, equipment: &Equipment, room: f32, fish: usize, dissolved: usize) -> Vec<WaterIssue> {
+        let target = equipment.heater.map_or(room, |heater| heater.max(room));
+        self.temperature = approach(self.temperature, target, TEMPERATURE_DRIFT);
+
+        self.debris += fish as f32 * DEBRIS_PER_FISH + dissolved as f32 * DEBRIS_PER_PELLET;
+        let rotted = self.debris * DEBRIS_ROT;
+        self.debris -= rotted;
+        self.ammonia +=
+            fish as f32 * AMMONIA_PER_FISH + dissolved as f32 * AMMONIA_PER_PELLET + rotted * AMMONIA_PER_DEBRIS;
+
+        // Evaporation leaves ammonia and nitrate behind, so they concentrate
+        let evaporated = EVAPORATION * (self.temperature / 25.0).max(0.0);
+        if self.level > evaporated {
+            let concentration = self.level / (self.level - evaporated);
+            self.level -= evaporated;
+            self.ammonia *= concentration;
+            self.nitrate *= concentration;
+        }
+
+        self.bacteria -= self.bacteria * self.chlorine * CHLORINE_KILL;
+        self.bacteria += (1.0 - self.bacteria) * BACTERIA_GROWTH;
+        self.chlorine -= self.chlorine * CHLORINE_DECAY;
+        self.conditioner = self.conditioner.saturating_sub(1);
+        if equipment.filter > 0.0 {
+            self.clog = (self.clog + CLOG_RATE).min(MAX_CLOG);
+        }
+
+        let converted = self.ammonia * equipment.filter.clamp(0.0, 1.0) * self.bacteria * (1.0 - self.clog);
+        self.ammonia -= converted;
+        self.nitrate += converted * NITRATE_PER_AMMONIA;
+        self.nitrate -= self.nitrate * NITRATE_UPTAKE;
+
+        let ph_target = BASE_PH - self.nitrate * PH_PER_NITRATE;
+        self.ph = approach(self.ph, ph_target, PH_DRIFT);
+
+        let issues = self.issues();
+        let new: Vec<WaterIssue> = issues.iter().copied().filter(|i| !self.alerts.contains(i)).collect();
+        self.alerts = issues;
+        new
+    }
+
+    /// Everything currently out of range.
+    pub fn issues(&self) -> Vec<WaterIssue> {
+        let mut issues = Vec::new();
+        if self.ammonia >= AMMONIA_WARNING {
+            issues.push(WaterIssue::Ammonia);
+        }
+        if self.nitrate >= NITRATE_WARNING {
+            issues.push(WaterIssue::Nitrate);
+        }
+        if self.temperature < TEMPERATURE_RANGE.0 {
+            issues.push(WaterIssue::Cold);
+        } else if self.temperature > TEMPERATURE_RANGE.1 {
+            issues.push(WaterIssue::Hot);
+        }
+        if self.ph < PH_RANGE.0 {
+            issues.push(WaterIssue::Acidic);
+        } else if self.ph > PH_RANGE.1 {
+            issues.push(WaterIssue::Alkaline);
+        }
+        if self.chlorine >= CHLORINE_TOXIC {
+            issues.push(WaterIssue::Chlorine);
+        }
+        if self.level < LOW_LEVEL {
+            issues.push(WaterIssue::LowLevel);
+        }
+        issues
+    }
+
+    /// Alert text for an issue, with the current reading.
+    pub fn describe(&self, issue: WaterIssue) -> String {
+        match issue {
+            WaterIssue::Ammonia => format!("Ammonia is high ({:.2} ppm)", self.ammonia),
+            WaterIssue::Nitrate => format!("Nitrate is high ({:.0} ppm)", self.nitrate),
+            WaterIssue::Cold => format!("Water is cold ({:.1} °C)", self.temperature),
+            WaterIssue::Hot => format!("Water is hot ({:.1} °C)", self.temperature),
+            WaterIssue::Acidic => format!("Water is acidic (pH {:.1})", self.ph),
+            WaterIssue::Alkaline => format!("Water is alkaline (pH {:.1})", self.ph),
+            WaterIssue::Chlorine => format!("Chlorine in the water ({:.2} ppm)", self.chlorine),
+            WaterIssue::LowLevel => format!("Water level is low ({:.0}%)", self.level * 100.0),
+        }
+    }
+
+    /// "24.5 °C, pH 7.4, NH3 0.02, NO3 12".
+    pub fn summary(&self) -> String {
+        format!(
+            "{:.1} °C, pH {:.1}, NH3 {:.2}, NO3 {:.0}",
+            self.temperature, self.ph, self.ammonia, self.nitrate
+        )
+    }
+
+    /// Health a fish of `species` loses per check in this water: ammonia
+    /// and nitrate hurt every fish, temperature and pH only outside what
+    /// the species tolerates.
+    pub fn harm(&self, species: &Species) -> u8 {
+        let mut harm = 0;
+        if self.ammonia >= AMMONIA_TOXIC {
+            harm += 3;
+        } else if self.ammonia >= AMMONIA_WARNING {
+            harm += 1;
+        }
+        if self.nitrate >= NITRATE_WARNING {
+            harm += 1;
+        }
+        if self.chlorine >= CHLORINE_TOXIC {
+            harm += 2;
+        }
+        if self.level < LOW_LEVEL {
+            harm += 1;
+        }
+        if outside(self.temperature, species.temperature, 1.0) {
+            harm += 1;
+        }
+        if outside(self.ph, species.ph, 0.3) {
+            harm += 1;
+        }
+        harm
+    }
+
+    /// Remove `share` of the water and refill to full with tap water at
+    /// `temperature`. Draining leaves the concentrations as they were; the
+    /// refill dilutes them.
+    pub fn change(&mut self, share: f32, temperature: f32) {
+        let share = share.clamp(0.0, 1.0);
+        self.level *= 1.0 - share;
+        self.top_off(temperature);
+    }
+
+    /// Fill back up to full with tap water at `temperature`. The fresh
+    /// water dilutes everything and brings chlorine unless conditioner is
+    /// still working.
+    pub fn top_off(&mut self, temperature: f32) {
+        let fresh = (1.0 - self.level).clamp(0.0, 1.0);
+        let old = 1.0 - fresh;
+        self.temperature = self.temperature * old + temperature * fresh;
+        self.ph = self.ph * old + TAP_PH * fresh;
+        self.ammonia *= old;
+        self.nitrate *= old;
+        self.chlorine *= old;
+        if self.conditioner == 0 {
+            self.chlorine += TAP_CHLORINE * fresh;
+        }
+        self.level = 1.0;
+    }
+
+    /// Siphon `share` of the debris out of the gravel, taking `water` of
+    /// the tank with it.
+    pub fn vacuum(&mut self, share: f32, water: f32) {
+        self.debris *= 1.0 - share;
+        self.level -= self.level * water;
+    }
+
+    /// Rinse the filter in tank water: unclogged, but some bacteria wash
+    /// out.
+    pub fn rinse_filter(&mut self, bacteria_lost: f32) {
+        self.clog = 0.0;
+        self.bacteria *= 1.0 - bacteria_lost;
+    }
+
+    /// Neutralise chlorine now and in water added for a while, and bind
+    /// `ammonia_bound` of the ammonia.
+    pub fn condition(&mut self, ammonia_bound: f32) {
+        self.chlorine = 0.0;
+        self.ammonia *= 1.0 - ammonia_bound;
+        self.conditioner = CONDITIONER_TICKS;
+    }
+
+    /// Stress (0-100) for a fish moved from this water into `other`.
+    pub fn acclimation_stress(&self, other: &Water) -> u8 {
+        let temperature = (self.temperature - other.temperature).abs() * 10.0;
+        let ph = (self.ph - other.ph).abs() * 40.0;
+        (temperature + ph).round().min(100.0) as u8
+    }
+}
+
+fn approach(value: f32, target: f32, step: f32) -> f32 {
+    if (target - value).abs() <= step {
+        target
+    } else if target > value {
+        value + step
+    } else {
+        value - step
+    }
+}
+
+/// Whether `value` is more than `slack` outside `range`.
+fn outside(value: f32, range: (f32, f32), slack: f32) -> bool {
+    value < range.0 - slack || value > range.1 + slack
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::species;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn the_filter_turns_ammonia_into_nitrate() {
+        let mut water = Water {
+            ammonia: 1.0,
+            ..Water::default()
+        };
+        let equipment = Equipment::default();
+        water.update(&equipment, ROOM_TEMPERATURE, 0, 0);
+        assert!(water.ammonia < 1.0);
+        assert!(water.nitrate > 0.0);
+
+        let mut unfiltered = Water {
+            ammonia: 1.0,
+            ..Water::default()
+        };
+        let none = Equipment {
+            filter: 0.0,
+            ..equipment
+        };
+        unfiltered.update(&none, ROOM_TEMPERATURE, 0, 0);
+        assert!(unfiltered.ammonia > 1.0);
+        assert_eq!(unfiltered.nitrate, 0.0);
+    }
+
+    #[test]
+    fn an_uncycled_filter_converts_nothing() {
+        let mut water = Water {
+            ammonia: 1.0,
+            bacteria: 0.0,
+            ..Water::default()
+        };
+        water.update(&Equipment::default(), ROOM_TEMPERATURE, 0, 0);
+        assert!(water.nitrate < 0.001);
+        assert!(water.bacteria > 0.0);
+    }
+
+    #[test]
+    fn temperature_follows_the_heater_or_the_room() {
+        let mut heated = Water::default();
+        let mut unheated = Water::default();
+        let no_heater = Equipment {
+            heater: None,
+            ..Equipment::default()
+        };
+        for _ in 0..1000 {
+            heated.update(&Equipment::default(), ROOM_TEMPERATURE, 0, 0);
+            unheated.update(&no_heater, ROOM_TEMPERATURE, 0, 0);
+        }
+        assert!(close(heated.temperature, 25.0));
+        assert!(close(unheated.temperature, ROOM_TEMPERATURE));
+    }
+
+    #[test]
+    fn issues_are_reported_once() {
+        let mut water = Water {
+            ammonia: AMMONIA_TOXIC,
+            ..Water::default()
+        };
+        let none = Equipment {
+            filter: 0.0,
+            ..Equipment::default()
+        };
+        assert_eq!(water.update(&none, ROOM_TEMPERATURE, 0, 0), vec![WaterIssue::Ammonia]);
+        assert!(water.update(&none, ROOM_TEMPERATURE, 0, 0).is_empty());
+        assert_eq!(water.issues(), vec![WaterIssue::Ammonia]);
+    }
+
+    #[test]
+    fn harm_depends_on_the_species() {
+        let guppy = species::get_species("Guppy").unwrap();
+        assert_eq!(Water::default().harm(&guppy), 0);
+        let toxic = Water {
+            ammonia: AMMONIA_TOXIC,
+            ..Water::default()
+        };
+        assert_eq!(toxic.harm(&guppy), 3);
+        let cold = Water {
+            temperature: guppy.temperature.0 - 2.0,
+            ..Water::default()
+        };
+        assert_eq!(cold.harm(&guppy), 1);
+    }
+
+    #[test]
+    fn a_water_change_replaces_its_share() {
+        let mut water = Water {
+            ammonia: 0.8,
+            nitrate: 40.0,
+            conditioner: 1,
+            ..Water::default()
+        };
+        water.change(0.25, water.temperature);
+        assert!(close(water.nitrate, 30.0));
+        assert!(close(water.ammonia, 0.6));
+        assert_eq!(water.level, 1.0);
+        assert_eq!(water.chlorine, 0.0);
+    }
+
+    #[test]
+    fn shares_outside_the_tank_are_clamped() {
+        let mut water = Water {
+            nitrate: 40.0,
+            ..Water::default()
+        };
+        water.change(1.5, 20.0);
+        assert_eq!(water.nitrate, 0.0);
+        assert_eq!(water.temperature, 20.0);
+        assert!(close(water.chlorine, TAP_CHLORINE));
+
+        let mut water = Water {
+            nitrate: 40.0,
+            ..Water::default()
+        };
+        water.change(-0.5, 20.0);
+        assert_eq!(water.nitrate, 40.0);
+        assert_eq!(water.chlorine, 0.0);
+    }
+
+    #[test]
+    fn topping_off_dilutes_by_the_water_added() {
+        let mut water = Water {
+            nitrate: 40.0,
+            level: 0.9,
+            ..Water::default()
+        };
+        water.top_off(water.temperature);
+        assert!(close(water.nitrate, 36.0));
+        assert!(close(water.chlorine, TAP_CHLORINE * 0.1));
+    }
+
+    #[test]
+    fn moving_between_different_water_is_stressful() {
+        let water = Water::default();
+        assert_eq!(water.acclimation_stress(&water), 0);
+        let colder = Water {
+            temperature: water.temperature - 3.0,
+            ..Water::default()
+        };
+        assert_eq!(water.acclimation_stress(&colder), 30);
+    }
+}